@@ -0,0 +1,350 @@
+//! Splitting an output byte stream into text and escape sequences.
+//!
+//! This is the common ground of the writers that need to understand what passes through them.
+
+use std::io;
+use std::str;
+
+/// A piece of an output byte stream.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Token<'a> {
+    /// Text, including control characters other than ESC.
+    Text(&'a [u8]),
+    /// A complete escape sequence, including the leading ESC.
+    Sequence(&'a [u8]),
+}
+
+/// The state of a sequence being read.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum State {
+    /// Reading text.
+    Ground,
+    /// Read ESC.
+    Escape,
+    /// Reading the intermediate bytes of an ESC sequence.
+    EscapeIntermediate,
+    /// Reading a CSI sequence.
+    Csi,
+    /// Reading a string sequence (OSC, DCS, APC, PM or SOS).
+    String,
+    /// Read ESC in a string sequence, possibly starting its terminator.
+    StringEscape,
+}
+
+/// A streaming splitter, which handles sequences split across several writes.
+pub struct Splitter {
+    state: State,
+    /// The bytes of the sequence being read.
+    pending: Vec<u8>,
+}
+
+impl Splitter {
+    /// Create a splitter, expecting text.
+    pub fn new() -> Splitter {
+        Splitter {
+            state: State::Ground,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Forget the incomplete sequence held back so far, returning its bytes.
+    pub fn take_pending(&mut self) -> Vec<u8> {
+        self.state = State::Ground;
+        ::std::mem::take(&mut self.pending)
+    }
+
+    /// Split `buf` into tokens, passed to `f` in order.
+    ///
+    /// An incomplete sequence at the end of `buf` is held back until it is completed by a later
+    /// call.
+    pub fn split<F>(&mut self, buf: &[u8], mut f: F) -> io::Result<()>
+        where F: FnMut(Token) -> io::Result<()>
+    {
+        let mut text_start = 0;
+        let mut i = 0;
+
+        while i < buf.len() {
+            let byte = buf[i];
+
+            if self.state == State::Ground {
+                if byte == b'\x1B' {
+                    if text_start < i {
+                        f(Token::Text(&buf[text_start..i]))?;
+                    }
+                    self.pending.push(byte);
+                    self.state = State::Escape;
+                }
+                i += 1;
+                continue;
+            }
+
+            match (self.state, byte) {
+                // A new ESC aborts the current sequence, except when it may terminate a string.
+                (State::Escape, b'\x1B') |
+                (State::EscapeIntermediate, b'\x1B') |
+                (State::Csi, b'\x1B') => {
+                    f(Token::Sequence(&self.pending))?;
+                    self.pending.clear();
+                    self.pending.push(byte);
+                    self.state = State::Escape;
+                }
+                (State::Escape, b'[') => {
+                    self.pending.push(byte);
+                    self.state = State::Csi;
+                }
+                (State::Escape, b']') |
+                (State::Escape, b'P') |
+                (State::Escape, b'_') |
+                (State::Escape, b'^') |
+                (State::Escape, b'X') => {
+                    self.pending.push(byte);
+                    self.state = State::String;
+                }
+                (State::Escape, 0x20..=0x2F) |
+                (State::EscapeIntermediate, 0x20..=0x2F) |
+                (State::Csi, 0x20..=0x3F) => {
+                    self.pending.push(byte);
+                    if self.state == State::Escape {
+                        self.state = State::EscapeIntermediate;
+                    }
+                }
+                (State::Escape, 0x30..=0x7E) |
+                (State::EscapeIntermediate, 0x30..=0x7E) |
+                (State::Csi, 0x40..=0x7E) => {
+                    self.pending.push(byte);
+                    self.finish(&mut f)?;
+                }
+                (State::String, b'\x07') => {
+                    self.pending.push(byte);
+                    self.finish(&mut f)?;
+                }
+                (State::String, b'\x1B') => {
+                    self.pending.push(byte);
+                    self.state = State::StringEscape;
+                }
+                (State::String, _) => self.pending.push(byte),
+                (State::StringEscape, b'\\') => {
+                    self.pending.push(byte);
+                    self.finish(&mut f)?;
+                }
+                (State::StringEscape, _) => {
+                    // Not a terminator after all: the string was aborted by a new sequence.
+                    self.pending.pop();
+                    f(Token::Sequence(&self.pending))?;
+                    self.pending.clear();
+                    self.pending.push(b'\x1B');
+                    self.state = State::Escape;
+                    // Reconsider this byte as the one following the new ESC.
+                    continue;
+                }
+                _ => {
+                    // A malformed sequence: emit what we got, and reconsider the byte as text.
+                    self.finish(&mut f)?;
+                    text_start = i;
+                    continue;
+                }
+            }
+
+            i += 1;
+            text_start = i;
+        }
+
+        if self.state == State::Ground && text_start < buf.len() {
+            f(Token::Text(&buf[text_start..]))?;
+        }
+
+        Ok(())
+    }
+
+    /// Emit the pending sequence and go back to reading text.
+    fn finish<F>(&mut self, f: &mut F) -> io::Result<()>
+        where F: FnMut(Token) -> io::Result<()>
+    {
+        self.state = State::Ground;
+        let res = f(Token::Sequence(&self.pending));
+        self.pending.clear();
+        res
+    }
+}
+
+/// A parsed CSI sequence.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Csi {
+    /// The private marker (`?`, `>`, `<` or `=`) preceding the parameters, if any.
+    pub private: Option<u8>,
+    /// The numeric parameters, with omitted ones given as 0.
+    pub params: Vec<u16>,
+    /// Is any parameter split into sub-parameters by `:`?
+    pub subparams: bool,
+    /// The intermediate bytes.
+    pub intermediates: Vec<u8>,
+    /// The final byte.
+    pub final_byte: u8,
+}
+
+impl Csi {
+    /// Parse a complete CSI sequence, as given by `Token::Sequence`.
+    pub fn parse(seq: &[u8]) -> Option<Csi> {
+        if seq.len() < 3 || &seq[..2] != b"\x1B[" {
+            return None;
+        }
+
+        let body = &seq[2..seq.len() - 1];
+        let final_byte = seq[seq.len() - 1];
+        if !(0x40..=0x7E).contains(&final_byte) {
+            return None;
+        }
+
+        let (private, body) = match body.first() {
+            Some(&c @ b'<'..=b'?') => (Some(c), &body[1..]),
+            _ => (None, body),
+        };
+
+        let split = body.iter().position(|&c| (0x20..=0x2F).contains(&c)).unwrap_or(body.len());
+        let (params, intermediates) = body.split_at(split);
+
+        let mut csi = Csi {
+            private,
+            params: Vec::new(),
+            subparams: false,
+            intermediates: intermediates.to_vec(),
+            final_byte,
+        };
+
+        if !params.is_empty() {
+            for param in params.split(|&c| c == b';') {
+                let mut value: u16 = 0;
+                for &c in param {
+                    match c {
+                        b'0'..=b'9' => {
+                            value = value.saturating_mul(10).saturating_add((c - b'0') as u16)
+                        }
+                        b':' => csi.subparams = true,
+                        _ => return None,
+                    }
+                }
+                csi.params.push(value);
+            }
+        }
+
+        Some(csi)
+    }
+}
+
+/// A streaming UTF-8 decoder for the text tokens, which handles characters split across several
+/// writes.
+pub struct Utf8Decoder {
+    /// The start of a character split across writes.
+    partial: Vec<u8>,
+}
+
+impl Utf8Decoder {
+    /// Create a decoder, expecting the start of a character.
+    pub fn new() -> Utf8Decoder {
+        Utf8Decoder { partial: Vec::new() }
+    }
+
+    /// Decode `text` into characters, passed to `f` in order.
+    ///
+    /// An invalid byte is decoded as a replacement character, and an incomplete character at
+    /// the end of `text` is held back until it is completed by a later call.
+    pub fn decode<F: FnMut(char)>(&mut self, text: &[u8], mut f: F) {
+        let mut buf = ::std::mem::take(&mut self.partial);
+        buf.extend_from_slice(text);
+
+        let mut rest = &buf[..];
+        while !rest.is_empty() {
+            let (valid, consumed) = match str::from_utf8(rest) {
+                Ok(s) => (s, rest.len()),
+                Err(e) => {
+                    let valid = str::from_utf8(&rest[..e.valid_up_to()]).unwrap();
+                    match e.error_len() {
+                        Some(len) => (valid, e.valid_up_to() + len),
+                        None => {
+                            self.partial = rest[e.valid_up_to()..].to_vec();
+                            (valid, rest.len())
+                        }
+                    }
+                }
+            };
+            valid.chars().for_each(&mut f);
+            if consumed > valid.len() && self.partial.is_empty() {
+                f('\u{FFFD}');
+            }
+            rest = &rest[consumed..];
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn split_all(chunks: &[&[u8]]) -> Vec<(bool, Vec<u8>)> {
+        let mut splitter = Splitter::new();
+        let mut tokens = Vec::new();
+        for chunk in chunks {
+            splitter.split(chunk, |token| {
+                    tokens.push(match token {
+                        Token::Text(t) => (false, t.to_vec()),
+                        Token::Sequence(s) => (true, s.to_vec()),
+                    });
+                    Ok(())
+                })
+                .unwrap();
+        }
+        tokens
+    }
+
+    #[test]
+    fn test_split() {
+        let tokens = split_all(&[b"ab\x1B[1;2Hc\x1B(0q\x1B]0;title\x07\x1BP>|x\x1B\\\x1B7"]);
+        assert_eq!(tokens,
+                   vec![(false, b"ab".to_vec()),
+                        (true, b"\x1B[1;2H".to_vec()),
+                        (false, b"c".to_vec()),
+                        (true, b"\x1B(0".to_vec()),
+                        (false, b"q".to_vec()),
+                        (true, b"\x1B]0;title\x07".to_vec()),
+                        (true, b"\x1BP>|x\x1B\\".to_vec()),
+                        (true, b"\x1B7".to_vec())]);
+    }
+
+    #[test]
+    fn test_split_across_writes() {
+        let tokens = split_all(&[b"a\x1B", b"[3", b"8;5;1mb\x1B]4;1;?", b"\x1B", b"\\"]);
+        assert_eq!(tokens,
+                   vec![(false, b"a".to_vec()),
+                        (true, b"\x1B[38;5;1m".to_vec()),
+                        (false, b"b".to_vec()),
+                        (true, b"\x1B]4;1;?\x1B\\".to_vec())]);
+    }
+
+    #[test]
+    fn test_split_malformed() {
+        let tokens = split_all(&[b"\x1B[1\n2\x1B\x1B[A"]);
+        assert_eq!(tokens,
+                   vec![(true, b"\x1B[1".to_vec()),
+                        (false, b"\n2".to_vec()),
+                        (true, b"\x1B".to_vec()),
+                        (true, b"\x1B[A".to_vec())]);
+    }
+
+    #[test]
+    fn test_parse_csi() {
+        let csi = Csi::parse(b"\x1B[?1049h").unwrap();
+        assert_eq!(csi.private, Some(b'?'));
+        assert_eq!(csi.params, vec![1049]);
+        assert_eq!(csi.final_byte, b'h');
+
+        let csi = Csi::parse(b"\x1B[;5H").unwrap();
+        assert_eq!(csi.params, vec![0, 5]);
+
+        let csi = Csi::parse(b"\x1B[2 q").unwrap();
+        assert_eq!(csi.intermediates, b" ".to_vec());
+        assert_eq!(csi.final_byte, b'q');
+
+        assert!(Csi::parse(b"\x1B[38:2::1:2:3m").unwrap().subparams);
+        assert!(Csi::parse(b"\x1B(0").is_none());
+    }
+}
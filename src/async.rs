@@ -1,7 +1,10 @@
 use std::io::{self, Read};
 use std::sync::mpsc;
+use std::time::Duration;
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 use std::thread;
 
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 use sys::tty::get_tty;
 
 /// Construct an asynchronous handle to the TTY standard input.
@@ -14,16 +17,34 @@ use sys::tty::get_tty;
 /// asyncronized from piped input would rarely make sense. In other words, if you pipe standard
 /// output from another process, it won't be reflected in the stream returned by this function, as
 /// this represents the TTY device, and not the piped standard input.
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
 pub fn async_stdin() -> AsyncReader {
     let (send, recv) = mpsc::channel();
 
-    thread::spawn(move || for i in get_tty().unwrap().bytes() {
-                      if send.send(i).is_err() {
-                          return;
-                      }
-                  });
+    thread::spawn(move || {
+        let mut tty = get_tty().unwrap();
+        let mut buf = [0u8; 1024];
+        loop {
+            let sent = match tty.read(&mut buf) {
+                Ok(0) => return,
+                Ok(n) => buf[..n].iter().all(|&b| send.send(Ok(b)).is_ok()),
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => true,
+                Err(e) => send.send(Err(e)).is_ok(),
+            };
+            if !sent {
+                return;
+            }
+        }
+    });
 
-    AsyncReader { recv: recv }
+    AsyncReader::from_receiver(recv)
+}
+
+/// Construct an asynchronous handle to the input fed from the JavaScript side (see the `wasm`
+/// module).
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub fn async_stdin() -> AsyncReader {
+    AsyncReader::from_receiver(::sys::subscribe_input())
 }
 
 /// An asynchronous reader.
@@ -33,6 +54,40 @@ pub fn async_stdin() -> AsyncReader {
 pub struct AsyncReader {
     /// The underlying mpsc receiver.
     recv: mpsc::Receiver<io::Result<u8>>,
+    /// The byte received while waiting for input, to be read first.
+    peeked: Option<io::Result<u8>>,
+}
+
+impl AsyncReader {
+    /// Read the bytes sent to `recv`.
+    pub(crate) fn from_receiver(recv: mpsc::Receiver<io::Result<u8>>) -> AsyncReader {
+        AsyncReader {
+            recv,
+            peeked: None,
+        }
+    }
+
+    /// Block until input is available or `timeout` has passed, returning whether input is
+    /// available.
+    ///
+    /// Once the input has ended, this waits for the whole timeout.
+    pub fn wait_timeout(&mut self, timeout: Duration) -> bool {
+        if self.peeked.is_some() {
+            return true;
+        }
+
+        match self.recv.recv_timeout(timeout) {
+            Ok(b) => {
+                self.peeked = Some(b);
+                true
+            }
+            Err(mpsc::RecvTimeoutError::Timeout) => false,
+            Err(mpsc::RecvTimeoutError::Disconnected) => {
+                ::std::thread::sleep(timeout);
+                false
+            }
+        }
+    }
 }
 
 // FIXME: Allow constructing an async reader from an arbitrary stream.
@@ -46,6 +101,13 @@ impl Read for AsyncReader {
     fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
         let mut total = 0;
 
+        if !buf.is_empty() {
+            if let Some(b) = self.peeked.take() {
+                buf[0] = b?;
+                total = 1;
+            }
+        }
+
         loop {
             if total >= buf.len() {
                 break;
@@ -0,0 +1,88 @@
+//! Alerting the user with the bell.
+//!
+//! The audible bell (BEL) may be muted, or be of no help to users who can't hear it. The visual
+//! bell flashes the screen instead, by briefly reversing its colors (DECSCNM). `bell` rings
+//! either or both, as the application or its user prefers.
+//!
+//! # Example
+//!
+#![cfg_attr(feature = "std", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "std"), doc = "```rust,ignore")]
+//! use termion::bell::{bell, BellStyle};
+//! use std::io::stdout;
+//!
+//! bell(&mut stdout(), BellStyle::Visual).unwrap();
+//! ```
+
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::thread;
+#[cfg(feature = "std")]
+use std::time::Duration;
+use std::fmt;
+
+#[cfg(feature = "std")]
+use screen::{NormalScreen, ReverseScreen};
+
+/// Ring the audible bell (BEL).
+#[derive(Copy, Clone)]
+pub struct Bell;
+
+impl fmt::Display for Bell {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\x07")
+    }
+}
+
+/// How the bell alerts the user.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum BellStyle {
+    /// Ring the audible bell, which the terminal may also show in its own way.
+    #[default]
+    Audible,
+    /// Flash the screen.
+    Visual,
+    /// Ring the audible bell and flash the screen.
+    Both,
+    /// Don't alert the user.
+    Off,
+}
+
+/// How long the visual bell reverses the screen.
+#[cfg(feature = "std")]
+pub const VISUAL_BELL_DURATION: Duration = Duration::from_millis(100);
+
+/// Alert the user through `output`, in the given style.
+///
+/// The visual bell blocks for `VISUAL_BELL_DURATION`, while the screen is reversed.
+#[cfg(feature = "std")]
+pub fn bell<W: Write>(output: &mut W, style: BellStyle) -> io::Result<()> {
+    if style == BellStyle::Audible || style == BellStyle::Both {
+        write!(output, "{}", Bell)?;
+    }
+    if style == BellStyle::Visual || style == BellStyle::Both {
+        write!(output, "{}", ReverseScreen)?;
+        output.flush()?;
+        thread::sleep(VISUAL_BELL_DURATION);
+        write!(output, "{}", NormalScreen)?;
+    }
+    output.flush()
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_bell() {
+        let mut out = Vec::new();
+        bell(&mut out, BellStyle::Audible).unwrap();
+        bell(&mut out, BellStyle::Off).unwrap();
+        assert_eq!(out, b"\x07");
+
+        let mut out = Vec::new();
+        bell(&mut out, BellStyle::Both).unwrap();
+        assert_eq!(out, b"\x07\x1B[?5h\x1B[?5l");
+    }
+}
@@ -0,0 +1,310 @@
+//! Drawing boxes and borders.
+//!
+//! Box-drawing characters are written either as Unicode, through the DEC special graphics
+//! character set, or as plain ASCII, depending on what the terminal is able to display.
+//!
+//! # Example
+//!
+#![cfg_attr(feature = "std", doc = "```rust")]
+#![cfg_attr(not(feature = "std"), doc = "```rust,ignore")]
+//! extern crate termion;
+//!
+//! use termion::box_drawing::{BoxChars, Part, Style};
+//!
+//! fn main() {
+//!     let chars = BoxChars::detect(Style::Rounded);
+//!
+//!     println!("{}{}{}",
+//!              chars.get(Part::TopLeft),
+//!              chars.repeat(Part::Horizontal, 10),
+//!              chars.get(Part::TopRight));
+//! }
+//! ```
+
+#[cfg(feature = "std")]
+use std::env;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+
+use charset;
+#[cfg(feature = "std")]
+use cursor;
+use width;
+
+/// The style of the lines.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Style {
+    /// Thin single lines.
+    Single,
+    /// Double lines.
+    Double,
+    /// Thin single lines with rounded corners.
+    Rounded,
+}
+
+/// The way box-drawing characters are encoded.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Encoding {
+    /// Unicode box-drawing characters.
+    ///
+    /// These are of ambiguous width, so they are replaced by ASCII when the characters of
+    /// ambiguous width take two columns (see `width::set_ambiguous_width`).
+    Unicode,
+    /// The DEC special graphics character set.
+    ///
+    /// This only supports single lines, so the other styles fall back to them.
+    DecGraphics,
+    /// Plain ASCII approximations.
+    Ascii,
+}
+
+#[cfg(feature = "std")]
+impl Encoding {
+    /// Guess the best encoding supported by the terminal.
+    ///
+    /// Beware: the information given isn't authoritative, it's inferred from the locale and the
+    /// value of `TERM`.
+    pub fn detect() -> Encoding {
+        match env::var("TERM") {
+            Ok(ref term) if term != "dumb" => {
+                if locale_is_utf8() {
+                    Encoding::Unicode
+                } else {
+                    Encoding::DecGraphics
+                }
+            }
+            _ => Encoding::Ascii,
+        }
+    }
+}
+
+/// Does the locale of the process use UTF-8?
+#[cfg(feature = "std")]
+fn locale_is_utf8() -> bool {
+    for var in &["LC_ALL", "LC_CTYPE", "LANG"] {
+        if let Ok(val) = env::var(var) {
+            if !val.is_empty() {
+                let val = val.to_lowercase();
+                return val.contains("utf-8") || val.contains("utf8");
+            }
+        }
+    }
+
+    false
+}
+
+/// A part of a box.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Part {
+    /// A horizontal line.
+    Horizontal,
+    /// A vertical line.
+    Vertical,
+    /// The upper left corner.
+    TopLeft,
+    /// The upper right corner.
+    TopRight,
+    /// The lower left corner.
+    BottomLeft,
+    /// The lower right corner.
+    BottomRight,
+    /// A vertical line with a branch to the right (`├`).
+    LeftTee,
+    /// A vertical line with a branch to the left (`┤`).
+    RightTee,
+    /// A horizontal line with a branch downwards (`┬`).
+    TopTee,
+    /// A horizontal line with a branch upwards (`┴`).
+    BottomTee,
+    /// Crossing lines.
+    Cross,
+}
+
+/// The characters of every part, in the order of `Part`.
+const UNICODE_SINGLE: [&str; 11] = ["─", "│", "┌", "┐", "└", "┘", "├", "┤", "┬", "┴", "┼"];
+const UNICODE_DOUBLE: [&str; 11] = ["═", "║", "╔", "╗", "╚", "╝", "╠", "╣", "╦", "╩", "╬"];
+const UNICODE_ROUNDED: [&str; 11] = ["─", "│", "╭", "╮", "╰", "╯", "├", "┤", "┬", "┴", "┼"];
+const DEC_GRAPHICS: [&str; 11] = ["q", "x", "l", "k", "m", "j", "t", "u", "w", "v", "n"];
+const ASCII_SINGLE: [&str; 11] = ["-", "|", "+", "+", "+", "+", "+", "+", "+", "+", "+"];
+const ASCII_DOUBLE: [&str; 11] = ["=", "|", "+", "+", "+", "+", "+", "+", "+", "+", "+"];
+
+/// A set of box-drawing characters of some style and encoding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BoxChars {
+    style: Style,
+    encoding: Encoding,
+}
+
+impl BoxChars {
+    /// Box-drawing characters of the given style and encoding.
+    pub fn new(style: Style, encoding: Encoding) -> BoxChars {
+        BoxChars { style, encoding }
+    }
+
+    /// Box-drawing characters of the given style, in the best encoding supported by the terminal.
+    #[cfg(feature = "std")]
+    pub fn detect(style: Style) -> BoxChars {
+        BoxChars::new(style, Encoding::detect())
+    }
+
+    /// The style of the lines.
+    pub fn style(&self) -> Style {
+        self.style
+    }
+
+    /// The encoding of the characters.
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// A single part of a box.
+    pub fn get(&self, part: Part) -> Glyph {
+        self.repeat(part, 1)
+    }
+
+    /// A part of a box, repeated `count` times.
+    ///
+    /// This is cheaper than repeating `get`, as the character set is switched only once.
+    pub fn repeat(&self, part: Part, count: u16) -> Glyph {
+        self.repeat_with(part, count, width::ambiguous_width())
+    }
+
+    /// `repeat`, if the characters of ambiguous width take `ambiguous` columns.
+    fn repeat_with(&self, part: Part, count: u16, ambiguous: usize) -> Glyph {
+        let encoding = match self.encoding {
+            Encoding::Unicode if ambiguous > 1 => Encoding::Ascii,
+            encoding => encoding,
+        };
+        let table = match (encoding, self.style) {
+            (Encoding::Unicode, Style::Single) => &UNICODE_SINGLE,
+            (Encoding::Unicode, Style::Double) => &UNICODE_DOUBLE,
+            (Encoding::Unicode, Style::Rounded) => &UNICODE_ROUNDED,
+            (Encoding::DecGraphics, _) => &DEC_GRAPHICS,
+            (Encoding::Ascii, Style::Double) => &ASCII_DOUBLE,
+            (Encoding::Ascii, _) => &ASCII_SINGLE,
+        };
+
+        Glyph {
+            ch: table[part as usize],
+            count,
+            dec: encoding == Encoding::DecGraphics,
+        }
+    }
+
+    /// Draw the outline of a box of `width` × `height` cells, with its upper left corner at the
+    /// (1,1)-based position (`x`, `y`).
+    ///
+    /// A box reaching past the last column or row the positions can address is cut there.
+    #[cfg(feature = "std")]
+    pub fn draw<W: Write>(&self, out: &mut W, x: u16, y: u16, width: u16, height: u16)
+                          -> io::Result<()> {
+        if width < 2 || height < 2 {
+            return Ok(());
+        }
+        let right = x.saturating_add(width - 1);
+        let bottom = y.saturating_add(height - 1);
+        if right == x || bottom == y {
+            return Ok(());
+        }
+
+        write!(out,
+               "{}{}{}{}",
+               cursor::Goto(x, y),
+               self.get(Part::TopLeft),
+               self.repeat(Part::Horizontal, right - x - 1),
+               self.get(Part::TopRight))?;
+        for row in y + 1..bottom {
+            write!(out,
+                   "{}{}{}{}",
+                   cursor::Goto(x, row),
+                   self.get(Part::Vertical),
+                   cursor::Goto(right, row),
+                   self.get(Part::Vertical))?;
+        }
+        write!(out,
+               "{}{}{}{}",
+               cursor::Goto(x, bottom),
+               self.get(Part::BottomLeft),
+               self.repeat(Part::Horizontal, right - x - 1),
+               self.get(Part::BottomRight))
+    }
+}
+
+/// A box-drawing character, possibly repeated.
+///
+/// When displayed, this switches to the DEC special graphics character set if needed, and back
+/// again afterwards.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Glyph {
+    ch: &'static str,
+    count: u16,
+    dec: bool,
+}
+
+impl fmt::Display for Glyph {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.count == 0 {
+            return Ok(());
+        }
+
+        if self.dec {
+            write!(f, "{}", charset::DecGraphics)?;
+        }
+        for _ in 0..self.count {
+            f.write_str(self.ch)?;
+        }
+        if self.dec {
+            write!(f, "{}", charset::Ascii)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unicode() {
+        let chars = BoxChars::new(Style::Double, Encoding::Unicode);
+        assert_eq!(chars.get(Part::TopLeft).to_string(), "╔");
+        assert_eq!(chars.repeat(Part::Horizontal, 3).to_string(), "═══");
+        assert_eq!(chars.repeat(Part::Horizontal, 0).to_string(), "");
+
+        let chars = BoxChars::new(Style::Rounded, Encoding::Unicode);
+        assert_eq!(chars.get(Part::BottomRight).to_string(), "╯");
+        assert_eq!(chars.get(Part::Cross).to_string(), "┼");
+
+        // Where they would take two columns, ASCII is used.
+        let chars = BoxChars::new(Style::Double, Encoding::Unicode);
+        assert_eq!(chars.repeat_with(Part::Horizontal, 3, 2).to_string(), "===");
+        assert_eq!(chars.repeat_with(Part::TopLeft, 1, 2).to_string(), "+");
+    }
+
+    #[test]
+    fn test_dec_graphics() {
+        let chars = BoxChars::new(Style::Rounded, Encoding::DecGraphics);
+        assert_eq!(chars.get(Part::TopLeft).to_string(), "\x1B(0l\x1B(B");
+        assert_eq!(chars.repeat(Part::Vertical, 2).to_string(), "\x1B(0xx\x1B(B");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_draw() {
+        let mut out = Vec::new();
+        BoxChars::new(Style::Single, Encoding::Ascii).draw(&mut out, 2, 1, 4, 3).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "\x1B[1;2H+--+\x1B[2;2H|\x1B[2;5H|\x1B[3;2H+--+");
+
+        let mut out = Vec::new();
+        let chars = BoxChars::new(Style::Single, Encoding::Ascii);
+        chars.draw(&mut out, u16::MAX - 2, u16::MAX - 1, 10, 10).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "\x1B[65534;65533H+-+\x1B[65535;65533H+-+");
+        let mut out = Vec::new();
+        chars.draw(&mut out, u16::MAX, 1, 10, 10).unwrap();
+        assert!(out.is_empty());
+    }
+}
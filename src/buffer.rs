@@ -0,0 +1,769 @@
+//! A buffer of styled cells, drawn by writing only what changed.
+//!
+//! Draw into a `Buffer`, then `flush` it to the terminal: the cells are compared to those of the
+//! previous flush, and only the ones that differ are written. The buffer also records which
+//! areas were drawn into since the last flush, so that rows nothing was drawn into are not even
+//! compared, and so that the areas can be used to re-render only what overlaps them.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::buffer::{Buffer, CellColor, CellStyle};
+//! use termion::raw::IntoRawMode;
+//! use std::io::stdout;
+//!
+//! let (width, height) = termion::terminal_size().unwrap();
+//! let mut buffer = Buffer::new(width, height);
+//! let mut stdout = stdout().into_raw_mode().unwrap();
+//!
+//! let style = CellStyle { fg: CellColor::Ansi(1), bold: true, ..CellStyle::default() };
+//! buffer.set_str(1, 1, "Hello", style);
+//! buffer.flush(&mut stdout).unwrap();
+//!
+//! // Only the changed characters are written.
+//! buffer.set_str(1, 1, "Help!", style);
+//! buffer.flush(&mut stdout).unwrap();
+//! ```
+
+use std::fmt;
+use std::io::{self, Write};
+use color::{Color, Rgb};
+use cursor;
+use rect::Rect;
+use styled::StyledStr;
+use width::{grapheme_width, graphemes, str_width};
+
+/// The color of a cell.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum CellColor {
+    /// The terminal's default color.
+    #[default]
+    Reset,
+    /// A color of the 256-color palette.
+    Ansi(u8),
+    /// A true color.
+    Rgb(Rgb),
+}
+
+impl Color for CellColor {
+    fn write_fg(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, csi!("{}m"), color_param(*self, 30))
+    }
+
+    fn write_bg(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, csi!("{}m"), color_param(*self, 40))
+    }
+}
+
+/// How the symbol of a cell is drawn.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct CellStyle {
+    /// The foreground color.
+    pub fg: CellColor,
+    /// The background color.
+    pub bg: CellColor,
+    /// Bold text.
+    pub bold: bool,
+    /// Faint text.
+    pub faint: bool,
+    /// Italic text.
+    pub italic: bool,
+    /// Underlined text.
+    pub underline: bool,
+    /// Blinking text.
+    pub blink: bool,
+    /// Inverted colors.
+    pub invert: bool,
+    /// Crossed out text.
+    pub crossed_out: bool,
+}
+
+/// The SGR parameters setting and unsetting each attribute, in the order of `CellStyle`.
+const ATTRIBUTES: [(&str, &str); 7] = [("1", "22"),
+                                       ("2", "22"),
+                                       ("3", "23"),
+                                       ("4", "24"),
+                                       ("5", "25"),
+                                       ("7", "27"),
+                                       ("9", "29")];
+
+impl CellStyle {
+    pub(crate) fn attributes(&self) -> [bool; 7] {
+        [self.bold,
+         self.faint,
+         self.italic,
+         self.underline,
+         self.blink,
+         self.invert,
+         self.crossed_out]
+    }
+
+    /// Write the SGR sequence changing the rendition from `from` to this style, or from an
+    /// unknown rendition if `from` is `None`.
+    ///
+    /// Only the attributes and colors which differ are changed, unless resetting the rendition
+    /// and setting this style is shorter.
+    pub(crate) fn write_sgr(&self, from: Option<&CellStyle>, out: &mut Vec<u8>) {
+        let mut full = vec!["0".to_string()];
+        for (&set, &(on, _)) in self.attributes().iter().zip(&ATTRIBUTES) {
+            if set {
+                full.push(on.to_string());
+            }
+        }
+        for &(color, base) in &[(self.fg, 30), (self.bg, 40)] {
+            if color != CellColor::Reset {
+                full.push(color_param(color, base));
+            }
+        }
+
+        let params = match from {
+            Some(from) => {
+                let diff = self.diff_params(from);
+                if diff.is_empty() {
+                    return;
+                }
+                if diff.join(";").len() < full.join(";").len() {
+                    diff
+                } else {
+                    full
+                }
+            }
+            None => full,
+        };
+        let _ = write!(out, "\x1B[{}m", params.join(";"));
+    }
+
+    /// Apply the parameters of an SGR sequence, including the `:`-separated forms of the
+    /// extended colors.
+    pub(crate) fn apply_sgr(&mut self, params: &[u8]) {
+        let groups: Vec<Vec<u16>> = params.split(|&c| c == b';')
+            .map(|group| group.split(|&c| c == b':').map(parse_param).collect())
+            .collect();
+
+        let mut i = 0;
+        while i < groups.len() {
+            let group = &groups[i];
+            i += 1;
+            let style = &mut *self;
+            match group[0] {
+                0 => *style = CellStyle::default(),
+                1 => style.bold = true,
+                2 => style.faint = true,
+                3 => style.italic = true,
+                4 => style.underline = group.get(1).cloned() != Some(0),
+                5 | 6 => style.blink = true,
+                7 => style.invert = true,
+                9 => style.crossed_out = true,
+                21 => style.underline = true,
+                22 => {
+                    style.bold = false;
+                    style.faint = false;
+                }
+                23 => style.italic = false,
+                24 => style.underline = false,
+                25 => style.blink = false,
+                27 => style.invert = false,
+                29 => style.crossed_out = false,
+                n @ 30..=37 => style.fg = CellColor::Ansi(n as u8 - 30),
+                n @ 40..=47 => style.bg = CellColor::Ansi(n as u8 - 40),
+                n @ 90..=97 => style.fg = CellColor::Ansi(n as u8 - 90 + 8),
+                n @ 100..=107 => style.bg = CellColor::Ansi(n as u8 - 100 + 8),
+                39 => style.fg = CellColor::Reset,
+                49 => style.bg = CellColor::Reset,
+                n @ 38 | n @ 48 => {
+                    let color = if group.len() > 1 {
+                        extended_color(&group[1..])
+                    } else {
+                        // The `;`-separated form takes the following parameters.
+                        let rest: Vec<u16> = groups[i..].iter().map(|group| group[0]).collect();
+                        let used = match rest.first() {
+                            Some(&5) => 2,
+                            Some(&2) => 4,
+                            _ => rest.len(),
+                        };
+                        i += used.min(rest.len());
+                        extended_color(&rest[..used.min(rest.len())])
+                    };
+                    if let Some(color) = color {
+                        if n == 38 {
+                            style.fg = color;
+                        } else {
+                            style.bg = color;
+                        }
+                    }
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// The SGR parameters changing the rendition from `from` to this style.
+    fn diff_params(&self, from: &CellStyle) -> Vec<String> {
+        let (old, new) = (from.attributes(), self.attributes());
+        let mut params = Vec::new();
+
+        // Bold and faint are unset together, so one may have to be set again.
+        let intensity_unset = (old[0] && !new[0]) || (old[1] && !new[1]);
+        if intensity_unset {
+            params.push("22".to_string());
+        }
+        for (i, &(on, off)) in ATTRIBUTES.iter().enumerate() {
+            let was_set = old[i] && !(i < 2 && intensity_unset);
+            if new[i] && !was_set {
+                params.push(on.to_string());
+            } else if !new[i] && old[i] && i >= 2 {
+                params.push(off.to_string());
+            }
+        }
+
+        for &(old, new, base) in &[(from.fg, self.fg, 30), (from.bg, self.bg, 40)] {
+            if old != new {
+                params.push(color_param(new, base));
+            }
+        }
+
+        params
+    }
+}
+
+/// The SGR parameter setting `color`, in the foreground if `base` is 30 or the background if 40.
+fn color_param(color: CellColor, base: u8) -> String {
+    match color {
+        CellColor::Reset => format!("{}", base + 9),
+        CellColor::Ansi(n) => format!("{};5;{}", base + 8, n),
+        CellColor::Rgb(Rgb(r, g, b)) => format!("{};2;{};{};{}", base + 8, r, g, b),
+    }
+}
+
+/// Parse a parameter of a sequence, taking an omitted one as 0.
+fn parse_param(param: &[u8]) -> u16 {
+    param.iter().fold(0u16, |value, &c| {
+        if c.is_ascii_digit() {
+            value.saturating_mul(10).saturating_add((c - b'0') as u16)
+        } else {
+            value
+        }
+    })
+}
+
+/// The color of the parameters following 38 or 48: `5;n` or `2;r;g;b`.
+///
+/// The `:`-separated true color form may also hold a color space before the components.
+fn extended_color(params: &[u16]) -> Option<CellColor> {
+    match params {
+        [5, n, ..] => Some(CellColor::Ansi((*n).min(255) as u8)),
+        [2, _, r, g, b, ..] | [2, r, g, b] => {
+            Some(CellColor::Rgb(Rgb((*r).min(255) as u8, (*g).min(255) as u8, (*b).min(255) as u8)))
+        }
+        _ => None,
+    }
+}
+
+/// A cell of the buffer.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Cell {
+    /// The grapheme cluster drawn in the cell: a character, followed by the combining marks
+    /// drawn over it, or an emoji sequence.
+    ///
+    /// The cell following a wide character has an empty symbol, as it is covered by the wide
+    /// character.
+    pub symbol: String,
+    /// How the symbol is drawn.
+    pub style: CellStyle,
+}
+
+impl Cell {
+    /// A cell drawing `c` with `style`.
+    pub fn new(c: char, style: CellStyle) -> Cell {
+        Cell {
+            symbol: c.to_string(),
+            style,
+        }
+    }
+
+    /// A cell drawing the grapheme cluster `grapheme` with `style`, such as an emoji made of
+    /// several characters.
+    pub fn from_grapheme(grapheme: &str, style: CellStyle) -> Cell {
+        Cell {
+            symbol: grapheme.to_string(),
+            style,
+        }
+    }
+
+    /// The number of columns the symbol takes.
+    pub(crate) fn width(&self) -> u16 {
+        if self.symbol.is_empty() {
+            0
+        } else {
+            str_width(&self.symbol).clamp(1, 2) as u16
+        }
+    }
+}
+
+impl Default for Cell {
+    /// A blank cell.
+    fn default() -> Cell {
+        Cell::new(' ', CellStyle::default())
+    }
+}
+
+/// Past this number of dirty regions, they are merged into their bounding area.
+const MAX_DIRTY_REGIONS: usize = 32;
+
+/// A buffer of cells, with (1,1)-based coordinates like `cursor::Goto`.
+#[derive(Clone, Debug)]
+pub struct Buffer {
+    width: u16,
+    height: u16,
+    /// The cells being drawn.
+    cells: Vec<Cell>,
+    /// The cells written by the last flush, or `None` if what is on the screen is unknown.
+    flushed: Option<Vec<Cell>>,
+    /// The areas drawn into since the last flush.
+    dirty: Vec<Rect>,
+}
+
+impl Buffer {
+    /// A blank buffer of `width` columns and `height` rows.
+    ///
+    /// The first flush draws every cell, as what is on the screen is unknown.
+    pub fn new(width: u16, height: u16) -> Buffer {
+        let mut buffer = Buffer {
+            width,
+            height,
+            cells: vec![Cell::default(); width as usize * height as usize],
+            flushed: None,
+            dirty: Vec::new(),
+        };
+        buffer.invalidate();
+        buffer
+    }
+
+    /// The number of columns.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// The whole area of the buffer.
+    pub fn area(&self) -> Rect {
+        Rect::new(1, 1, self.width, self.height)
+    }
+
+    fn index(&self, x: u16, y: u16) -> Option<usize> {
+        if self.area().contains(x, y) {
+            Some((y as usize - 1) * self.width as usize + x as usize - 1)
+        } else {
+            None
+        }
+    }
+
+    /// The cell at column `x` and row `y`, if it is in the buffer.
+    pub fn get(&self, x: u16, y: u16) -> Option<&Cell> {
+        self.index(x, y).map(|i| &self.cells[i])
+    }
+
+    /// The cell at column `x` and row `y`, if it is in the buffer, marking it dirty.
+    ///
+    /// Unlike `set`, this leaves the wide characters around the cell as they are.
+    pub fn get_mut(&mut self, x: u16, y: u16) -> Option<&mut Cell> {
+        let i = self.index(x, y)?;
+        self.mark_dirty(Rect::new(x, y, 1, 1));
+        Some(&mut self.cells[i])
+    }
+
+    /// Set the cell at column `x` and row `y`. Cells outside of the buffer are ignored.
+    ///
+    /// A wide character partly overwritten is replaced by blanks, and a wide character which
+    /// doesn't fit before the right edge is replaced by a blank.
+    pub fn set(&mut self, x: u16, y: u16, cell: Cell) {
+        let i = match self.index(x, y) {
+            Some(i) => i,
+            None => return,
+        };
+        let mut cell = cell;
+        let mut width = cell.width();
+        if width == 2 && x == self.width {
+            cell.symbol = " ".to_string();
+            width = 1;
+        }
+
+        // Blank the wide character this cell is the second half of.
+        if self.cells[i].symbol.is_empty() && x > 1 {
+            self.cells[i - 1].symbol = " ".to_string();
+            self.mark_dirty(Rect::new(x - 1, y, 1, 1));
+        }
+        // Blank the second half of the wide character being overwritten.
+        if self.cells[i].width() == 2 && width != 2 && x < self.width {
+            self.cells[i + 1].symbol = " ".to_string();
+            self.mark_dirty(Rect::new(x + 1, y, 1, 1));
+        }
+
+        let style = cell.style;
+        self.cells[i] = cell;
+        self.mark_dirty(Rect::new(x, y, width.max(1), 1));
+
+        if width == 2 {
+            // The cell covered by the wide character may itself start a wide character.
+            if self.cells[i + 1].width() == 2 && x + 1 < self.width {
+                self.cells[i + 2].symbol = " ".to_string();
+                self.mark_dirty(Rect::new(x + 2, y, 1, 1));
+            }
+            self.cells[i + 1] = Cell {
+                symbol: String::new(),
+                style,
+            };
+        }
+    }
+
+    /// Draw `s` with `style`, from column `x` of row `y`, returning the number of columns
+    /// drawn.
+    ///
+    /// The string is cut at the right edge rather than wrapped. Control characters are
+    /// skipped, and combining marks are added to the symbol of the cell before them.
+    pub fn set_str(&mut self, x: u16, y: u16, s: &str, style: CellStyle) -> u16 {
+        if self.index(x, y).is_none() {
+            return 0;
+        }
+
+        let mut column = x;
+        let mut last: Option<usize> = None;
+        for grapheme in graphemes(s) {
+            if grapheme.starts_with(|c: char| c.is_control()) {
+                continue;
+            }
+            match grapheme_width(grapheme) {
+                0 => {
+                    if let Some(i) = last {
+                        self.cells[i].symbol.push_str(grapheme);
+                    }
+                }
+                width => {
+                    if column as usize + width - 1 > self.width as usize {
+                        break;
+                    }
+                    self.set(column, y, Cell::from_grapheme(grapheme, style));
+                    last = self.index(column, y);
+                    column += width as u16;
+                }
+            }
+        }
+
+        column - x
+    }
+
+    /// Draw the styled text `text` from column `x` of row `y`, returning the number of columns
+    /// drawn.
+    ///
+    /// As with `set_str`, the text is cut at the right edge.
+    pub fn set_styled(&mut self, x: u16, y: u16, text: &StyledStr) -> u16 {
+        let mut column = x;
+        for span in text.spans() {
+            column += self.set_str(column, y, span.text(), span.style());
+        }
+        column - x
+    }
+
+    /// Set every cell of `area` to `cell`.
+    pub fn fill(&mut self, area: Rect, cell: &Cell) {
+        for y in area.y..area.y.saturating_add(area.height) {
+            for x in area.x..area.x.saturating_add(area.width) {
+                self.set(x, y, cell.clone());
+            }
+        }
+    }
+
+    /// Blank every cell.
+    pub fn clear(&mut self) {
+        for cell in &mut self.cells {
+            *cell = Cell::default();
+        }
+        let area = self.area();
+        self.mark_dirty(area);
+    }
+
+    /// Resize the buffer, keeping the cells which still fit.
+    ///
+    /// The next flush draws every cell, as resizing the terminal often leaves the screen in an
+    /// unknown state.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let mut cells = vec![Cell::default(); width as usize * height as usize];
+        for y in 1..=height.min(self.height) {
+            for x in 1..=width.min(self.width) {
+                let i = (y as usize - 1) * width as usize + x as usize - 1;
+                cells[i] = self.cells[self.index(x, y).unwrap()].clone();
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.cells = cells;
+        // Don't leave half of a wide character at the new right edge.
+        for y in 1..=height {
+            let last = self.get(width, y).map(Cell::width);
+            if last == Some(2) {
+                let i = self.index(width, y).unwrap();
+                self.cells[i].symbol = " ".to_string();
+            }
+        }
+        self.invalidate();
+    }
+
+    /// The areas drawn into since the last flush.
+    ///
+    /// Overlapping and adjacent areas are merged, so the areas don't overlap, but they may
+    /// contain cells which were not drawn into. After an `invalidate`, this is the whole buffer.
+    pub fn dirty_regions(&self) -> &[Rect] {
+        &self.dirty
+    }
+
+    /// Was anything drawn into the buffer since the last flush?
+    pub fn is_dirty(&self) -> bool {
+        !self.dirty.is_empty()
+    }
+
+    /// Mark `area` as drawn into, so that it is compared on the next flush.
+    pub fn mark_dirty(&mut self, area: Rect) {
+        let mut area = match intersection(area, self.area()) {
+            Some(area) => area,
+            None => return,
+        };
+
+        // Merge the areas overlapping or touching this one, until none is left.
+        loop {
+            let grown = Rect::new(area.x.saturating_sub(1).max(1),
+                                  area.y.saturating_sub(1).max(1),
+                                  area.width.saturating_add(2),
+                                  area.height.saturating_add(2));
+            match self.dirty.iter().position(|&other| grown.intersects(other)) {
+                Some(i) => area = area.union(self.dirty.swap_remove(i)),
+                None => break,
+            }
+        }
+        self.dirty.push(area);
+
+        if self.dirty.len() > MAX_DIRTY_REGIONS {
+            let all = self.dirty.drain(..).fold(Rect::default(), |all, area| all.union(area));
+            self.dirty.push(all);
+        }
+    }
+
+    /// Forget what is on the screen, so that the next flush draws every cell.
+    ///
+    /// Use this when the screen was changed behind the buffer's back, e.g. after it was cleared.
+    pub fn invalidate(&mut self) {
+        self.flushed = None;
+        self.dirty.clear();
+        let area = self.area();
+        self.mark_dirty(area);
+    }
+
+    /// Write the cells changed since the last flush to `output`, and flush it.
+    ///
+    /// Only the rows overlapping a dirty region are compared to the last flush. The cursor is
+    /// left after the last cell written, and the graphic rendition is reset if it was changed.
+    pub fn flush<W: Write>(&mut self, output: &mut W) -> io::Result<()> {
+        let mut out = Vec::new();
+        let width = self.width as usize;
+
+        let mut dirty_rows = vec![false; self.height as usize];
+        for area in &self.dirty {
+            for y in area.y..area.y + area.height {
+                dirty_rows[y as usize - 1] = true;
+            }
+        }
+
+        let mut cursor_at = None;
+        let mut style = None;
+        for (y, _) in dirty_rows.iter().enumerate().filter(|&(_, &dirty)| dirty) {
+            let row = &self.cells[y * width..(y + 1) * width];
+            let flushed_row = self.flushed.as_ref().map(|cells| &cells[y * width..(y + 1) * width]);
+
+            for (x, cell) in row.iter().enumerate() {
+                if cell.symbol.is_empty() || flushed_row.map(|r| r[x] == *cell).unwrap_or(false) {
+                    continue;
+                }
+                let pos = (x as u16 + 1, y as u16 + 1);
+                if cursor_at != Some(pos) {
+                    let _ = write!(out, "{}", cursor::Goto(pos.0, pos.1));
+                }
+                if style != Some(cell.style) {
+                    cell.style.write_sgr(style.as_ref(), &mut out);
+                    style = Some(cell.style);
+                }
+                out.extend_from_slice(cell.symbol.as_bytes());
+                cursor_at = Some((pos.0 + cell.width(), pos.1));
+            }
+        }
+        if style.is_some_and(|style| style != CellStyle::default()) {
+            out.extend_from_slice(b"\x1B[m");
+        }
+
+        output.write_all(&out)?;
+        output.flush()?;
+
+        match self.flushed {
+            Some(ref mut flushed) => {
+                for (y, _) in dirty_rows.iter().enumerate().filter(|&(_, &dirty)| dirty) {
+                    flushed[y * width..(y + 1) * width]
+                        .clone_from_slice(&self.cells[y * width..(y + 1) * width]);
+                }
+            }
+            None => self.flushed = Some(self.cells.clone()),
+        }
+        self.dirty.clear();
+
+        Ok(())
+    }
+}
+
+/// The cells two areas share, if any.
+fn intersection(a: Rect, b: Rect) -> Option<Rect> {
+    if !a.intersects(b) {
+        return None;
+    }
+    let left = a.x.max(b.x);
+    let top = a.y.max(b.y);
+    let right = (a.x as u32 + a.width as u32).min(b.x as u32 + b.width as u32);
+    let bottom = (a.y as u32 + a.height as u32).min(b.y as u32 + b.height as u32);
+    Some(Rect::new(left, top, (right - left as u32) as u16, (bottom - top as u32) as u16))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn flushed(buffer: &mut Buffer) -> String {
+        let mut out = Vec::new();
+        buffer.flush(&mut out).unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn symbols(buffer: &Buffer, y: u16) -> Vec<&str> {
+        (1..=buffer.width()).map(|x| &buffer.get(x, y).unwrap().symbol[..]).collect()
+    }
+
+    #[test]
+    fn test_set_str() {
+        let mut buffer = Buffer::new(5, 2);
+        assert_eq!(buffer.set_str(1, 1, "a日e\u{301}xyz", CellStyle::default()), 5);
+        assert_eq!(symbols(&buffer, 1), vec!["a", "日", "", "e\u{301}", "x"]);
+
+        // Overwriting half of a wide character blanks the other half.
+        buffer.set_str(3, 1, "b", CellStyle::default());
+        assert_eq!(symbols(&buffer, 1), vec!["a", " ", "b", "e\u{301}", "x"]);
+
+        // A wide character doesn't fit in the last column.
+        assert_eq!(buffer.set_str(5, 2, "日", CellStyle::default()), 0);
+        assert_eq!(buffer.set_str(9, 2, "a", CellStyle::default()), 0);
+    }
+
+    #[test]
+    fn test_set_graphemes() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let flag = "\u{1F1EB}\u{1F1F7}";
+        let mut buffer = Buffer::new(6, 1);
+        let drawn = buffer.set_str(1, 1, &format!("{}{}a", family, flag), CellStyle::default());
+        assert_eq!(drawn, 5);
+        assert_eq!(symbols(&buffer, 1), vec![family, "", flag, "", "a", " "]);
+
+        // Redrawing the same clusters writes nothing.
+        flushed(&mut buffer);
+        buffer.set_str(1, 1, family, CellStyle::default());
+        assert_eq!(flushed(&mut buffer), "");
+    }
+
+    #[test]
+    fn test_set_styled() {
+        let bold = CellStyle { bold: true, ..CellStyle::default() };
+        let mut buffer = Buffer::new(4, 1);
+        let text = StyledStr::from("ab") + StyledStr::styled("cde", bold);
+        assert_eq!(buffer.set_styled(1, 1, &text), 4);
+        assert_eq!(symbols(&buffer, 1), vec!["a", "b", "c", "d"]);
+        assert!(buffer.get(3, 1).unwrap().style.bold);
+        assert!(!buffer.get(2, 1).unwrap().style.bold);
+    }
+
+    #[test]
+    fn test_dirty_regions() {
+        let mut buffer = Buffer::new(20, 10);
+        assert_eq!(buffer.dirty_regions(), &[buffer.area()]);
+        flushed(&mut buffer);
+        assert!(!buffer.is_dirty());
+
+        buffer.set_str(2, 2, "ab", CellStyle::default());
+        buffer.set_str(4, 2, "cd", CellStyle::default());
+        buffer.set_str(10, 8, "x", CellStyle::default());
+        let mut regions = buffer.dirty_regions().to_vec();
+        regions.sort_by_key(|area| (area.y, area.x));
+        assert_eq!(regions, vec![Rect::new(2, 2, 4, 1), Rect::new(10, 8, 1, 1)]);
+
+        buffer.mark_dirty(Rect::new(15, 9, 10, 10));
+        assert!(buffer.dirty_regions().contains(&Rect::new(15, 9, 6, 2)));
+    }
+
+    #[test]
+    fn test_flush_changes_only() {
+        let mut buffer = Buffer::new(4, 3);
+        assert_eq!(flushed(&mut buffer),
+                   "\x1B[1;1H\x1B[0m    \x1B[2;1H    \x1B[3;1H    ");
+
+        let bold = CellStyle { bold: true, ..CellStyle::default() };
+        buffer.set_str(2, 2, "ab", bold);
+        buffer.set_str(4, 2, " ", CellStyle::default());
+        assert_eq!(flushed(&mut buffer), "\x1B[2;2H\x1B[0;1mab\x1B[m");
+
+        // Drawing the same cells again writes nothing.
+        buffer.set_str(2, 2, "ab", bold);
+        assert_eq!(flushed(&mut buffer), "");
+
+        buffer.set_str(1, 3, "日", CellStyle { fg: CellColor::Rgb(Rgb(1, 2, 3)), ..bold });
+        assert_eq!(flushed(&mut buffer), "\x1B[3;1H\x1B[0;1;38;2;1;2;3m日\x1B[m");
+    }
+
+    #[test]
+    fn test_sgr_diff() {
+        let sgr = |from: Option<CellStyle>, to: CellStyle| {
+            let mut out = Vec::new();
+            to.write_sgr(from.as_ref(), &mut out);
+            String::from_utf8(out).unwrap()
+        };
+        let red_bold = CellStyle { fg: CellColor::Ansi(1), bold: true, ..CellStyle::default() };
+        let blue_bold = CellStyle { fg: CellColor::Ansi(4), ..red_bold };
+        let faint = CellStyle { faint: true, ..blue_bold };
+
+        assert_eq!(sgr(None, red_bold), "\x1B[0;1;38;5;1m");
+        assert_eq!(sgr(Some(red_bold), red_bold), "");
+        assert_eq!(sgr(Some(red_bold), blue_bold), "\x1B[38;5;4m");
+        assert_eq!(sgr(Some(blue_bold), faint), "\x1B[2m");
+        assert_eq!(sgr(Some(faint), CellStyle { bold: false, ..faint }), "\x1B[22;2m");
+        assert_eq!(sgr(Some(red_bold), CellStyle { bold: false, ..red_bold }), "\x1B[22m");
+        assert_eq!(sgr(Some(red_bold), CellStyle { italic: true, ..CellStyle::default() }),
+                   "\x1B[0;3m");
+        assert_eq!(sgr(Some(CellStyle { underline: true, ..blue_bold }), blue_bold),
+                   "\x1B[24m");
+        assert_eq!(sgr(Some(blue_bold), CellStyle { fg: CellColor::Reset, ..blue_bold }),
+                   "\x1B[39m");
+
+        let mut buffer = Buffer::new(3, 1);
+        buffer.set_str(1, 1, "a", red_bold);
+        buffer.set_str(2, 1, "b", blue_bold);
+        buffer.set_str(3, 1, "c", faint);
+        assert_eq!(flushed(&mut buffer), "\x1B[1;1H\x1B[0;1;38;5;1ma\x1B[38;5;4mb\x1B[2mc\x1B[m");
+    }
+
+    #[test]
+    fn test_invalidate_and_resize() {
+        let mut buffer = Buffer::new(3, 1);
+        buffer.set_str(2, 1, "日", CellStyle::default());
+        flushed(&mut buffer);
+
+        buffer.resize(2, 2);
+        assert_eq!(symbols(&buffer, 1), vec![" ", " "]);
+        assert_eq!(buffer.dirty_regions(), &[Rect::new(1, 1, 2, 2)]);
+        assert_eq!(flushed(&mut buffer), "\x1B[1;1H\x1B[0m  \x1B[2;1H  ");
+    }
+}
@@ -0,0 +1,10 @@
+//! Character set selection.
+//!
+//! Terminals without UTF-8 support (such as the Linux console under a non-UTF-8 locale) can still
+//! draw lines and boxes through the DEC special graphics character set. See the `box_drawing`
+//! module for a higher-level interface.
+
+derive_esc_sequence!("Use the DEC special graphics (line drawing) character set.",
+                     DecGraphics,
+                     "(0");
+derive_esc_sequence!("Use the US ASCII character set (the default).", Ascii, "(B");
@@ -12,11 +12,29 @@
 //! }
 //! ```
 
+#[cfg(feature = "std")]
+use std::error;
 use std::fmt;
+use std::str::FromStr;
+#[cfg(feature = "std")]
+use std::str;
+#[cfg(feature = "std")]
 use raw::CONTROL_SEQUENCE_TIMEOUT;
+#[cfg(feature = "std")]
 use std::io::{self, Write, Read};
+#[cfg(feature = "std")]
+use std::ops;
+#[cfg(feature = "std")]
 use std::time::{SystemTime, Duration};
+#[cfg(feature = "std")]
+use ansi::{Csi, Splitter, Token};
+#[cfg(feature = "std")]
 use async::async_stdin;
+#[cfg(feature = "std")]
+use reply;
+#[cfg(feature = "std")]
+use terminal::Quirks;
+#[cfg(feature = "std")]
 use std::env;
 
 /// A terminal color.
@@ -108,8 +126,49 @@ impl AnsiValue {
 
         AnsiValue(0xE8 + shade)
     }
+
+    /// The RGB value this color usually has.
+    ///
+    /// The 16 base colors are given as in xterm's default palette, although they can be
+    /// customized by the user.
+    pub fn to_rgb(&self) -> Rgb {
+        match self.0 {
+            0..=15 => BASE_PALETTE[self.0 as usize],
+            16..=231 => {
+                let n = self.0 - 16;
+                Rgb(CUBE_LEVELS[(n / 36) as usize],
+                    CUBE_LEVELS[(n / 6 % 6) as usize],
+                    CUBE_LEVELS[(n % 6) as usize])
+            }
+            shade => {
+                let level = 8 + 10 * (shade - 0xE8);
+                Rgb(level, level, level)
+            }
+        }
+    }
 }
 
+/// xterm's default RGB values of the 16 base colors.
+const BASE_PALETTE: [Rgb; 16] = [Rgb(0, 0, 0),
+                                 Rgb(205, 0, 0),
+                                 Rgb(0, 205, 0),
+                                 Rgb(205, 205, 0),
+                                 Rgb(0, 0, 238),
+                                 Rgb(205, 0, 205),
+                                 Rgb(0, 205, 205),
+                                 Rgb(229, 229, 229),
+                                 Rgb(127, 127, 127),
+                                 Rgb(255, 0, 0),
+                                 Rgb(0, 255, 0),
+                                 Rgb(255, 255, 0),
+                                 Rgb(92, 92, 255),
+                                 Rgb(255, 0, 255),
+                                 Rgb(0, 255, 255),
+                                 Rgb(255, 255, 255)];
+
+/// The channel levels of the 6×6×6 color cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
 impl Color for AnsiValue {
     #[inline]
     fn write_fg(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -123,9 +182,347 @@ impl Color for AnsiValue {
 }
 
 /// A truecolor RGB.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Rgb(pub u8, pub u8, pub u8);
 
+impl Rgb {
+    /// The nearest color of the 256-color palette, ignoring the customizable 16 base colors.
+    pub fn to_ansi_256(&self) -> AnsiValue {
+        fn cube_index(x: u8) -> u8 {
+            match x {
+                0..=47 => 0,
+                48..=114 => 1,
+                x => (x - 35) / 40,
+            }
+        }
+
+        let (r, g, b) = (cube_index(self.0), cube_index(self.1), cube_index(self.2));
+        let cube = AnsiValue::rgb(r, g, b);
+
+        let average = (self.0 as u16 + self.1 as u16 + self.2 as u16) / 3;
+        let gray = if average < 8 {
+            AnsiValue::grayscale(0)
+        } else {
+            AnsiValue::grayscale(((average - 8) / 10).min(23) as u8)
+        };
+
+        if self.distance(cube.to_rgb()) <= self.distance(gray.to_rgb()) {
+            cube
+        } else {
+            gray
+        }
+    }
+
+    /// The nearest of the 16 base colors, assuming xterm's default palette.
+    pub fn to_ansi_16(&self) -> AnsiValue {
+        let nearest = (0..16).min_by_key(|&i| self.distance(BASE_PALETTE[i])).unwrap();
+        AnsiValue(nearest as u8)
+    }
+
+    /// Parse a hexadecimal color, such as `#ff8800`, `ff8800` or the short form `#f80`.
+    pub fn from_hex(hex: &str) -> Result<Rgb, ParseColorError> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if !hex.bytes().all(|c| c.is_ascii_hexdigit()) {
+            return Err(ParseColorError(()));
+        }
+
+        let channel = |i: usize, len: usize| {
+            let value = u8::from_str_radix(&hex[i * len..(i + 1) * len], 16).unwrap();
+            if len == 1 { value * 0x11 } else { value }
+        };
+
+        match hex.len() {
+            3 => Ok(Rgb(channel(0, 1), channel(1, 1), channel(2, 1))),
+            6 => Ok(Rgb(channel(0, 2), channel(1, 2), channel(2, 2))),
+            _ => Err(ParseColorError(())),
+        }
+    }
+
+    /// Look up a CSS/X11 color name, such as `steelblue`.
+    ///
+    /// Names are case-insensitive, and may contain spaces, `-` or `_` (as in `Dark Slate Gray`).
+    pub fn from_name(name: &str) -> Option<Rgb> {
+        let normalized = || {
+            name.bytes()
+                .filter(|&c| c != b' ' && c != b'-' && c != b'_')
+                .map(|c| c.to_ascii_lowercase())
+        };
+
+        NAMED_COLORS.binary_search_by(|&(known, _)| known.bytes().cmp(normalized()))
+            .ok()
+            .map(|i| NAMED_COLORS[i].1)
+    }
+
+    /// Create a color from hue (in degrees), saturation and lightness (both from 0 to 1).
+    #[cfg(feature = "std")]
+    pub fn from_hsl(hue: f32, saturation: f32, lightness: f32) -> Rgb {
+        let s = saturation.clamp(0.0, 1.0);
+        let l = lightness.clamp(0.0, 1.0);
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+        Rgb::from_hue(hue, chroma, l - chroma / 2.0)
+    }
+
+    /// Create a color from hue (in degrees), saturation and value (both from 0 to 1).
+    #[cfg(feature = "std")]
+    pub fn from_hsv(hue: f32, saturation: f32, value: f32) -> Rgb {
+        let s = saturation.clamp(0.0, 1.0);
+        let v = value.clamp(0.0, 1.0);
+        let chroma = v * s;
+        Rgb::from_hue(hue, chroma, v - chroma)
+    }
+
+    /// Create a color from hue, chroma and the amount added to every channel.
+    #[cfg(feature = "std")]
+    fn from_hue(hue: f32, chroma: f32, m: f32) -> Rgb {
+        let h = hue.rem_euclid(360.0) / 60.0;
+        let x = chroma * (1.0 - (h % 2.0 - 1.0).abs());
+        let (r, g, b) = match h as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+        let channel = |c: f32| ((c + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+
+        Rgb(channel(r), channel(g), channel(b))
+    }
+
+    /// The hue (in degrees), saturation and lightness (both from 0 to 1) of this color.
+    #[cfg(feature = "std")]
+    pub fn to_hsl(&self) -> (f32, f32, f32) {
+        let (r, g, b) = (self.0 as f32 / 255.0, self.1 as f32 / 255.0, self.2 as f32 / 255.0);
+        let max = r.max(g).max(b);
+        let min = r.min(g).min(b);
+        let chroma = max - min;
+        let lightness = (max + min) / 2.0;
+
+        if chroma == 0.0 {
+            return (0.0, 0.0, lightness);
+        }
+
+        let hue = if max == r {
+            60.0 * ((g - b) / chroma).rem_euclid(6.0)
+        } else if max == g {
+            60.0 * ((b - r) / chroma + 2.0)
+        } else {
+            60.0 * ((r - g) / chroma + 4.0)
+        };
+        let saturation = chroma / (1.0 - (2.0 * lightness - 1.0).abs());
+
+        (hue, saturation, lightness)
+    }
+
+    /// Interpolate linearly between this color (`t` = 0) and `other` (`t` = 1).
+    #[cfg(feature = "std")]
+    pub fn lerp(&self, other: Rgb, t: f32) -> Rgb {
+        let t = t.clamp(0.0, 1.0);
+        let mix = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * t).round() as u8;
+
+        Rgb(mix(self.0, other.0), mix(self.1, other.1), mix(self.2, other.2))
+    }
+
+    /// Increase the lightness of this color by `amount` (from 0 to 1).
+    #[cfg(feature = "std")]
+    pub fn lighten(&self, amount: f32) -> Rgb {
+        let (h, s, l) = self.to_hsl();
+        Rgb::from_hsl(h, s, l + amount)
+    }
+
+    /// Decrease the lightness of this color by `amount` (from 0 to 1).
+    #[cfg(feature = "std")]
+    pub fn darken(&self, amount: f32) -> Rgb {
+        let (h, s, l) = self.to_hsl();
+        Rgb::from_hsl(h, s, l - amount)
+    }
+
+    /// The squared euclidean distance to another color.
+    fn distance(&self, other: Rgb) -> u32 {
+        let d = |a: u8, b: u8| (a as i32 - b as i32).pow(2) as u32;
+        d(self.0, other.0) + d(self.1, other.1) + d(self.2, other.2)
+    }
+}
+
+impl FromStr for Rgb {
+    type Err = ParseColorError;
+
+    /// Parse either a hexadecimal color starting with `#`, or a color name.
+    fn from_str(s: &str) -> Result<Rgb, ParseColorError> {
+        let s = s.trim();
+        if s.starts_with('#') {
+            Rgb::from_hex(s)
+        } else {
+            Rgb::from_name(s).ok_or(ParseColorError(()))
+        }
+    }
+}
+
+/// An error returned when parsing a color fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseColorError(());
+
+impl fmt::Display for ParseColorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid color, expected a hex value such as `#ff8800` or a color name")
+    }
+}
+
+#[cfg(feature = "std")]
+impl error::Error for ParseColorError {}
+
+/// The CSS color names (which mostly agree with X11's), sorted by name.
+const NAMED_COLORS: &[(&str, Rgb)] = &[
+    ("aliceblue", Rgb(240, 248, 255)),
+    ("antiquewhite", Rgb(250, 235, 215)),
+    ("aqua", Rgb(0, 255, 255)),
+    ("aquamarine", Rgb(127, 255, 212)),
+    ("azure", Rgb(240, 255, 255)),
+    ("beige", Rgb(245, 245, 220)),
+    ("bisque", Rgb(255, 228, 196)),
+    ("black", Rgb(0, 0, 0)),
+    ("blanchedalmond", Rgb(255, 235, 205)),
+    ("blue", Rgb(0, 0, 255)),
+    ("blueviolet", Rgb(138, 43, 226)),
+    ("brown", Rgb(165, 42, 42)),
+    ("burlywood", Rgb(222, 184, 135)),
+    ("cadetblue", Rgb(95, 158, 160)),
+    ("chartreuse", Rgb(127, 255, 0)),
+    ("chocolate", Rgb(210, 105, 30)),
+    ("coral", Rgb(255, 127, 80)),
+    ("cornflowerblue", Rgb(100, 149, 237)),
+    ("cornsilk", Rgb(255, 248, 220)),
+    ("crimson", Rgb(220, 20, 60)),
+    ("cyan", Rgb(0, 255, 255)),
+    ("darkblue", Rgb(0, 0, 139)),
+    ("darkcyan", Rgb(0, 139, 139)),
+    ("darkgoldenrod", Rgb(184, 134, 11)),
+    ("darkgray", Rgb(169, 169, 169)),
+    ("darkgreen", Rgb(0, 100, 0)),
+    ("darkgrey", Rgb(169, 169, 169)),
+    ("darkkhaki", Rgb(189, 183, 107)),
+    ("darkmagenta", Rgb(139, 0, 139)),
+    ("darkolivegreen", Rgb(85, 107, 47)),
+    ("darkorange", Rgb(255, 140, 0)),
+    ("darkorchid", Rgb(153, 50, 204)),
+    ("darkred", Rgb(139, 0, 0)),
+    ("darksalmon", Rgb(233, 150, 122)),
+    ("darkseagreen", Rgb(143, 188, 143)),
+    ("darkslateblue", Rgb(72, 61, 139)),
+    ("darkslategray", Rgb(47, 79, 79)),
+    ("darkslategrey", Rgb(47, 79, 79)),
+    ("darkturquoise", Rgb(0, 206, 209)),
+    ("darkviolet", Rgb(148, 0, 211)),
+    ("deeppink", Rgb(255, 20, 147)),
+    ("deepskyblue", Rgb(0, 191, 255)),
+    ("dimgray", Rgb(105, 105, 105)),
+    ("dimgrey", Rgb(105, 105, 105)),
+    ("dodgerblue", Rgb(30, 144, 255)),
+    ("firebrick", Rgb(178, 34, 34)),
+    ("floralwhite", Rgb(255, 250, 240)),
+    ("forestgreen", Rgb(34, 139, 34)),
+    ("fuchsia", Rgb(255, 0, 255)),
+    ("gainsboro", Rgb(220, 220, 220)),
+    ("ghostwhite", Rgb(248, 248, 255)),
+    ("gold", Rgb(255, 215, 0)),
+    ("goldenrod", Rgb(218, 165, 32)),
+    ("gray", Rgb(128, 128, 128)),
+    ("green", Rgb(0, 128, 0)),
+    ("greenyellow", Rgb(173, 255, 47)),
+    ("grey", Rgb(128, 128, 128)),
+    ("honeydew", Rgb(240, 255, 240)),
+    ("hotpink", Rgb(255, 105, 180)),
+    ("indianred", Rgb(205, 92, 92)),
+    ("indigo", Rgb(75, 0, 130)),
+    ("ivory", Rgb(255, 255, 240)),
+    ("khaki", Rgb(240, 230, 140)),
+    ("lavender", Rgb(230, 230, 250)),
+    ("lavenderblush", Rgb(255, 240, 245)),
+    ("lawngreen", Rgb(124, 252, 0)),
+    ("lemonchiffon", Rgb(255, 250, 205)),
+    ("lightblue", Rgb(173, 216, 230)),
+    ("lightcoral", Rgb(240, 128, 128)),
+    ("lightcyan", Rgb(224, 255, 255)),
+    ("lightgoldenrodyellow", Rgb(250, 250, 210)),
+    ("lightgray", Rgb(211, 211, 211)),
+    ("lightgreen", Rgb(144, 238, 144)),
+    ("lightgrey", Rgb(211, 211, 211)),
+    ("lightpink", Rgb(255, 182, 193)),
+    ("lightsalmon", Rgb(255, 160, 122)),
+    ("lightseagreen", Rgb(32, 178, 170)),
+    ("lightskyblue", Rgb(135, 206, 250)),
+    ("lightslategray", Rgb(119, 136, 153)),
+    ("lightslategrey", Rgb(119, 136, 153)),
+    ("lightsteelblue", Rgb(176, 196, 222)),
+    ("lightyellow", Rgb(255, 255, 224)),
+    ("lime", Rgb(0, 255, 0)),
+    ("limegreen", Rgb(50, 205, 50)),
+    ("linen", Rgb(250, 240, 230)),
+    ("magenta", Rgb(255, 0, 255)),
+    ("maroon", Rgb(128, 0, 0)),
+    ("mediumaquamarine", Rgb(102, 205, 170)),
+    ("mediumblue", Rgb(0, 0, 205)),
+    ("mediumorchid", Rgb(186, 85, 211)),
+    ("mediumpurple", Rgb(147, 112, 219)),
+    ("mediumseagreen", Rgb(60, 179, 113)),
+    ("mediumslateblue", Rgb(123, 104, 238)),
+    ("mediumspringgreen", Rgb(0, 250, 154)),
+    ("mediumturquoise", Rgb(72, 209, 204)),
+    ("mediumvioletred", Rgb(199, 21, 133)),
+    ("midnightblue", Rgb(25, 25, 112)),
+    ("mintcream", Rgb(245, 255, 250)),
+    ("mistyrose", Rgb(255, 228, 225)),
+    ("moccasin", Rgb(255, 228, 181)),
+    ("navajowhite", Rgb(255, 222, 173)),
+    ("navy", Rgb(0, 0, 128)),
+    ("oldlace", Rgb(253, 245, 230)),
+    ("olive", Rgb(128, 128, 0)),
+    ("olivedrab", Rgb(107, 142, 35)),
+    ("orange", Rgb(255, 165, 0)),
+    ("orangered", Rgb(255, 69, 0)),
+    ("orchid", Rgb(218, 112, 214)),
+    ("palegoldenrod", Rgb(238, 232, 170)),
+    ("palegreen", Rgb(152, 251, 152)),
+    ("paleturquoise", Rgb(175, 238, 238)),
+    ("palevioletred", Rgb(219, 112, 147)),
+    ("papayawhip", Rgb(255, 239, 213)),
+    ("peachpuff", Rgb(255, 218, 185)),
+    ("peru", Rgb(205, 133, 63)),
+    ("pink", Rgb(255, 192, 203)),
+    ("plum", Rgb(221, 160, 221)),
+    ("powderblue", Rgb(176, 224, 230)),
+    ("purple", Rgb(128, 0, 128)),
+    ("rebeccapurple", Rgb(102, 51, 153)),
+    ("red", Rgb(255, 0, 0)),
+    ("rosybrown", Rgb(188, 143, 143)),
+    ("royalblue", Rgb(65, 105, 225)),
+    ("saddlebrown", Rgb(139, 69, 19)),
+    ("salmon", Rgb(250, 128, 114)),
+    ("sandybrown", Rgb(244, 164, 96)),
+    ("seagreen", Rgb(46, 139, 87)),
+    ("seashell", Rgb(255, 245, 238)),
+    ("sienna", Rgb(160, 82, 45)),
+    ("silver", Rgb(192, 192, 192)),
+    ("skyblue", Rgb(135, 206, 235)),
+    ("slateblue", Rgb(106, 90, 205)),
+    ("slategray", Rgb(112, 128, 144)),
+    ("slategrey", Rgb(112, 128, 144)),
+    ("snow", Rgb(255, 250, 250)),
+    ("springgreen", Rgb(0, 255, 127)),
+    ("steelblue", Rgb(70, 130, 180)),
+    ("tan", Rgb(210, 180, 140)),
+    ("teal", Rgb(0, 128, 128)),
+    ("thistle", Rgb(216, 191, 216)),
+    ("tomato", Rgb(255, 99, 71)),
+    ("turquoise", Rgb(64, 224, 208)),
+    ("violet", Rgb(238, 130, 238)),
+    ("wheat", Rgb(245, 222, 179)),
+    ("white", Rgb(255, 255, 255)),
+    ("whitesmoke", Rgb(245, 245, 245)),
+    ("yellow", Rgb(255, 255, 0)),
+    ("yellowgreen", Rgb(154, 205, 50)),
+];
+
 impl Color for Rgb {
     #[inline]
     fn write_fg(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -154,6 +551,126 @@ impl Color for Reset {
     }
 }
 
+/// Redefine a palette color, using OSC 4.
+///
+/// See `PaletteTerminal` for restoring the palette afterwards.
+#[derive(Debug, Clone, Copy)]
+pub struct SetPalette(pub u8, pub Rgb);
+
+impl fmt::Display for SetPalette {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Rgb(r, g, b) = self.1;
+        write!(f, "\x1B]4;{};rgb:{:02x}/{:02x}/{:02x}\x07", self.0, r, g, b)
+    }
+}
+
+/// Reset a palette color to the terminal's default, using OSC 104.
+#[derive(Debug, Clone, Copy)]
+pub struct ResetPalette(pub u8);
+
+impl fmt::Display for ResetPalette {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\x1B]104;{}\x07", self.0)
+    }
+}
+
+/// Reset every palette color to the terminal's default, using OSC 104.
+#[derive(Debug, Clone, Copy)]
+pub struct ResetAllPalette;
+
+impl fmt::Display for ResetAllPalette {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\x1B]104\x07")
+    }
+}
+
+/// A terminal restorer, which resets the palette colors redefined through it when dropped.
+///
+/// # Example
+///
+/// ```rust
+/// use termion::color::{PaletteTerminal, Rgb};
+/// use std::io::stdout;
+///
+/// let mut stdout = PaletteTerminal::from(stdout());
+/// stdout.set_palette(1, Rgb(0xcc, 0x24, 0x1d)).unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub struct PaletteTerminal<W: Write> {
+    output: W,
+    /// The palette colors redefined so far.
+    changed: Vec<u8>,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> PaletteTerminal<W> {
+    /// Redefine the palette color `index` until this terminal is dropped.
+    pub fn set_palette(&mut self, index: u8, color: Rgb) -> io::Result<()> {
+        write!(self.output, "{}", SetPalette(index, color))?;
+        if !self.changed.contains(&index) {
+            self.changed.push(index);
+        }
+        Ok(())
+    }
+
+    /// Reset the palette colors redefined so far.
+    pub fn reset_palette(&mut self) -> io::Result<()> {
+        for &index in &self.changed {
+            write!(self.output, "{}", ResetPalette(index))?;
+        }
+        self.changed.clear();
+        self.output.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> From<W> for PaletteTerminal<W> {
+    fn from(output: W) -> PaletteTerminal<W> {
+        PaletteTerminal {
+            output,
+            changed: Vec::new(),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Drop for PaletteTerminal<W> {
+    fn drop(&mut self) {
+        self.reset_palette().expect("reset palette colors");
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::Deref for PaletteTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::DerefMut for PaletteTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for PaletteTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
 /// A foreground color.
 #[derive(Debug, Clone, Copy)]
 pub struct Fg<C: Color>(pub C);
@@ -175,6 +692,7 @@ impl<C: Color> fmt::Display for Bg<C> {
 }
 
 /// Types that allow detection of the colors they support.
+#[cfg(feature = "std")]
 pub trait DetectColors {
     /// How many ANSI colors are supported (from 8 to 256)?
     ///
@@ -183,6 +701,7 @@ pub trait DetectColors {
     fn available_colors(&mut self) -> io::Result<u16>;
 }
 
+#[cfg(feature = "std")]
 impl<W: Write> DetectColors for W {
     fn available_colors(&mut self) -> io::Result<u16> {
         let mut stdin = async_stdin();
@@ -218,7 +737,312 @@ impl<W: Write> DetectColors for W {
     }
 }
 
+/// The range of colors a terminal is able to display.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg(feature = "std")]
+pub enum ColorDepth {
+    /// The 16 base colors.
+    Ansi16,
+    /// The 256-color palette.
+    Ansi256,
+    /// 24-bit RGB colors.
+    TrueColor,
+}
+
+#[cfg(feature = "std")]
+impl ColorDepth {
+    /// Guess the color depth of the terminal.
+    ///
+    /// Beware: the information given isn't authoritative, it's inferred from the values of
+    /// `COLORTERM` and `TERM`, and from the known quirks of the terminal (see
+    /// `terminal::Quirks`).
+    pub fn detect() -> ColorDepth {
+        let colorterm = env::var("COLORTERM").unwrap_or_default();
+        let term = env::var("TERM").unwrap_or_default();
+        let truecolor = !Quirks::from_env().no_truecolor;
+
+        if (colorterm == "truecolor" || colorterm == "24bit") && truecolor {
+            ColorDepth::TrueColor
+        } else if term.contains("256color") {
+            ColorDepth::Ansi256
+        } else {
+            ColorDepth::Ansi16
+        }
+    }
+}
+
+/// A writer, which rewrites the colors written to it to fit the color depth of the terminal.
+///
+/// RGB colors are downgraded to the nearest color of the 256-color palette or of the 16 base
+/// colors, and the 256-color palette to the 16 base colors. Everything else is written unchanged.
+///
+/// # Example
+///
+/// ```rust
+/// use termion::color::{self, ColorDepth, DowngradeColors};
+/// use std::io::Write;
+///
+/// let mut out = DowngradeColors::new(Vec::new(), ColorDepth::Ansi16);
+/// write!(out, "{}Orange", color::Fg(color::Rgb(255, 135, 0))).unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub struct DowngradeColors<W: Write> {
+    output: W,
+    depth: ColorDepth,
+    splitter: Splitter,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> DowngradeColors<W> {
+    /// Downgrade the colors written to `output` to fit `depth`.
+    pub fn new(output: W, depth: ColorDepth) -> DowngradeColors<W> {
+        DowngradeColors {
+            output,
+            depth,
+            splitter: Splitter::new(),
+        }
+    }
+
+    /// The color depth colors are downgraded to.
+    pub fn depth(&self) -> ColorDepth {
+        self.depth
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> From<W> for DowngradeColors<W> {
+    /// Downgrade the colors written to `output` to fit the detected color depth.
+    fn from(output: W) -> DowngradeColors<W> {
+        DowngradeColors::new(output, ColorDepth::detect())
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Drop for DowngradeColors<W> {
+    fn drop(&mut self) {
+        let pending = self.splitter.take_pending();
+        let _ = self.output.write_all(&pending);
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::Deref for DowngradeColors<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::DerefMut for DowngradeColors<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for DowngradeColors<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let output = &mut self.output;
+        let depth = self.depth;
+
+        self.splitter.split(buf, |token| match token {
+                Token::Text(text) => output.write_all(text),
+                Token::Sequence(seq) => {
+                    match Csi::parse(seq) {
+                        Some(ref csi) if csi.final_byte == b'm' && csi.private.is_none() &&
+                                         !csi.subparams &&
+                                         csi.intermediates.is_empty() => {
+                            output.write_all(downgrade_sgr(&csi.params, depth).as_bytes())
+                        }
+                        _ => output.write_all(seq),
+                    }
+                }
+            })?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+/// Rewrite the parameters of an SGR sequence to fit `depth`.
+#[cfg(feature = "std")]
+fn downgrade_sgr(params: &[u16], depth: ColorDepth) -> String {
+    let mut out = Vec::with_capacity(params.len());
+    let mut i = 0;
+
+    while i < params.len() {
+        match (params[i], params.get(i + 1)) {
+            (base @ 38, Some(&2)) | (base @ 48, Some(&2)) if i + 4 < params.len() => {
+                let rgb = Rgb(params[i + 2] as u8, params[i + 3] as u8, params[i + 4] as u8);
+                out.push(match depth {
+                    ColorDepth::TrueColor => format!("{};2;{};{};{}", base, rgb.0, rgb.1, rgb.2),
+                    ColorDepth::Ansi256 => format!("{};5;{}", base, rgb.to_ansi_256().0),
+                    ColorDepth::Ansi16 => base_color_sgr(base, rgb.to_ansi_16().0),
+                });
+                i += 5;
+            }
+            (base @ 38, Some(&5)) | (base @ 48, Some(&5)) if i + 2 < params.len() => {
+                let value = AnsiValue(params[i + 2] as u8);
+                out.push(match depth {
+                    ColorDepth::Ansi16 if value.0 < 16 => base_color_sgr(base, value.0),
+                    ColorDepth::Ansi16 => base_color_sgr(base, value.to_rgb().to_ansi_16().0),
+                    _ => format!("{};5;{}", base, value.0),
+                });
+                i += 3;
+            }
+            (param, _) => {
+                out.push(param.to_string());
+                i += 1;
+            }
+        }
+    }
+
+    format!(csi!("{}m"), out.join(";"))
+}
+
+/// The SGR parameter selecting base color `n` as foreground (`base` = 38) or background (48).
+#[cfg(feature = "std")]
+fn base_color_sgr(base: u16, n: u8) -> String {
+    let first = if base == 38 { 30 } else { 40 };
+    if n < 8 {
+        (first + n as u16).to_string()
+    } else {
+        (first + 60 + (n - 8) as u16).to_string()
+    }
+}
+
+/// Types that allow querying the palette of the terminal.
+#[cfg(feature = "std")]
+pub trait DetectPalette {
+    /// Get the RGB value of the palette color `index`, using OSC 4.
+    fn palette_color(&mut self, index: u8) -> io::Result<Rgb>;
+
+    /// Get the RGB values of the 16 base colors, using OSC 4.
+    ///
+    /// These are typically customized by the user's theme.
+    fn base_palette(&mut self) -> io::Result<[Rgb; 16]>;
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> DetectPalette for W {
+    fn palette_color(&mut self, index: u8) -> io::Result<Rgb> {
+        let mut stdin = async_stdin();
+
+        query_palette(self, &mut stdin, &[index])?[0]
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "Palette query timed out."))
+    }
+
+    fn base_palette(&mut self) -> io::Result<[Rgb; 16]> {
+        let mut stdin = async_stdin();
+        let indices: Vec<u8> = (0..16).collect();
+        let colors = query_palette(self, &mut stdin, &indices)?;
+
+        let mut palette = [Rgb(0, 0, 0); 16];
+        for (color, reply) in palette.iter_mut().zip(colors) {
+            *color = reply.ok_or_else(|| {
+                    io::Error::new(io::ErrorKind::TimedOut, "Palette query timed out.")
+                })?;
+        }
+        Ok(palette)
+    }
+}
+
+/// Query the palette colors `indices` using OSC 4, all at once.
+///
+/// The colors the terminal did not report are `None`.
+#[cfg(feature = "std")]
+pub(crate) fn query_palette(stdout: &mut dyn Write,
+                            stdin: &mut dyn Read,
+                            indices: &[u8])
+                            -> io::Result<Vec<Option<Rgb>>> {
+    // Use `ESC ] 4 ; color ; ? BEL` for every color.
+    for index in indices {
+        write!(stdout, "\x1B]4;{};?\x07", index)?;
+    }
+    stdout.flush()?;
+
+    let replies = reply::read_until(stdin, |buf| reply::count_strings(buf) >= indices.len())?;
+
+    // The answers will look like `ESC ] 4 ; color ; rgb:RRRR/GGGG/BBBB BEL`.
+    let mut colors = vec![None; indices.len()];
+    for string in reply::split_strings(&replies) {
+        if let Some((index, rgb)) = parse_palette_reply(string) {
+            if let Some(i) = indices.iter().position(|&x| x == index) {
+                colors[i] = Some(rgb);
+            }
+        }
+    }
+
+    Ok(colors)
+}
+
+/// Parse an OSC 4 reply (`ESC ] 4 ; color ; spec`, without the terminator).
+#[cfg(feature = "std")]
+fn parse_palette_reply(string: &[u8]) -> Option<(u8, Rgb)> {
+    let start = string.windows(4).position(|w| w == b"\x1B]4;")?;
+    let mut fields = string[start + 4..].splitn(2, |&c| c == b';');
+
+    let index = str::from_utf8(fields.next()?).ok()?.parse().ok()?;
+    let (r, g, b) = reply::parse_x11_color(fields.next()?)?;
+
+    Some((index, Rgb(r, g, b)))
+}
+
+/// Types that allow querying the default colors of the terminal.
+#[cfg(feature = "std")]
+pub trait DetectDefaultColors {
+    /// Get the default foreground color, in which text is drawn, using OSC 10.
+    fn foreground_color(&mut self) -> io::Result<Rgb>;
+
+    /// Get the default background color, using OSC 11.
+    fn background_color(&mut self) -> io::Result<Rgb>;
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> DetectDefaultColors for W {
+    fn foreground_color(&mut self) -> io::Result<Rgb> {
+        let mut stdin = async_stdin();
+        query_default_color(self, &mut stdin, 10)
+    }
+
+    fn background_color(&mut self) -> io::Result<Rgb> {
+        let mut stdin = async_stdin();
+        query_default_color(self, &mut stdin, 11)
+    }
+}
+
+/// Query the default foreground (`code` = 10) or background (11) color.
+#[cfg(feature = "std")]
+pub(crate) fn query_default_color(stdout: &mut dyn Write,
+                                  stdin: &mut dyn Read,
+                                  code: u8)
+                                  -> io::Result<Rgb> {
+    // Use `ESC ] code ; ? BEL`.
+    write!(stdout, "\x1B]{};?\x07", code)?;
+    stdout.flush()?;
+
+    let replies = reply::read_until(stdin, |buf| reply::count_strings(buf) > 0)?;
+
+    // The answer will look like `ESC ] code ; rgb:RRRR/GGGG/BBBB BEL`.
+    let prefix = format!("\x1B]{};", code);
+    let color = reply::split_strings(&replies)
+        .filter_map(|string| {
+            let start = string.windows(prefix.len()).position(|w| w == prefix.as_bytes())?;
+            reply::parse_x11_color(&string[start + prefix.len()..])
+        })
+        .map(|(r, g, b)| Rgb(r, g, b))
+        .next()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "Color query timed out."));
+    color
+}
+
 /// Detect a color using OSC 4.
+#[cfg(feature = "std")]
 fn detect_color(stdout: &mut Write, stdin: &mut Read, color: u16) -> io::Result<bool> {
     // Is the color available?
     // Use `ESC ] 4 ; color ; ? BEL`.
@@ -240,3 +1064,173 @@ fn detect_color(stdout: &mut Write, stdin: &mut Read, color: u16) -> io::Result<
     // If there was a response, the color is supported.
     Ok(total_read > 0)
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_to_rgb() {
+        assert_eq!(AnsiValue(1).to_rgb(), Rgb(205, 0, 0));
+        assert_eq!(AnsiValue::rgb(5, 1, 0).to_rgb(), Rgb(255, 95, 0));
+        assert_eq!(AnsiValue::grayscale(2).to_rgb(), Rgb(28, 28, 28));
+    }
+
+    #[test]
+    fn test_to_ansi_256() {
+        assert_eq!(Rgb(255, 95, 0).to_ansi_256().0, AnsiValue::rgb(5, 1, 0).0);
+        assert_eq!(Rgb(0, 0, 0).to_ansi_256().0, 16);
+        assert_eq!(Rgb(255, 255, 255).to_ansi_256().0, 231);
+        assert_eq!(Rgb(128, 128, 128).to_ansi_256().0, AnsiValue::grayscale(12).0);
+        assert_eq!(Rgb(100, 140, 180).to_ansi_256().0, AnsiValue::rgb(1, 2, 3).0);
+    }
+
+    #[test]
+    fn test_to_ansi_16() {
+        assert_eq!(Rgb(250, 10, 10).to_ansi_16().0, 9);
+        assert_eq!(Rgb(190, 0, 0).to_ansi_16().0, 1);
+        assert_eq!(Rgb(10, 10, 10).to_ansi_16().0, 0);
+        assert_eq!(Rgb(240, 240, 240).to_ansi_16().0, 7);
+    }
+
+    #[test]
+    fn test_from_hex() {
+        assert_eq!(Rgb::from_hex("#ff8800"), Ok(Rgb(255, 136, 0)));
+        assert_eq!(Rgb::from_hex("0A0b0C"), Ok(Rgb(10, 11, 12)));
+        assert_eq!(Rgb::from_hex("#f80"), Ok(Rgb(255, 136, 0)));
+        assert!(Rgb::from_hex("#ff880").is_err());
+        assert!(Rgb::from_hex("#gg8800").is_err());
+        assert!(Rgb::from_hex("#+f+f+f").is_err());
+        assert!(Rgb::from_hex("").is_err());
+    }
+
+    #[test]
+    fn test_from_str() {
+        assert_eq!("#102030".parse(), Ok(Rgb(16, 32, 48)));
+        assert_eq!(" SteelBlue ".parse(), Ok(Rgb(70, 130, 180)));
+        assert_eq!("dark slate_gray".parse(), Ok(Rgb(47, 79, 79)));
+        assert_eq!("aliceblue".parse(), Ok(Rgb(240, 248, 255)));
+        assert_eq!("yellowgreen".parse(), Ok(Rgb(154, 205, 50)));
+        assert!("102030".parse::<Rgb>().is_err());
+        assert!("notacolor".parse::<Rgb>().is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_hsl() {
+        assert_eq!(Rgb::from_hsl(0.0, 1.0, 0.5), Rgb(255, 0, 0));
+        assert_eq!(Rgb::from_hsl(120.0, 1.0, 0.25), Rgb(0, 128, 0));
+        assert_eq!(Rgb::from_hsl(-120.0, 1.0, 0.5), Rgb(0, 0, 255));
+        assert_eq!(Rgb::from_hsl(200.0, 0.0, 1.0), Rgb(255, 255, 255));
+        assert_eq!(Rgb::from_hsl(207.0, 0.44, 0.49), Rgb(70, 130, 180));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_from_hsv() {
+        assert_eq!(Rgb::from_hsv(60.0, 1.0, 1.0), Rgb(255, 255, 0));
+        assert_eq!(Rgb::from_hsv(300.0, 0.5, 0.5), Rgb(128, 64, 128));
+        assert_eq!(Rgb::from_hsv(360.0, 1.0, 1.0), Rgb(255, 0, 0));
+        assert_eq!(Rgb::from_hsv(0.0, 0.0, 0.0), Rgb(0, 0, 0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_to_hsl() {
+        for &color in &[Rgb(70, 130, 180), Rgb(255, 136, 0), Rgb(12, 200, 99), Rgb(9, 9, 9)] {
+            let (h, s, l) = color.to_hsl();
+            assert_eq!(Rgb::from_hsl(h, s, l), color);
+        }
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_lerp() {
+        let (a, b) = (Rgb(0, 100, 200), Rgb(100, 100, 0));
+        assert_eq!(a.lerp(b, 0.0), a);
+        assert_eq!(a.lerp(b, 1.0), b);
+        assert_eq!(a.lerp(b, 0.5), Rgb(50, 100, 100));
+        assert_eq!(a.lerp(b, 2.0), b);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_lighten_darken() {
+        assert_eq!(Rgb(255, 0, 0).lighten(0.25), Rgb(255, 128, 128));
+        assert_eq!(Rgb(255, 0, 0).darken(0.25), Rgb(128, 0, 0));
+        assert_eq!(Rgb(255, 0, 0).lighten(1.0), Rgb(255, 255, 255));
+        assert_eq!(Rgb(255, 0, 0).darken(1.0), Rgb(0, 0, 0));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_query_palette() {
+        let mut stdout = Vec::new();
+        let mut stdin: &[u8] = b"\x1B]4;3;rgb:cdcd/cdcd/0000\x1B\\\x1B]4;1;rgb:ff/00/80\x07";
+
+        let colors = query_palette(&mut stdout, &mut stdin, &[1, 2, 3]).unwrap();
+        assert_eq!(stdout, b"\x1B]4;1;?\x07\x1B]4;2;?\x07\x1B]4;3;?\x07".to_vec());
+        assert_eq!(colors, vec![Some(Rgb(255, 0, 128)), None, Some(Rgb(205, 205, 0))]);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_query_default_color() {
+        let mut stdout = Vec::new();
+        let mut stdin: &[u8] = b"\x1B]11;rgb:1c1c/1c1c/2020\x1B\\";
+
+        let color = query_default_color(&mut stdout, &mut stdin, 11).unwrap();
+        assert_eq!(stdout, b"\x1B]11;?\x07".to_vec());
+        assert_eq!(color, Rgb(28, 28, 32));
+
+        let mut stdin: &[u8] = b"\x1B]10;rgb:ff/ff/ff\x07";
+        assert!(query_default_color(&mut Vec::new(), &mut stdin, 11).is_err());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_palette_terminal() {
+        let mut out = Vec::new();
+        {
+            let mut term = PaletteTerminal::from(&mut out);
+            term.set_palette(1, Rgb(0xcc, 0x24, 0x1d)).unwrap();
+            term.set_palette(12, Rgb(0x45, 0x85, 0x88)).unwrap();
+            term.set_palette(1, Rgb(0xfb, 0x49, 0x34)).unwrap();
+        }
+
+        assert_eq!(String::from_utf8(out).unwrap(),
+                   "\x1B]4;1;rgb:cc/24/1d\x07\x1B]4;12;rgb:45/85/88\x07\x1B]4;1;rgb:fb/49/34\x07\
+                    \x1B]104;1\x07\x1B]104;12\x07");
+    }
+
+    #[cfg(feature = "std")]
+    fn downgrade(depth: ColorDepth, input: &[u8]) -> String {
+        let mut out = DowngradeColors::new(Vec::new(), depth);
+        out.write_all(input).unwrap();
+        String::from_utf8(out.clone()).unwrap()
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_downgrade_colors() {
+        let input = format!("a{}b{}c{}{}",
+                            Fg(Rgb(255, 95, 0)),
+                            Bg(AnsiValue(200)),
+                            Fg(LightRed),
+                            ::cursor::Goto(1, 2));
+
+        assert_eq!(downgrade(ColorDepth::TrueColor, input.as_bytes()), input);
+        assert_eq!(downgrade(ColorDepth::Ansi256, input.as_bytes()),
+                   "a\x1B[38;5;202mb\x1B[48;5;200mc\x1B[38;5;9m\x1B[2;1H");
+        assert_eq!(downgrade(ColorDepth::Ansi16, input.as_bytes()),
+                   "a\x1B[91mb\x1B[105mc\x1B[91m\x1B[2;1H");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_downgrade_mixed_sgr() {
+        assert_eq!(downgrade(ColorDepth::Ansi16, b"\x1B[1;38;2;0;0;0;4m"),
+                   "\x1B[1;30;4m");
+        assert_eq!(downgrade(ColorDepth::Ansi16, b"\x1B[m"), "\x1B[m");
+    }
+}
@@ -0,0 +1,180 @@
+//! Choosing between 7-bit and 8-bit control sequences.
+//!
+//! Every control sequence can be written in two ways: introduced by ESC and a character (7-bit,
+//! as this crate writes them), or by a single C1 byte in the range 0x80 to 0x9F (8-bit). The
+//! 8-bit form is shorter, but only terminals reading 8-bit bytes understand it, and it is mangled
+//! by UTF-8 terminals and by serial links stripping the high bit. The 7-bit form is thus the
+//! safe default; `ControlWriter` converts to the 8-bit form where it is known to work.
+//!
+//! # Example
+//!
+#![cfg_attr(feature = "std", doc = "```rust")]
+#![cfg_attr(not(feature = "std"), doc = "```rust,ignore")]
+//! use termion::control::{ControlEncoding, ControlWriter};
+//! use termion::cursor;
+//! use std::io::Write;
+//!
+//! let mut out = ControlWriter::new(Vec::new(), ControlEncoding::EightBit);
+//! write!(out, "{}", cursor::Goto(2, 3)).unwrap();
+//! assert_eq!(*out, b"\x9B3;2H".to_vec());
+//! ```
+
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::ops;
+
+#[cfg(feature = "std")]
+use ansi::{Splitter, Token};
+
+derive_esc_sequence!("Ask the terminal to send its replies as 7-bit control sequences (S7C1T).",
+                     SevenBitReplies,
+                     " F");
+derive_esc_sequence!("Ask the terminal to send its replies as 8-bit control sequences (S8C1T).",
+                     EightBitReplies,
+                     " G");
+
+/// How control sequences are written.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum ControlEncoding {
+    /// Introduced by ESC, with string sequences terminated by `ESC \`: the form every terminal
+    /// understands.
+    #[default]
+    SevenBit,
+    /// Introduced by a C1 byte, with string sequences terminated by the ST byte (0x9C), for
+    /// terminals reading 8-bit bytes.
+    EightBit,
+}
+
+/// A writer converting the control sequences written through it to an encoding.
+///
+/// String sequences terminated by BEL rather than ST keep their terminator, as BEL has no 8-bit
+/// form.
+#[cfg(feature = "std")]
+pub struct ControlWriter<W: Write> {
+    output: W,
+    encoding: ControlEncoding,
+    splitter: Splitter,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ControlWriter<W> {
+    /// Write to `output`, converting the control sequences to `encoding`.
+    pub fn new(output: W, encoding: ControlEncoding) -> ControlWriter<W> {
+        ControlWriter {
+            output,
+            encoding,
+            splitter: Splitter::new(),
+        }
+    }
+
+    /// The encoding control sequences are converted to.
+    pub fn encoding(&self) -> ControlEncoding {
+        self.encoding
+    }
+
+    /// Change the encoding control sequences are converted to.
+    pub fn set_encoding(&mut self, encoding: ControlEncoding) {
+        self.encoding = encoding;
+    }
+}
+
+/// Convert the 7-bit control sequence `seq` to the 8-bit form, if it has one.
+#[cfg(feature = "std")]
+fn to_eight_bit(seq: &[u8], out: &mut Vec<u8>) {
+    match seq.get(1) {
+        Some(&c @ 0x40..=0x5F) => {
+            out.push(c + 0x40);
+            let rest = &seq[2..];
+            let is_string = matches!(c, b'P' | b']' | b'X' | b'^' | b'_');
+            match rest.strip_suffix(b"\x1B\\") {
+                Some(body) if is_string => {
+                    out.extend_from_slice(body);
+                    out.push(0x9C);
+                }
+                _ => out.extend_from_slice(rest),
+            }
+        }
+        _ => out.extend_from_slice(seq),
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Drop for ControlWriter<W> {
+    fn drop(&mut self) {
+        // An incomplete sequence is written as it is, rather than lost.
+        let pending = self.splitter.take_pending();
+        let _ = self.output.write_all(&pending);
+        let _ = self.output.flush();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::Deref for ControlWriter<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::DerefMut for ControlWriter<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for ControlWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.encoding == ControlEncoding::SevenBit {
+            let mut pending = self.splitter.take_pending();
+            if pending.is_empty() {
+                return self.output.write(buf);
+            }
+            pending.extend_from_slice(buf);
+            self.output.write_all(&pending)?;
+            return Ok(buf.len());
+        }
+
+        let mut out = Vec::with_capacity(buf.len());
+        self.splitter.split(buf, |token| {
+            match token {
+                Token::Text(text) => out.extend_from_slice(text),
+                Token::Sequence(seq) => to_eight_bit(seq, &mut out),
+            }
+            Ok(())
+        })?;
+        self.output.write_all(&out)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_eight_bit() {
+        let mut out = ControlWriter::new(Vec::new(), ControlEncoding::EightBit);
+        out.write_all(b"a\x1B[1mb\x1B]8;;http://x\x1B\\c\x1B]2;t\x07\x1BM\x1B7\x1B(0").unwrap();
+        out.write_all(b"\x1BP$q").unwrap();
+        out.write_all(b"m\x1B\\").unwrap();
+        assert_eq!(*out,
+                   b"a\x9B1mb\x9D8;;http://x\x9Cc\x9D2;t\x07\x8D\x1B7\x1B(0\x90$qm\x9C".to_vec());
+    }
+
+    #[test]
+    fn test_seven_bit() {
+        let mut out = ControlWriter::new(Vec::new(), ControlEncoding::EightBit);
+        out.write_all(b"\x1B[").unwrap();
+        out.set_encoding(ControlEncoding::SevenBit);
+        out.write_all(b"1m\x1B]2;t\x1B\\").unwrap();
+        assert_eq!(*out, b"\x1B[1m\x1B]2;t\x1B\\".to_vec());
+    }
+}
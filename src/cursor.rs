@@ -1,14 +1,34 @@
 //! Cursor movement.
 
 use std::fmt;
+#[cfg(feature = "std")]
 use std::io::{self, Write, Error, ErrorKind, Read};
+#[cfg(feature = "std")]
+use std::ops;
+#[cfg(feature = "std")]
 use async::async_stdin;
-use std::time::{SystemTime, Duration};
-use raw::CONTROL_SEQUENCE_TIMEOUT;
+use color::Rgb;
+use scroll::Region;
+#[cfg(feature = "std")]
+use reply;
+#[cfg(feature = "std")]
+use std::str;
+#[cfg(feature = "std")]
+use ansi::{Csi, Splitter, Token, Utf8Decoder};
+#[cfg(feature = "std")]
+use width::char_width;
 
 derive_csi_sequence!("Hide the cursor.", Hide, "?25l");
 derive_csi_sequence!("Show the cursor.", Show, "?25h");
 
+derive_csi_sequence!("Count the rows of cursor positions from the top of the scroll region, and \
+                      keep the cursor in the region (origin mode, DECOM).",
+                     EnableOriginMode,
+                     "?6h");
+derive_csi_sequence!("Count the rows of cursor positions from the top of the screen again.",
+                     DisableOriginMode,
+                     "?6l");
+
 derive_csi_sequence!("Restore the cursor.", Restore, "u");
 derive_csi_sequence!("Save the cursor.", Save, "s");
 
@@ -29,9 +49,43 @@ derive_csi_sequence!("Save the cursor.", Save, "s");
 ///     print!("{}{}Stuff", termion::clear::All, termion::cursor::Goto(5, 3));
 /// }
 /// ```
+///
+/// In origin mode (see `EnableOriginMode`), the row is counted from the top of the scroll region
+/// rather than of the screen. Outside of it, `Goto::in_region` addresses the region the same way.
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct Goto(pub u16, pub u16);
 
+impl Goto {
+    /// The position of column `x` and row `y` counted from the top of the scroll region `region`,
+    /// for a terminal not in origin mode.
+    ///
+    /// Rows past the bottom of the region are moved to its bottom, as origin mode does.
+    pub fn in_region(region: Region, x: u16, y: u16) -> Goto {
+        let Region(top, bottom) = region;
+        Goto(x, top.saturating_add(y.max(1) - 1).min(bottom))
+    }
+
+    /// The position of column `x` and row `y`, moved onto a screen of `size` columns and rows.
+    ///
+    /// A column or row of 0, which terminals take as 1, is moved to 1, and those past the edge
+    /// of the screen to the edge, so that a computed position is always one the terminal goes
+    /// to.
+    pub fn clamped(x: u16, y: u16, size: (u16, u16)) -> Goto {
+        Goto(x.clamp(1, size.0.max(1)), y.clamp(1, size.1.max(1)))
+    }
+
+    /// The position `dx` columns right and `dy` rows down of `pos`, on a screen of `size`.
+    ///
+    /// Negative offsets go left and up. The position is kept on the screen as with `clamped`,
+    /// rather than wrapping around when going left of the first column.
+    pub fn relative(pos: (u16, u16), dx: i32, dy: i32, size: (u16, u16)) -> Goto {
+        fn offset(from: u16, delta: i32) -> u16 {
+            (from as i32).saturating_add(delta).clamp(1, u16::MAX as i32) as u16
+        }
+        Goto::clamped(offset(pos.0, dx), offset(pos.1, dy), size)
+    }
+}
+
 impl Default for Goto {
     fn default() -> Goto {
         Goto(1, 1)
@@ -87,54 +141,547 @@ impl fmt::Display for Down {
 }
 
 /// Types that allow detection of the cursor position.
+#[cfg(feature = "std")]
 pub trait DetectCursorPos {
     /// Get the (1,1)-based cursor position from the terminal.
     fn cursor_pos(&mut self) -> io::Result<(u16, u16)>;
 }
 
+#[cfg(feature = "std")]
 impl<W: Write> DetectCursorPos for W {
     fn cursor_pos(&mut self) -> io::Result<(u16, u16)> {
         let mut stdin = async_stdin();
+        query_cursor_pos(self, &mut stdin)
+    }
+}
+
+/// Query the (1,1)-based cursor position, reading the reply from `stdin`.
+#[cfg(feature = "std")]
+pub(crate) fn query_cursor_pos(stdout: &mut dyn Write,
+                               stdin: &mut dyn Read)
+                               -> io::Result<(u16, u16)> {
+    // Where is the cursor?
+    // Use `ESC [ 6 n`.
+    write!(stdout, "\x1B[6n")?;
+    stdout.flush()?;
+
+    // Either consume all data up to R or wait for a timeout.
+    let read_chars = reply::read_until(stdin, |buf| buf.last() == Some(&b'R'))?;
+
+    if read_chars.is_empty() {
+        return Err(Error::new(ErrorKind::TimedOut, "Cursor position detection timed out."));
+    }
+
+    parse_cursor_pos(&read_chars)
+        .ok_or_else(|| Error::new(ErrorKind::InvalidData, "Invalid cursor position report."))
+}
+
+/// Parse a cursor position report (`ESC [ Cy ; Cx R`) into `(Cx, Cy)`.
+#[cfg(feature = "std")]
+fn parse_cursor_pos(reply: &[u8]) -> Option<(u16, u16)> {
+    let start = reply.iter().rposition(|&c| c == b'[')?;
+    let coords = str::from_utf8(&reply[start + 1..]).ok()?.strip_suffix('R')?;
+    let mut nums = coords.split(';').map(|n| n.parse().ok());
 
-        // Where is the cursor?
-        // Use `ESC [ 6 n`.
-        write!(self, "\x1B[6n")?;
-        self.flush()?;
+    let cy = nums.next()??;
+    let cx = nums.next()??;
+    Some((cx, cy))
+}
+
+/// Set the color of the cursor, using OSC 12.
+///
+/// See `CursorColorTerminal` for restoring it afterwards.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct SetColor(pub Rgb);
+
+impl fmt::Display for SetColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let Rgb(r, g, b) = self.0;
+        write!(f, "\x1B]12;rgb:{:02x}/{:02x}/{:02x}\x07", r, g, b)
+    }
+}
+
+/// Reset the color of the cursor to the terminal's default, using OSC 112.
+#[derive(Copy, Clone, Debug)]
+pub struct ResetColor;
+
+impl fmt::Display for ResetColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\x1B]112\x07")
+    }
+}
+
+/// Types that allow detection of the cursor color.
+#[cfg(feature = "std")]
+pub trait DetectCursorColor {
+    /// Get the color of the cursor from the terminal, using OSC 12.
+    fn cursor_color(&mut self) -> io::Result<Rgb>;
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> DetectCursorColor for W {
+    fn cursor_color(&mut self) -> io::Result<Rgb> {
+        let mut stdin = async_stdin();
+        query_cursor_color(self, &mut stdin)
+    }
+}
 
-        let mut buf: [u8; 1] = [0];
-        let mut read_chars = Vec::new();
+/// Query the cursor color using OSC 12.
+#[cfg(feature = "std")]
+pub(crate) fn query_cursor_color(stdout: &mut dyn Write,
+                                 stdin: &mut dyn Read)
+                                 -> io::Result<Rgb> {
+    // Use `ESC ] 12 ; ? BEL`.
+    write!(stdout, "\x1B]12;?\x07")?;
+    stdout.flush()?;
 
-        let timeout = Duration::from_millis(CONTROL_SEQUENCE_TIMEOUT);
-        let now = SystemTime::now();
+    let replies = reply::read_until(stdin, |buf| reply::count_strings(buf) > 0)?;
 
-        // Either consume all data up to R or wait for a timeout.
-        while buf[0] != b'R' && now.elapsed().unwrap() < timeout {
-            if stdin.read(&mut buf)? > 0 {
-                read_chars.push(buf[0]);
+    // The answer will look like `ESC ] 12 ; rgb:RRRR/GGGG/BBBB BEL`.
+    let color = reply::split_strings(&replies)
+        .filter_map(|string| {
+            let start = string.windows(5).position(|w| w == b"\x1B]12;")?;
+            reply::parse_x11_color(&string[start + 5..])
+        })
+        .map(|(r, g, b)| Rgb(r, g, b))
+        .next()
+        .ok_or_else(|| Error::new(ErrorKind::TimedOut, "Cursor color detection timed out."));
+    color
+}
+
+/// A terminal restorer, which restores the cursor color when dropped.
+///
+/// The original color is queried on creation. If the terminal doesn't report it, the cursor
+/// color is reset to the terminal's default instead.
+#[cfg(feature = "std")]
+pub struct CursorColorTerminal<W: Write> {
+    output: W,
+    /// The cursor color found on creation.
+    original: Option<Rgb>,
+    /// Was the cursor color changed?
+    changed: bool,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> CursorColorTerminal<W> {
+    /// Set the color of the cursor until this terminal is dropped.
+    pub fn set_cursor_color(&mut self, color: Rgb) -> io::Result<()> {
+        self.changed = true;
+        write!(self.output, "{}", SetColor(color))
+    }
+
+    /// The cursor color found on creation, if the terminal reported it.
+    pub fn original_cursor_color(&self) -> Option<Rgb> {
+        self.original
+    }
+
+    /// Restore the cursor color found on creation.
+    pub fn restore_cursor_color(&mut self) -> io::Result<()> {
+        if self.changed {
+            match self.original {
+                Some(color) => write!(self.output, "{}", SetColor(color))?,
+                None => write!(self.output, "{}", ResetColor)?,
             }
+            self.changed = false;
         }
+        self.output.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> From<W> for CursorColorTerminal<W> {
+    fn from(mut output: W) -> CursorColorTerminal<W> {
+        let original = output.cursor_color().ok();
 
-        if read_chars.len() == 0 {
-            return Err(Error::new(ErrorKind::Other, "Cursor position detection timed out."));
+        CursorColorTerminal {
+            output,
+            original,
+            changed: false,
         }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Drop for CursorColorTerminal<W> {
+    fn drop(&mut self) {
+        self.restore_cursor_color().expect("restore cursor color");
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::Deref for CursorColorTerminal<W> {
+    type Target = W;
 
-        // The answer will look like `ESC [ Cy ; Cx R`.
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::DerefMut for CursorColorTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for CursorColorTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
 
-        read_chars.pop(); // remove trailing R.
-        let read_str = String::from_utf8(read_chars).unwrap();
-        let beg = read_str.rfind('[').unwrap();
-        let coords: String = read_str.chars().skip(beg + 1).collect();
-        let mut nums = coords.split(';');
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
 
-        let cy = nums.next()
-            .unwrap()
-            .parse::<u16>()
-            .unwrap();
-        let cx = nums.next()
-            .unwrap()
-            .parse::<u16>()
-            .unwrap();
+/// A writer tracking the cursor position from what is written through it.
+///
+/// The bytes written are parsed enough to follow text, line breaks, tabs, wrapping at the right
+/// margin and the cursor movement sequences, so `current_pos` is known without querying the
+/// terminal. The tracker only learns what passes through it: it has to be told where the cursor
+/// starts, and a terminal resize or output written around it makes it lose track.
+///
+/// ```rust,no_run
+/// extern crate termion;
+///
+/// use termion::cursor::CursorTracker;
+/// use std::io::{Write, stdout};
+///
+/// fn main() {
+///     let size = termion::terminal_size().unwrap();
+///     let mut stdout = CursorTracker::new(stdout(), (1, 1), size);
+///
+///     write!(stdout, "{}A rather long prompt: ", termion::cursor::Goto(1, 1)).unwrap();
+///     let (x, y) = stdout.current_pos();
+///     println!("The answer goes at {}, {}.", x, y);
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub struct CursorTracker<W: Write> {
+    output: W,
+    splitter: Splitter,
+    /// The 1-based position of the cursor.
+    pos: (u16, u16),
+    /// The size of the terminal.
+    size: (u16, u16),
+    /// Was the last column written, so that the next character goes on the next line?
+    wrap_pending: bool,
+    /// The position saved by `ESC 7` or `CSI s`.
+    saved: (u16, u16),
+    /// Does a line feed also return the carriage, as it does outside of raw mode?
+    newline_returns: bool,
+    /// The decoder of the text written, holding the start of a character split across writes.
+    decoder: Utf8Decoder,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> CursorTracker<W> {
+    /// Track the cursor written to `output`, starting at the 1-based `pos` on a terminal of
+    /// `size` columns and rows.
+    ///
+    /// A line feed is taken to return the carriage too, as terminals not in raw mode do.
+    pub fn new(output: W, pos: (u16, u16), size: (u16, u16)) -> CursorTracker<W> {
+        let mut tracker = CursorTracker {
+            output,
+            splitter: Splitter::new(),
+            pos: (1, 1),
+            size: (size.0.max(1), size.1.max(1)),
+            wrap_pending: false,
+            saved: (1, 1),
+            newline_returns: true,
+            decoder: Utf8Decoder::new(),
+        };
+        tracker.set_pos(pos.0, pos.1);
+        tracker.saved = tracker.pos;
+        tracker
+    }
+
+    /// The 1-based position the cursor was moved to by what was written so far.
+    pub fn current_pos(&self) -> (u16, u16) {
+        self.pos
+    }
+
+    /// Correct the position of the cursor, e.g. after querying it.
+    pub fn set_pos(&mut self, x: u16, y: u16) {
+        self.pos = (x.clamp(1, self.size.0), y.clamp(1, self.size.1));
+        self.wrap_pending = false;
+    }
+
+    /// Set the size of the terminal, e.g. after it was resized.
+    pub fn set_size(&mut self, width: u16, height: u16) {
+        self.size = (width.max(1), height.max(1));
+        let (x, y) = self.pos;
+        self.set_pos(x, y);
+    }
+
+    /// Set whether a line feed returns the carriage too.
+    ///
+    /// This is the case outside of raw mode, where the terminal translates `\n` to `\r\n`,
+    /// but not in raw mode.
+    pub fn set_newline_returns(&mut self, returns: bool) {
+        self.newline_returns = returns;
+    }
+
+    fn line_feed(&mut self) {
+        self.pos.1 = (self.pos.1 + 1).min(self.size.1);
+    }
+
+    fn text(&mut self, text: &[u8]) {
+        let mut decoder = ::std::mem::replace(&mut self.decoder, Utf8Decoder::new());
+        decoder.decode(text, |c| self.char(c));
+        self.decoder = decoder;
+    }
+
+    fn char(&mut self, c: char) {
+        match c {
+            '\r' => {
+                self.pos.0 = 1;
+                self.wrap_pending = false;
+            }
+            '\n' | '\x0B' | '\x0C' => {
+                self.line_feed();
+                if self.newline_returns {
+                    self.pos.0 = 1;
+                }
+                self.wrap_pending = false;
+            }
+            '\x08' => {
+                self.pos.0 = (self.pos.0 - 1).max(1);
+                self.wrap_pending = false;
+            }
+            '\t' => {
+                self.pos.0 = (((self.pos.0 - 1) / 8 + 1) * 8 + 1).min(self.size.0);
+                self.wrap_pending = false;
+            }
+            c => {
+                let width = char_width(c) as u16;
+                if width == 0 {
+                    return;
+                }
+                if self.wrap_pending || self.pos.0 + width - 1 > self.size.0 {
+                    self.pos.0 = 1;
+                    self.line_feed();
+                    self.wrap_pending = false;
+                }
+                if self.pos.0 + width > self.size.0 {
+                    self.pos.0 = self.size.0;
+                    self.wrap_pending = true;
+                } else {
+                    self.pos.0 += width;
+                }
+            }
+        }
+    }
+
+    fn sequence(&mut self, seq: &[u8]) {
+        let (x, y) = self.pos;
+        match seq {
+            b"\x1B7" => self.saved = self.pos,
+            b"\x1B8" => {
+                let (x, y) = self.saved;
+                self.set_pos(x, y);
+            }
+            b"\x1BD" => {
+                self.line_feed();
+                self.wrap_pending = false;
+            }
+            b"\x1BE" => self.set_pos(1, y.saturating_add(1)),
+            b"\x1BM" => self.set_pos(x, y - 1),
+            b"\x1Bc" => self.set_pos(1, 1),
+            _ => {
+                let csi = match Csi::parse(seq) {
+                    Some(csi) => csi,
+                    None => return,
+                };
+                if csi.private.is_some() || !csi.intermediates.is_empty() {
+                    return;
+                }
+                let n = csi.params.first().cloned().unwrap_or(0).max(1);
+                let m = csi.params.get(1).cloned().unwrap_or(0).max(1);
+                match csi.final_byte {
+                    b'A' => self.set_pos(x, y.saturating_sub(n)),
+                    b'B' | b'e' => self.set_pos(x, y.saturating_add(n)),
+                    b'C' | b'a' => self.set_pos(x.saturating_add(n), y),
+                    b'D' => self.set_pos(x.saturating_sub(n), y),
+                    b'E' => self.set_pos(1, y.saturating_add(n)),
+                    b'F' => self.set_pos(1, y.saturating_sub(n)),
+                    b'G' | b'`' => self.set_pos(n, y),
+                    b'd' => self.set_pos(x, n),
+                    b'H' | b'f' => self.set_pos(m, n),
+                    b's' => self.saved = self.pos,
+                    b'u' => {
+                        let (x, y) = self.saved;
+                        self.set_pos(x, y);
+                    }
+                    _ => (),
+                }
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::Deref for CursorTracker<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::DerefMut for CursorTracker<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for CursorTracker<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write_all(buf)?;
+
+        // The splitter is moved out for the tokens to update the rest of the tracker.
+        let mut splitter = ::std::mem::replace(&mut self.splitter, Splitter::new());
+        let result = splitter.split(buf, |token| {
+            match token {
+                Token::Text(text) => self.text(text),
+                Token::Sequence(seq) => self.sequence(seq),
+            }
+            Ok(())
+        });
+        self.splitter = splitter;
+        result?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_goto_in_region() {
+        assert!(Goto::in_region(Region(5, 10), 3, 1) == Goto(3, 5));
+        assert!(Goto::in_region(Region(5, 10), 3, 4) == Goto(3, 8));
+        assert!(Goto::in_region(Region(5, 10), 3, 20) == Goto(3, 10));
+    }
+
+    #[test]
+    fn test_goto_clamped() {
+        assert!(Goto::clamped(0, 0, (80, 24)) == Goto(1, 1));
+        assert!(Goto::clamped(81, 12, (80, 24)) == Goto(80, 12));
+        assert!(Goto::clamped(5, 5, (0, 0)) == Goto(1, 1));
+
+        assert!(Goto::relative((10, 5), -3, 2, (80, 24)) == Goto(7, 7));
+        assert!(Goto::relative((2, 2), -5, -5, (80, 24)) == Goto(1, 1));
+        assert!(Goto::relative((79, 23), 5, i32::MAX, (80, 24)) == Goto(80, 24));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_parse_cursor_pos() {
+        assert_eq!(parse_cursor_pos(b"\x1B[12;34R"), Some((34, 12)));
+        assert_eq!(parse_cursor_pos(b"typed\x1B[1;1R"), Some((1, 1)));
+        assert_eq!(parse_cursor_pos(b"\x1B[12;34"), None);
+        assert_eq!(parse_cursor_pos(b"\x1B[;R"), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_query_cursor_color() {
+        let mut stdout = Vec::new();
+        let mut stdin: &[u8] = b"\x1B]12;rgb:ffff/8080/0000\x1B\\";
+
+        assert_eq!(query_cursor_color(&mut stdout, &mut stdin).unwrap(), Rgb(255, 128, 0));
+        assert_eq!(stdout, b"\x1B]12;?\x07".to_vec());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_restore_cursor_color() {
+        let mut out = Vec::new();
+        {
+            let mut term = CursorColorTerminal {
+                output: &mut out,
+                original: Some(Rgb(1, 2, 3)),
+                changed: false,
+            };
+            term.set_cursor_color(Rgb(255, 0, 0)).unwrap();
+        }
+        assert_eq!(out, b"\x1B]12;rgb:ff/00/00\x07\x1B]12;rgb:01/02/03\x07".to_vec());
+
+        let mut out = Vec::new();
+        {
+            let mut term = CursorColorTerminal {
+                output: &mut out,
+                original: None,
+                changed: false,
+            };
+            term.set_cursor_color(Rgb(255, 0, 0)).unwrap();
+        }
+        assert_eq!(out, b"\x1B]12;rgb:ff/00/00\x07\x1B]112\x07".to_vec());
+    }
+
+    #[cfg(feature = "std")]
+    fn tracked(size: (u16, u16), written: &[&[u8]]) -> (u16, u16) {
+        let mut tracker = CursorTracker::new(Vec::new(), (1, 1), size);
+        for chunk in written {
+            tracker.write_all(chunk).unwrap();
+        }
+        tracker.current_pos()
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_track_text() {
+        assert_eq!(tracked((10, 5), &[b"abc"]), (4, 1));
+        assert_eq!(tracked((10, 5), &[b"abc\ndef"]), (4, 2));
+        assert_eq!(tracked((10, 5), &[b"abc\rd\x08"]), (1, 1));
+        assert_eq!(tracked((10, 5), &[b"a\tb"]), (10, 1));
+        assert_eq!(tracked((10, 5), &["日本".as_bytes()]), (5, 1));
+        assert_eq!(tracked((10, 5), &[&"日".as_bytes()[..1], &"日".as_bytes()[1..]]), (3, 1));
+        assert_eq!(tracked((10, 2), &[b"\n\n\n"]), (1, 2));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_track_wrap() {
+        // The cursor stays on the last column until something else is written.
+        assert_eq!(tracked((5, 5), &[b"abcde"]), (5, 1));
+        assert_eq!(tracked((5, 5), &[b"abcdef"]), (2, 2));
+        assert_eq!(tracked((5, 5), &[b"abcde\r\n"]), (1, 2));
+        assert_eq!(tracked((5, 5), &["abcd日".as_bytes()]), (3, 2));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_track_sequences() {
+        assert_eq!(tracked((80, 24), &[format!("{}", Goto(10, 5)).as_bytes()]), (10, 5));
+        assert_eq!(tracked((80, 24), &[b"\x1B[5;10H\x1B[2A\x1B[3C"]), (13, 3));
+        assert_eq!(tracked((80, 24), &[b"\x1B[5;10H\x1B[B\x1B[D\x1B[100C"]), (80, 6));
+        assert_eq!(tracked((80, 24), &[b"\x1B[5;10H\x1B7\x1B[H\x1B8"]), (10, 5));
+        assert_eq!(tracked((80, 24), &[b"\x1B[5;1", b"0H\x1B[31mab"]), (12, 5));
+        assert_eq!(tracked((80, 24), &[b"\x1B[5;10H\x1B]0;title\x07\x1B[?25l"]), (10, 5));
+    }
 
-        Ok((cx, cy))
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_track_raw_newline() {
+        let mut tracker = CursorTracker::new(Vec::new(), (3, 1), (80, 24));
+        tracker.set_newline_returns(false);
+        tracker.write_all(b"ab\n").unwrap();
+        assert_eq!(tracker.current_pos(), (5, 2));
+        assert_eq!(*tracker, b"ab\n".to_vec());
     }
 }
@@ -0,0 +1,596 @@
+//! Debugging terminal programs: transcripts of their output, and recordings of their input.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::debug::DebugWriter;
+//! use std::fs::File;
+//! use std::io::{Write, stdout};
+//!
+//! fn main() {
+//!     let log = File::create("transcript.log").unwrap();
+//!     let mut stdout = DebugWriter::new(stdout(), log);
+//!
+//!     write!(stdout, "{}{}Hello!\n", termion::clear::All, termion::cursor::Goto(1, 1)).unwrap();
+//! }
+//! ```
+//!
+//! The transcript then reads:
+//!
+//! ```text
+//! CSI 2 J
+//! CSI 1;1 H
+//! "Hello!"
+//! LF
+//! ```
+//!
+//! # Recording the input
+//!
+//! An `InputRecorder` writes down what is read from the terminal, with the time between the
+//! reads, and an `InputReplay` reads it back, so that a bug found by typing can be reproduced
+//! exactly:
+//!
+//! ```rust,no_run
+//! use termion::debug::{InputRecorder, InputReplay};
+//! use termion::input::TermRead;
+//! use std::fs::File;
+//! use std::io::stdin;
+//!
+//! let recording = File::create("keys.rec").unwrap();
+//! for key in InputRecorder::new(stdin(), recording).keys() {
+//!     // Run the program...
+//! }
+//!
+//! // Later, or in a test:
+//! let replay = InputReplay::new(File::open("keys.rec").unwrap());
+//! for key in replay.keys() {
+//!     // Run the program again, on the same keys...
+//! }
+//! ```
+//!
+//! A recording has a line per read: the milliseconds since the previous read, then the bytes
+//! read in hexadecimal, such as `120 1b5b41` for the up arrow typed 120 ms after the previous
+//! key. Recordings are easily written by hand to script keys in a test.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::ops;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use ansi::{Splitter, Token};
+
+/// A writer, which passes the bytes written to it through to the terminal, while writing a
+/// human-readable transcript of them to a log.
+///
+/// Escape sequences are rendered symbolically, one per line.
+pub struct DebugWriter<W: Write, L: Write> {
+    output: W,
+    log: L,
+    splitter: Splitter,
+    /// The start of a character split across writes.
+    partial: Vec<u8>,
+}
+
+impl<W: Write, L: Write> DebugWriter<W, L> {
+    /// Pass the bytes through to `output`, while writing the transcript to `log`.
+    pub fn new(output: W, log: L) -> DebugWriter<W, L> {
+        DebugWriter {
+            output,
+            log,
+            splitter: Splitter::new(),
+            partial: Vec::new(),
+        }
+    }
+
+    /// The log the transcript is written to.
+    pub fn log(&mut self) -> &mut L {
+        &mut self.log
+    }
+}
+
+impl<W: Write, L: Write> ops::Deref for DebugWriter<W, L> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+impl<W: Write, L: Write> ops::DerefMut for DebugWriter<W, L> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+impl<W: Write, L: Write> Write for DebugWriter<W, L> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.output.write(buf)?;
+
+        let (log, partial) = (&mut self.log, &mut self.partial);
+        self.splitter.split(&buf[..n], |token| write_held(log, partial, token))?;
+
+        Ok(n)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        let n = self.output.write_vectored(bufs)?;
+
+        let (log, partial) = (&mut self.log, &mut self.partial);
+        let mut left = n;
+        for buf in bufs {
+            let written = left.min(buf.len());
+            self.splitter.split(&buf[..written], |token| write_held(log, partial, token))?;
+            left -= written;
+        }
+
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.log.flush()?;
+        self.output.flush()
+    }
+}
+
+/// A reader, which passes the bytes read through, while recording them with their timing.
+///
+/// See the module documentation for the format of the recording.
+pub struct InputRecorder<R: Read, L: Write> {
+    input: R,
+    recording: L,
+    last_read: Instant,
+    /// The error recording the bytes last read, if not taken yet.
+    error: Option<io::Error>,
+}
+
+impl<R: Read, L: Write> InputRecorder<R, L> {
+    /// Pass the bytes read from `input` through, while recording them to `recording`.
+    pub fn new(input: R, recording: L) -> InputRecorder<R, L> {
+        InputRecorder {
+            input,
+            recording,
+            last_read: Instant::now(),
+            error: None,
+        }
+    }
+
+    /// The recording the bytes are written to.
+    pub fn recording(&mut self) -> &mut L {
+        &mut self.recording
+    }
+
+    /// Take the error met writing to the recording, if any.
+    ///
+    /// Reading doesn't fail when the recording does, as the bytes read would be lost: they are
+    /// still passed through, and the error is kept for this.
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+
+    fn record(&mut self, bytes: &[u8]) -> io::Result<()> {
+        let now = Instant::now();
+        let delay = now.duration_since(self.last_read).as_millis();
+        self.last_read = now;
+
+        write!(self.recording, "{} ", delay)?;
+        for b in bytes {
+            write!(self.recording, "{:02x}", b)?;
+        }
+        self.recording.write_all(b"\n")?;
+        self.recording.flush()
+    }
+}
+
+impl<R: Read, L: Write> Read for InputRecorder<R, L> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let n = self.input.read(buf)?;
+        if n > 0 {
+            if let Err(err) = self.record(&buf[..n]) {
+                self.error = Some(err);
+            }
+        }
+        Ok(n)
+    }
+}
+
+/// A reader, which reads back the bytes of a recording, in the same chunks and, unless told
+/// otherwise, with the same timing.
+///
+/// Keeping the chunks keeps e.g. a lone `Esc` from being taken for the start of a sequence. Lines
+/// which are empty or start with `#` are skipped.
+pub struct InputReplay<R: Read> {
+    recording: BufReader<R>,
+    timing: bool,
+    /// The rest of the current chunk.
+    pending: Vec<u8>,
+}
+
+impl<R: Read> InputReplay<R> {
+    /// Read back `recording`.
+    pub fn new(recording: R) -> InputReplay<R> {
+        InputReplay {
+            recording: BufReader::new(recording),
+            timing: true,
+            pending: Vec::new(),
+        }
+    }
+
+    /// Choose whether to wait between the chunks as long as when they were recorded, rather
+    /// than reading them back right away.
+    pub fn set_timing(&mut self, timing: bool) {
+        self.timing = timing;
+    }
+
+    /// The next chunk of the recording and its delay, or `None` at the end.
+    fn next_chunk(&mut self) -> io::Result<Option<(Duration, Vec<u8>)>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.recording.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            return parse_chunk(line).map(Some).ok_or_else(|| {
+                io::Error::new(io::ErrorKind::InvalidData,
+                               format!("Invalid line in the recording: {:?}", line))
+            });
+        }
+    }
+}
+
+impl<R: Read> Read for InputReplay<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.pending.is_empty() {
+            match self.next_chunk()? {
+                Some((delay, chunk)) => {
+                    if self.timing {
+                        thread::sleep(delay);
+                    }
+                    self.pending = chunk;
+                }
+                None => return Ok(0),
+            }
+        }
+
+        let n = buf.len().min(self.pending.len());
+        buf[..n].copy_from_slice(&self.pending[..n]);
+        self.pending.drain(..n);
+        Ok(n)
+    }
+}
+
+/// Parse a line of a recording: the delay in milliseconds, then the bytes in hexadecimal.
+fn parse_chunk(line: &str) -> Option<(Duration, Vec<u8>)> {
+    let (delay, hex) = line.split_once(' ')?;
+    let delay = Duration::from_millis(delay.parse().ok()?);
+    let hex = hex.trim().as_bytes();
+    if hex.len() % 2 != 0 {
+        return None;
+    }
+
+    let bytes = hex.chunks(2)
+        .map(|pair| u8::from_str_radix(::std::str::from_utf8(pair).ok()?, 16).ok())
+        .collect::<Option<Vec<u8>>>()?;
+    Some((delay, bytes))
+}
+
+/// Render the bytes of `buf` as a transcript, like `DebugWriter` does.
+///
+/// # Example
+///
+/// ```rust
+/// use termion::debug::transcript;
+///
+/// assert_eq!(transcript(b"\x1B[2Jhi\r\n"), "CSI 2 J\n\"hi\"\nCR\nLF\n");
+/// ```
+pub fn transcript(buf: &[u8]) -> String {
+    let mut out = Vec::new();
+    let mut splitter = Splitter::new();
+
+    splitter.split(buf, |token| write_token(&mut out, token)).unwrap();
+    // Render a dangling incomplete sequence too.
+    let pending = splitter.take_pending();
+    if !pending.is_empty() {
+        write_token(&mut out, Token::Sequence(&pending)).unwrap();
+    }
+
+    String::from_utf8(out).unwrap()
+}
+
+/// Write the transcript of a token, holding back an incomplete character at the end of a text
+/// in `partial`.
+///
+/// The character is completed by the next text. If a sequence comes first instead, it is
+/// written as it is, escaped.
+fn write_held<L: Write>(log: &mut L, partial: &mut Vec<u8>, token: Token) -> io::Result<()> {
+    let mut text = ::std::mem::take(partial);
+    match token {
+        Token::Text(rest) => {
+            text.extend_from_slice(rest);
+            let len = complete_len(&text);
+            *partial = text[len..].to_vec();
+            write_text(log, &text[..len])
+        }
+        seq => {
+            write_text(log, &text)?;
+            write_token(log, seq)
+        }
+    }
+}
+
+/// Write the transcript of a single token.
+fn write_token<L: Write>(log: &mut L, token: Token) -> io::Result<()> {
+    match token {
+        Token::Text(text) => write_text(log, text),
+        Token::Sequence(seq) => {
+            write_sequence(log, seq)?;
+            log.write_all(b"\n")
+        }
+    }
+}
+
+/// Write text, one line per run of printable characters and one per control character.
+fn write_text<L: Write>(log: &mut L, text: &[u8]) -> io::Result<()> {
+    let mut rest = text;
+
+    while let Some(&first) = rest.first() {
+        if let Some(name) = control_name(first) {
+            writeln!(log, "{}", name)?;
+            rest = &rest[1..];
+            continue;
+        }
+
+        let len = rest.iter().position(|&c| control_name(c).is_some()).unwrap_or(rest.len());
+        let (run, tail) = rest.split_at(len);
+        log.write_all(b"\"")?;
+        write_escaped(log, run)?;
+        log.write_all(b"\"\n")?;
+        rest = tail;
+    }
+
+    Ok(())
+}
+
+/// The length of `text` without the incomplete character at its end, if any.
+fn complete_len(text: &[u8]) -> usize {
+    let mut start = 0;
+    loop {
+        match ::std::str::from_utf8(&text[start..]) {
+            Ok(_) => return text.len(),
+            Err(e) => match e.error_len() {
+                Some(len) => start += e.valid_up_to() + len,
+                None => return start + e.valid_up_to(),
+            },
+        }
+    }
+}
+
+/// Write an escape sequence symbolically.
+fn write_sequence<L: Write>(log: &mut L, seq: &[u8]) -> io::Result<()> {
+    let body = &seq[1..];
+    let (intro, body) = match body.first() {
+        Some(&b'[') => ("CSI", &body[1..]),
+        Some(&b']') => ("OSC", &body[1..]),
+        Some(&b'P') => ("DCS", &body[1..]),
+        Some(&b'_') => ("APC", &body[1..]),
+        Some(&b'^') => ("PM", &body[1..]),
+        Some(&b'X') => ("SOS", &body[1..]),
+        _ => ("ESC", body),
+    };
+    log.write_all(intro.as_bytes())?;
+
+    match intro {
+        "CSI" => {
+            // Separate the private marker, the parameters, the intermediates and the final byte.
+            let mut prev = None;
+            for &c in body {
+                let class = match c {
+                    b'<'..=b'?' => 0,
+                    b'0'..=b';' => 1,
+                    0x20..=0x2F => 2,
+                    _ => 3,
+                };
+                if prev != Some(class) || class == 3 {
+                    log.write_all(b" ")?;
+                }
+                write_intermediate(log, c)?;
+                prev = Some(class);
+            }
+            Ok(())
+        }
+        "ESC" => {
+            for &c in body {
+                log.write_all(b" ")?;
+                write_intermediate(log, c)?;
+            }
+            Ok(())
+        }
+        _ => {
+            // A string sequence, terminated by BEL or ST (if complete).
+            let (string, terminator) = if body.ends_with(b"\x07") {
+                (&body[..body.len() - 1], " BEL")
+            } else if body.ends_with(b"\x1B\\") {
+                (&body[..body.len() - 2], " ST")
+            } else {
+                (body, "")
+            };
+            log.write_all(b" ")?;
+            write_escaped(log, string)?;
+            log.write_all(terminator.as_bytes())
+        }
+    }
+}
+
+/// Write a byte of an escape sequence, naming the space so that it stays visible.
+fn write_intermediate<L: Write>(log: &mut L, c: u8) -> io::Result<()> {
+    if c == b' ' {
+        log.write_all(b"SP")
+    } else {
+        write_escaped(log, &[c])
+    }
+}
+
+/// Write bytes, escaping what isn't printable UTF-8.
+fn write_escaped<L: Write>(log: &mut L, mut bytes: &[u8]) -> io::Result<()> {
+    while !bytes.is_empty() {
+        let (valid, invalid) = match ::std::str::from_utf8(bytes) {
+            Ok(s) => (s, &b""[..]),
+            Err(e) => {
+                let (valid, rest) = bytes.split_at(e.valid_up_to());
+                let len = e.error_len().unwrap_or(rest.len());
+                (::std::str::from_utf8(valid).unwrap(), &rest[..len])
+            }
+        };
+
+        for c in valid.chars() {
+            match c {
+                '"' => log.write_all(b"\\\"")?,
+                '\\' => log.write_all(b"\\\\")?,
+                c if c.is_control() => write!(log, "\\u{{{:x}}}", c as u32)?,
+                c => write!(log, "{}", c)?,
+            }
+        }
+        for &b in invalid {
+            write!(log, "\\x{:02X}", b)?;
+        }
+
+        bytes = &bytes[valid.len() + invalid.len()..];
+    }
+
+    Ok(())
+}
+
+/// The name of a C0 control character.
+fn control_name(c: u8) -> Option<&'static str> {
+    const NAMES: [&str; 32] = ["NUL", "SOH", "STX", "ETX", "EOT", "ENQ", "ACK", "BEL", "BS", "HT",
+                               "LF", "VT", "FF", "CR", "SO", "SI", "DLE", "DC1", "DC2", "DC3",
+                               "DC4", "NAK", "SYN", "ETB", "CAN", "EM", "SUB", "ESC", "FS", "GS",
+                               "RS", "US"];
+
+    match c {
+        0..=0x1F => Some(NAMES[c as usize]),
+        0x7F => Some("DEL"),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_transcript() {
+        assert_eq!(transcript(b"\x1B[?1049h\x1B[38;5;1mred \"q\"\x1B[m\x07"),
+                   "CSI ? 1049 h\nCSI 38;5;1 m\n\"red \\\"q\\\"\"\nCSI m\nBEL\n");
+        assert_eq!(transcript(b"\x1B(0q\x1B(B\x1B7\x1B[2 q"),
+                   "ESC ( 0\n\"q\"\nESC ( B\nESC 7\nCSI 2 SP q\n");
+        assert_eq!(transcript(b"\x1B]4;1;?\x07\x1B]0;t\x1B\\\x1BP>|x"),
+                   "OSC 4;1;? BEL\nOSC 0;t ST\nDCS >|x\n");
+        assert_eq!(transcript(b"\xFFa\xC3\xA9\t"), "\"\\xFFaé\"\nHT\n");
+    }
+
+    #[test]
+    fn test_record_replay() {
+        let mut recording = Vec::new();
+        {
+            let mut input = InputRecorder::new(&b"a\x1B[A"[..], &mut recording);
+            let mut buf = [0; 2];
+            assert_eq!(input.read(&mut buf).unwrap(), 2);
+            assert_eq!(input.read(&mut buf).unwrap(), 2);
+            assert_eq!(input.read(&mut buf).unwrap(), 0);
+        }
+        let recording = String::from_utf8(recording).unwrap();
+        let lines: Vec<&str> = recording.lines().collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with(" 611b") && lines[1].ends_with(" 5b41"));
+
+        let mut replay = InputReplay::new("# Up, after Esc\n0 1b\n\n50 1b5b41\n".as_bytes());
+        replay.set_timing(false);
+        let mut buf = [0; 8];
+        assert_eq!(replay.read(&mut buf).unwrap(), 1);
+        assert_eq!(replay.read(&mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], b"\x1B[A");
+        assert_eq!(replay.read(&mut buf).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_recording_error() {
+        let mut recording = [0; 4];
+        let mut input = InputRecorder::new(&b"abc"[..], &mut recording[..]);
+        let mut buf = [0; 8];
+        assert_eq!(input.read(&mut buf).unwrap(), 3);
+        assert_eq!(&buf[..3], b"abc");
+        assert_eq!(input.take_error().unwrap().kind(), io::ErrorKind::WriteZero);
+        assert!(input.take_error().is_none());
+    }
+
+    #[test]
+    fn test_replay_errors() {
+        let mut buf = [0; 8];
+        for &recording in &["10 1b5\n", "x 1b\n", "10\n", "10 zz\n"] {
+            let err = InputReplay::new(recording.as_bytes()).read(&mut buf).unwrap_err();
+            assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        }
+
+        let start = Instant::now();
+        assert_eq!(InputReplay::new(&b"30 61\n"[..]).read(&mut buf).unwrap(), 1);
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[test]
+    fn test_debug_writer() {
+        let mut output = Vec::new();
+        let mut log = Vec::new();
+        {
+            let mut writer = DebugWriter::new(&mut output, &mut log);
+            writer.write_all(b"ab\x1B[").unwrap();
+            writer.write_all(b"1;2Hc").unwrap();
+        }
+
+        assert_eq!(output, b"ab\x1B[1;2Hc".to_vec());
+        assert_eq!(String::from_utf8(log).unwrap(), "\"ab\"\nCSI 1;2 H\n\"c\"\n");
+    }
+
+    #[test]
+    fn test_debug_writer_split_char() {
+        let mut log = Vec::new();
+        {
+            let mut writer = DebugWriter::new(io::sink(), &mut log);
+            writer.write_all(b"a\xC3").unwrap();
+            writer.write_all(b"\xA9\xE2\x82").unwrap();
+            writer.write_all(b"\xAC").unwrap();
+            // Never completed.
+            writer.write_all(b"\xC3\x1B[m\xC3\n").unwrap();
+        }
+
+        assert_eq!(String::from_utf8(log).unwrap(),
+                   "\"a\"\n\"é\"\n\"€\"\n\"\\xC3\"\nCSI m\n\"\\xC3\"\nLF\n");
+        assert_eq!(transcript(b"a\xC3"), "\"a\\xC3\"\n");
+    }
+
+    #[test]
+    fn test_debug_writer_vectored() {
+        let bufs = [
+            io::IoSlice::new(b"ab\x1B["),
+            io::IoSlice::new(b"1;2H"),
+            io::IoSlice::new(b"c"),
+        ];
+        let mut output = Vec::new();
+        let mut log = Vec::new();
+        assert_eq!(DebugWriter::new(&mut output, &mut log).write_vectored(&bufs).unwrap(), 9);
+        assert_eq!(output, b"ab\x1B[1;2Hc".to_vec());
+        assert_eq!(String::from_utf8(log).unwrap(), "\"ab\"\nCSI 1;2 H\n\"c\"\n");
+
+        // Only what the output took is logged.
+        let mut output = [0; 6];
+        let mut log = Vec::new();
+        let mut writer = DebugWriter::new(&mut output[..], &mut log);
+        assert_eq!(writer.write_vectored(&bufs).unwrap(), 6);
+        assert_eq!(writer.write_vectored(&bufs[2..]).unwrap(), 0);
+        drop(writer);
+        assert_eq!(String::from_utf8(log).unwrap(), "\"ab\"\n");
+    }
+}
@@ -0,0 +1,46 @@
+//! Editing a line in place.
+//!
+//! A line editor inserting a character in the middle of a line can let the terminal shift the
+//! rest of the line, rather than printing it again: in insert mode, the characters written push
+//! those after the cursor to the right. Likewise, `DeleteChars` pulls them to the left.
+//!
+//! ```rust,no_run
+//! use termion::{cursor, edit};
+//! use std::io::{Write, stdout};
+//!
+//! let mut stdout = stdout();
+//! write!(stdout, "helo{}{}l{}", cursor::Left(1), edit::EnableInsertMode, edit::DisableInsertMode)
+//!     .unwrap();
+//! ```
+//!
+//! Insert mode stays on until disabled, including for the output of the next program run in the
+//! terminal, so it should be disabled before exiting.
+
+use std::fmt;
+
+derive_csi_sequence!("Insert the characters written before those after the cursor (IRM).",
+                     EnableInsertMode,
+                     "4h");
+derive_csi_sequence!("Overwrite the characters after the cursor again (the default).",
+                     DisableInsertMode,
+                     "4l");
+
+/// Insert blank characters at the cursor, shifting the rest of the line to the right (ICH).
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct InsertChars(pub u16);
+
+impl fmt::Display for InsertChars {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, csi!("{}@"), self.0)
+    }
+}
+
+/// Delete characters at the cursor, shifting the rest of the line to the left (DCH).
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct DeleteChars(pub u16);
+
+impl fmt::Display for DeleteChars {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, csi!("{}P"), self.0)
+    }
+}
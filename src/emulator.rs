@@ -0,0 +1,1187 @@
+//! A headless VT100/xterm emulator, for testing what an application draws.
+//!
+//! An `Emulator` is written to like a terminal, and keeps the screen it would show: the cells
+//! with their style, the cursor, the modes, the scroll region and the current graphic rendition.
+//! An interface can thus run its whole render path in a test, and assert on the result rather
+//! than on the escape sequences written.
+//!
+//! The emulator answers the position and status reports (DSR), the primary device attributes
+//! (DA1) and the requests of the rendition and scroll margins (DECRQSS); the answers are kept
+//! until taken with `take_replies`.
+//!
+//! For snapshot tests (with `insta` or the like), `render_to_string` and
+//! `render_to_styled_string` turn an escape stream into a plain-text screenshot, the latter
+//! marking up the styled text.
+//!
+//! # Example
+//!
+//! ```rust
+//! use termion::color;
+//! use termion::cursor;
+//! use termion::emulator::Emulator;
+//! use std::io::Write;
+//!
+//! let mut term = Emulator::new(20, 5);
+//! write!(term, "{}{}Hello{}", cursor::Goto(3, 2), color::Fg(color::Red), cursor::Hide).unwrap();
+//!
+//! assert_eq!(term.line(2), "  Hello");
+//! assert_eq!(term.cursor_pos(), (8, 2));
+//! assert!(!term.modes().cursor_visible);
+//! ```
+
+use std::fmt;
+use std::io::{self, Write};
+use std::mem;
+
+use ansi::{Csi, Splitter, Token, Utf8Decoder};
+use buffer::{Cell, CellColor, CellStyle};
+use color::Rgb;
+use tab::TabStops;
+use width::{char_width, extends_grapheme};
+
+/// The characters of the DEC special graphics character set, replacing `` ` `` to `~`.
+const DEC_GRAPHICS: &str = "◆▒␉␌␍␊°±␤␋┘┐┌└┼⎺⎻─⎼⎽├┤┴┬│≤≥π≠£·";
+
+/// The modes of the terminal, as set by `SM`/`RM` and `DECSET`/`DECRST`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Modes {
+    /// Is the cursor shown (DECTCEM, `?25`)?
+    pub cursor_visible: bool,
+    /// Does writing past the last column wrap to the next line (DECAWM, `?7`)?
+    pub auto_wrap: bool,
+    /// Are rows relative to the scroll region (DECOM, `?6`)?
+    pub origin: bool,
+    /// Are the colors of the whole screen reversed (DECSCNM, `?5`)?
+    pub reverse_video: bool,
+    /// Are characters inserted rather than overwritten (IRM, `4`)?
+    pub insert: bool,
+    /// Does a line feed also return the carriage (LNM, `20`)?
+    pub newline: bool,
+    /// Do the arrow keys send application sequences (DECCKM, `?1`)?
+    pub application_cursor: bool,
+    /// Does the keypad send application sequences (DECKPAM, `ESC =`)?
+    pub application_keypad: bool,
+    /// Is the alternate screen shown (`?47`, `?1047` or `?1049`)?
+    pub alternate_screen: bool,
+    /// Does the mouse wheel send arrow keys on the alternate screen (`?1007`)?
+    pub alternate_scroll: bool,
+    /// Are mouse button presses reported (`?1000`)?
+    pub mouse_press: bool,
+    /// Are mouse moves with a button held reported (`?1002`)?
+    pub mouse_drag: bool,
+    /// Are all mouse moves reported (`?1003`)?
+    pub mouse_motion: bool,
+    /// Are mouse reports in the SGR format (`?1006`)?
+    pub mouse_sgr: bool,
+    /// Are mouse reports in the urxvt format (`?1015`)?
+    pub mouse_urxvt: bool,
+    /// Are focus changes reported (`?1004`)?
+    pub focus_events: bool,
+    /// Is pasted text bracketed (`?2004`)?
+    pub bracketed_paste: bool,
+}
+
+impl Default for Modes {
+    /// The modes of a terminal after a reset.
+    fn default() -> Modes {
+        Modes {
+            cursor_visible: true,
+            auto_wrap: true,
+            origin: false,
+            reverse_video: false,
+            insert: false,
+            newline: false,
+            application_cursor: false,
+            application_keypad: false,
+            alternate_screen: false,
+            alternate_scroll: false,
+            mouse_press: false,
+            mouse_drag: false,
+            mouse_motion: false,
+            mouse_sgr: false,
+            mouse_urxvt: false,
+            focus_events: false,
+            bracketed_paste: false,
+        }
+    }
+}
+
+/// The state saved by `DECSC` and restored by `DECRC`.
+#[derive(Copy, Clone, Debug, Default)]
+struct SavedCursor {
+    pos: (u16, u16),
+    style: CellStyle,
+    origin: bool,
+    /// Are G0 and G1 the DEC special graphics?
+    graphics: [bool; 2],
+    /// Is G1 selected?
+    shifted: bool,
+}
+
+/// A headless terminal, keeping the screen drawn by what is written to it.
+///
+/// Coordinates are (1,1)-based, like `cursor::Goto`. Writing never fails.
+pub struct Emulator {
+    width: u16,
+    height: u16,
+    /// The rows of the screen shown.
+    rows: Vec<Vec<Cell>>,
+    /// The rows of the other screen: the primary one while the alternate one is shown, and the
+    /// reverse.
+    other_rows: Vec<Vec<Cell>>,
+    cursor: (u16, u16),
+    /// Was the last column written, so that the next character goes on the next line?
+    wrap_pending: bool,
+    /// The graphic rendition characters are drawn with.
+    style: CellStyle,
+    saved: SavedCursor,
+    /// The cursor saved when entering the alternate screen through `?1049`.
+    saved_primary: SavedCursor,
+    /// The first and last rows of the scroll region.
+    scroll_region: (u16, u16),
+    tabs: TabStops,
+    modes: Modes,
+    graphics: [bool; 2],
+    shifted: bool,
+    /// The cell the last character was drawn in, which combining marks are added to.
+    last_cell: Option<(u16, u16)>,
+    /// The last character drawn, repeated by `REP`.
+    last_char: Option<char>,
+    title: String,
+    /// The number of times the bell rang.
+    bells: usize,
+    replies: Vec<u8>,
+    splitter: Splitter,
+    decoder: Utf8Decoder,
+}
+
+impl Emulator {
+    /// A blank terminal of `width` columns and `height` rows.
+    pub fn new(width: u16, height: u16) -> Emulator {
+        let (width, height) = (width.max(1), height.max(1));
+        Emulator {
+            width,
+            height,
+            rows: blank_rows(width, height),
+            other_rows: blank_rows(width, height),
+            cursor: (1, 1),
+            wrap_pending: false,
+            style: CellStyle::default(),
+            saved: SavedCursor::default(),
+            saved_primary: SavedCursor::default(),
+            scroll_region: (1, height),
+            tabs: TabStops::new(width),
+            modes: Modes::default(),
+            graphics: [false; 2],
+            shifted: false,
+            last_cell: None,
+            last_char: None,
+            title: String::new(),
+            bells: 0,
+            replies: Vec::new(),
+            splitter: Splitter::new(),
+            decoder: Utf8Decoder::new(),
+        }
+    }
+
+    /// The number of columns and rows.
+    pub fn size(&self) -> (u16, u16) {
+        (self.width, self.height)
+    }
+
+    /// Resize the screen, as a terminal window would be.
+    ///
+    /// Rows are cut or added at the bottom, columns at the right. The scroll region is reset,
+    /// and so are the tab stops.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        let (width, height) = (width.max(1), height.max(1));
+        for rows in &mut [&mut self.rows, &mut self.other_rows] {
+            rows.resize(height as usize, blank_row(width));
+            for row in rows.iter_mut() {
+                row.resize(width as usize, Cell::default());
+                repair_wide(row, width as usize - 1, width as usize);
+            }
+        }
+        self.width = width;
+        self.height = height;
+        self.scroll_region = (1, height);
+        self.tabs = TabStops::new(width);
+        self.last_cell = None;
+        let (x, y) = self.cursor;
+        self.set_pos(x, y);
+    }
+
+    /// The cell at column `x` and row `y`, if it is on the screen.
+    ///
+    /// The cell following a wide character has an empty symbol.
+    pub fn cell(&self, x: u16, y: u16) -> Option<&Cell> {
+        if x == 0 || y == 0 {
+            return None;
+        }
+        self.rows.get(y as usize - 1)?.get(x as usize - 1)
+    }
+
+    /// The text of row `y`, without the trailing blanks.
+    pub fn line(&self, y: u16) -> String {
+        let mut line = String::new();
+        if let Some(row) = y.checked_sub(1).and_then(|y| self.rows.get(y as usize)) {
+            for cell in row {
+                line.push_str(&cell.symbol);
+            }
+        }
+        line.truncate(line.trim_end_matches(' ').len());
+        line
+    }
+
+    /// The text of the screen, a line per row, without the trailing blanks and blank lines.
+    pub fn contents(&self) -> String {
+        let mut lines: Vec<String> = (1..=self.height).map(|y| self.line(y)).collect();
+        while lines.last().is_some_and(|line| line.is_empty()) {
+            lines.pop();
+        }
+        lines.join("\n")
+    }
+
+    /// A plain-text screenshot, for snapshot tests: a line per row, blank rows included, without
+    /// the trailing blanks.
+    pub fn screenshot(&self) -> String {
+        (1..=self.height).map(|y| self.line(y)).collect::<Vec<String>>().join("\n")
+    }
+
+    /// A screenshot marking up the styled text, for snapshot tests.
+    ///
+    /// Like `screenshot`, but each run of cells drawn with the same style other than the default
+    /// one is written as `[style]text[/]`. The style lists the attributes set (`bold`, `faint`,
+    /// `italic`, `underline`, `blink`, `invert` and `crossed_out`) and the colors, as `fg=1` or
+    /// `bg=#ff8000`. A `[` of the text is written twice.
+    pub fn styled_screenshot(&self) -> String {
+        let mut lines = Vec::new();
+        for row in &self.rows {
+            let end = row.iter()
+                .rposition(|cell| cell.symbol != " " || cell.style != CellStyle::default())
+                .map_or(0, |i| i + 1);
+
+            let mut line = String::new();
+            let mut style = CellStyle::default();
+            for cell in &row[..end] {
+                if cell.style != style {
+                    if style != CellStyle::default() {
+                        line.push_str("[/]");
+                    }
+                    if cell.style != CellStyle::default() {
+                        line.push_str(&style_markup(&cell.style));
+                    }
+                    style = cell.style;
+                }
+                for c in cell.symbol.chars() {
+                    if c == '[' {
+                        line.push_str("[[");
+                    } else {
+                        line.push(c);
+                    }
+                }
+            }
+            if style != CellStyle::default() {
+                line.push_str("[/]");
+            }
+            lines.push(line);
+        }
+        lines.join("\n")
+    }
+
+    /// The position of the cursor.
+    pub fn cursor_pos(&self) -> (u16, u16) {
+        self.cursor
+    }
+
+    /// The modes of the terminal.
+    pub fn modes(&self) -> &Modes {
+        &self.modes
+    }
+
+    /// The graphic rendition new characters are drawn with.
+    pub fn style(&self) -> CellStyle {
+        self.style
+    }
+
+    /// The first and last rows of the scroll region.
+    pub fn scroll_region(&self) -> (u16, u16) {
+        self.scroll_region
+    }
+
+    /// The window title, as set by `OSC 0` or `OSC 2`.
+    pub fn title(&self) -> &str {
+        &self.title
+    }
+
+    /// The number of times the bell (BEL) rang.
+    pub fn bells(&self) -> usize {
+        self.bells
+    }
+
+    /// Take the replies to the queries written so far, which a terminal would have sent to the
+    /// application's input.
+    pub fn take_replies(&mut self) -> Vec<u8> {
+        mem::take(&mut self.replies)
+    }
+
+    /// A blank cell, with the current background color like erased cells in xterm.
+    fn blank(&self) -> Cell {
+        Cell {
+            symbol: " ".to_string(),
+            style: CellStyle {
+                bg: self.style.bg,
+                ..CellStyle::default()
+            },
+        }
+    }
+
+    fn blank_row(&self) -> Vec<Cell> {
+        vec![self.blank(); self.width as usize]
+    }
+
+    /// Move the cursor, keeping it on the screen, and in the scroll region in origin mode.
+    fn set_pos(&mut self, x: u16, y: u16) {
+        let (top, bottom) = if self.modes.origin { self.scroll_region } else { (1, self.height) };
+        self.cursor = (x.clamp(1, self.width), y.clamp(top, bottom));
+        self.wrap_pending = false;
+    }
+
+    /// Move the cursor to a row relative to the scroll region in origin mode.
+    fn goto(&mut self, x: u16, y: u16) {
+        if self.modes.origin {
+            let (top, bottom) = self.scroll_region;
+            let y = top.saturating_add(y.max(1) - 1).min(bottom);
+            self.set_pos(x, y);
+        } else {
+            self.set_pos(x, y);
+        }
+    }
+
+    /// Move the cursor down a row, scrolling the region up at its bottom.
+    fn index(&mut self) {
+        if self.cursor.1 == self.scroll_region.1 {
+            self.scroll_up(1);
+        } else if self.cursor.1 < self.height {
+            self.cursor.1 += 1;
+        }
+        self.wrap_pending = false;
+    }
+
+    /// Move the cursor up a row, scrolling the region down at its top.
+    fn reverse_index(&mut self) {
+        if self.cursor.1 == self.scroll_region.0 {
+            self.scroll_down(1);
+        } else if self.cursor.1 > 1 {
+            self.cursor.1 -= 1;
+        }
+        self.wrap_pending = false;
+    }
+
+    fn line_feed(&mut self) {
+        self.index();
+        if self.modes.newline {
+            self.cursor.0 = 1;
+        }
+    }
+
+    fn scroll_up(&mut self, n: u16) {
+        let (top, bottom) = self.scroll_region;
+        for _ in 0..n.min(bottom - top + 1) {
+            let blank = self.blank_row();
+            self.rows.remove(top as usize - 1);
+            self.rows.insert(bottom as usize - 1, blank);
+        }
+        self.last_cell = None;
+    }
+
+    fn scroll_down(&mut self, n: u16) {
+        let (top, bottom) = self.scroll_region;
+        for _ in 0..n.min(bottom - top + 1) {
+            let blank = self.blank_row();
+            self.rows.remove(bottom as usize - 1);
+            self.rows.insert(top as usize - 1, blank);
+        }
+        self.last_cell = None;
+    }
+
+    /// Insert (or delete, if `insert` is `false`) `n` lines at the cursor, within the scroll
+    /// region.
+    fn shift_lines(&mut self, n: u16, insert: bool) {
+        let (top, bottom) = self.scroll_region;
+        let y = self.cursor.1;
+        if y < top || y > bottom {
+            return;
+        }
+        for _ in 0..n.min(bottom - y + 1) {
+            let blank = self.blank_row();
+            if insert {
+                self.rows.remove(bottom as usize - 1);
+                self.rows.insert(y as usize - 1, blank);
+            } else {
+                self.rows.remove(y as usize - 1);
+                self.rows.insert(bottom as usize - 1, blank);
+            }
+        }
+        self.set_pos(1, y);
+        self.last_cell = None;
+    }
+
+    /// Blank the columns `from` to `to` of row `y`.
+    fn erase(&mut self, y: u16, from: u16, to: u16) {
+        let blank = self.blank();
+        let row = &mut self.rows[y as usize - 1];
+        for cell in &mut row[from as usize - 1..to as usize] {
+            *cell = blank.clone();
+        }
+        repair_wide(row, from as usize - 1, to as usize);
+    }
+
+    fn erase_rows(&mut self, from: u16, to: u16) {
+        for y in from..=to {
+            self.erase(y, 1, self.width);
+        }
+    }
+
+    /// Insert (or delete, if `insert` is `false`) `n` blank cells at the cursor.
+    fn shift_chars(&mut self, n: u16, insert: bool) {
+        let blank = self.blank();
+        let (x, y) = self.cursor;
+        let width = self.width as usize;
+        let n = n.min(self.width - x + 1) as usize;
+        let row = &mut self.rows[y as usize - 1];
+        if insert {
+            row.splice(x as usize - 1..x as usize - 1, vec![blank; n]);
+            row.truncate(width);
+        } else {
+            row.drain(x as usize - 1..x as usize - 1 + n);
+            row.resize(width, blank);
+        }
+        repair_wide(row, x as usize - 1, width);
+        self.wrap_pending = false;
+    }
+
+    fn text(&mut self, text: &[u8]) {
+        let mut decoder = mem::replace(&mut self.decoder, Utf8Decoder::new());
+        decoder.decode(text, |c| self.char(c));
+        self.decoder = decoder;
+    }
+
+    fn char(&mut self, c: char) {
+        match c {
+            '\r' => {
+                self.cursor.0 = 1;
+                self.wrap_pending = false;
+            }
+            '\n' | '\x0B' | '\x0C' => self.line_feed(),
+            '\x08' => {
+                self.cursor.0 = (self.cursor.0 - 1).max(1);
+                self.wrap_pending = false;
+            }
+            '\t' => {
+                self.cursor.0 = self.tabs.next_stop(self.cursor.0);
+                self.wrap_pending = false;
+            }
+            '\x07' => self.bells += 1,
+            '\x0E' => self.shifted = true,
+            '\x0F' => self.shifted = false,
+            c if c.is_control() => (),
+            c => self.print(c),
+        }
+    }
+
+    fn print(&mut self, c: char) {
+        let c = match c {
+            '`'..='~' if self.graphics[self.shifted as usize] => {
+                DEC_GRAPHICS.chars().nth(c as usize - '`' as usize).unwrap_or(c)
+            }
+            c => c,
+        };
+
+        // Only while the cursor is still right after that cell, as it was left by drawing it.
+        if let Some((x, y)) = self.last_cell {
+            let cell = &self.rows[y as usize - 1][x as usize - 1];
+            if self.cursor == ((x + cell.width()).min(self.width), y) &&
+               extends_grapheme(&cell.symbol, c) {
+                self.extend_last_cell(c);
+                return;
+            }
+        }
+
+        let width = char_width(c) as u16;
+        if width == 0 {
+            return;
+        }
+        if width > self.width {
+            return;
+        }
+
+        if self.wrap_pending || self.cursor.0 + width - 1 > self.width {
+            if self.modes.auto_wrap {
+                self.cursor.0 = 1;
+                self.index();
+            } else {
+                self.cursor.0 = self.width - width + 1;
+            }
+        }
+        if self.modes.insert {
+            self.shift_chars(width, true);
+        }
+
+        let (x, y) = self.cursor;
+        let style = self.style;
+        let row = &mut self.rows[y as usize - 1];
+        let i = x as usize - 1;
+        row[i] = Cell::new(c, style);
+        if width == 2 {
+            row[i + 1] = Cell {
+                symbol: String::new(),
+                style,
+            };
+        }
+        repair_wide(row, i, i + width as usize);
+
+        self.last_cell = Some((x, y));
+        self.last_char = Some(c);
+        if x + width > self.width {
+            self.cursor.0 = self.width;
+            self.wrap_pending = self.modes.auto_wrap;
+        } else {
+            self.cursor.0 = x + width;
+            self.wrap_pending = false;
+        }
+    }
+
+    /// Add `c` to the grapheme cluster drawn last, which may widen it from one column to two.
+    fn extend_last_cell(&mut self, c: char) {
+        let (x, y) = match self.last_cell {
+            Some(pos) => pos,
+            None => return,
+        };
+        let row = &mut self.rows[y as usize - 1];
+        let i = x as usize - 1;
+        let old_width = row[i].width();
+        row[i].symbol.push(c);
+
+        // The cursor moves on with the cluster, unless something was drawn since.
+        if row[i].width() > old_width && x < self.width && self.cursor == (x + 1, y) &&
+           !self.wrap_pending {
+            row[i + 1] = Cell {
+                symbol: String::new(),
+                style: row[i].style,
+            };
+            repair_wide(row, i, i + 2);
+            if x + 2 > self.width {
+                self.wrap_pending = self.modes.auto_wrap;
+            } else {
+                self.cursor.0 = x + 2;
+            }
+        }
+    }
+
+    fn save_cursor(&self) -> SavedCursor {
+        SavedCursor {
+            pos: self.cursor,
+            style: self.style,
+            origin: self.modes.origin,
+            graphics: self.graphics,
+            shifted: self.shifted,
+        }
+    }
+
+    fn restore_cursor(&mut self, saved: SavedCursor) {
+        let (x, y) = saved.pos;
+        self.style = saved.style;
+        self.modes.origin = saved.origin;
+        self.graphics = saved.graphics;
+        self.shifted = saved.shifted;
+        self.set_pos(x, y);
+    }
+
+    /// Show the alternate screen, or the primary one.
+    fn set_alternate_screen(&mut self, alternate: bool, save_cursor: bool) {
+        if alternate == self.modes.alternate_screen {
+            return;
+        }
+        if alternate && save_cursor {
+            self.saved_primary = self.save_cursor();
+        }
+        mem::swap(&mut self.rows, &mut self.other_rows);
+        self.modes.alternate_screen = alternate;
+        self.last_cell = None;
+        if alternate && save_cursor {
+            self.erase_rows(1, self.height);
+        }
+        if !alternate && save_cursor {
+            let saved = self.saved_primary;
+            self.restore_cursor(saved);
+        }
+    }
+
+    /// Reset the terminal to its initial state (RIS).
+    fn reset(&mut self) {
+        let (replies, bells) = (mem::take(&mut self.replies), self.bells);
+        *self = Emulator::new(self.width, self.height);
+        self.replies = replies;
+        self.bells = bells;
+    }
+
+    fn sequence(&mut self, seq: &[u8]) {
+        let x = self.cursor.0;
+        match seq {
+            b"\x1B7" => self.saved = self.save_cursor(),
+            b"\x1B8" => {
+                let saved = self.saved;
+                self.restore_cursor(saved);
+            }
+            b"\x1BD" => self.index(),
+            b"\x1BE" => {
+                self.index();
+                self.cursor.0 = 1;
+            }
+            b"\x1BM" => self.reverse_index(),
+            b"\x1BH" => self.tabs.set(x),
+            b"\x1Bc" => self.reset(),
+            b"\x1B=" => self.modes.application_keypad = true,
+            b"\x1B>" => self.modes.application_keypad = false,
+            b"\x1B(0" => self.graphics[0] = true,
+            b"\x1B)0" => self.graphics[1] = true,
+            b"\x1B(B" => self.graphics[0] = false,
+            b"\x1B)B" => self.graphics[1] = false,
+            b"\x1B#8" => {
+                // DECALN fills the screen with E, to align it.
+                let cell = Cell::new('E', CellStyle::default());
+                for row in &mut self.rows {
+                    *row = vec![cell.clone(); self.width as usize];
+                }
+                self.scroll_region = (1, self.height);
+                self.set_pos(1, 1);
+            }
+            _ if seq.starts_with(b"\x1B]") => self.osc(seq),
+            _ if seq.starts_with(b"\x1BP") => self.dcs(seq),
+            _ if seq.starts_with(b"\x1B[") => {
+                if let Some(csi) = Csi::parse(seq) {
+                    self.csi(&csi, seq);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn osc(&mut self, seq: &[u8]) {
+        let body = string_body(seq);
+        let mut parts = body.splitn(2, |&c| c == b';');
+        let (command, text) = (parts.next(), parts.next());
+        if let (Some(b"0"), Some(title)) | (Some(b"2"), Some(title)) = (command, text) {
+            self.title = String::from_utf8_lossy(title).into_owned();
+        }
+    }
+
+    fn dcs(&mut self, seq: &[u8]) {
+        // Only DECRQSS is answered, for the settings the emulator has.
+        let selector = match string_body(seq).strip_prefix(b"$q") {
+            Some(selector) => selector,
+            None => return,
+        };
+        let value = match selector {
+            b"m" => {
+                let mut sgr = Vec::new();
+                self.style.write_sgr(None, &mut sgr);
+                Some(String::from_utf8_lossy(&sgr[2..sgr.len() - 1]).into_owned())
+            }
+            b"r" => Some(format!("{};{}", self.scroll_region.0, self.scroll_region.1)),
+            _ => None,
+        };
+        match value {
+            Some(value) => {
+                let _ = write!(self.replies, "\x1BP1$r{}", value);
+                self.replies.extend_from_slice(selector);
+                self.replies.extend_from_slice(b"\x1B\\");
+            }
+            None => self.replies.extend_from_slice(b"\x1BP0$r\x1B\\"),
+        }
+    }
+
+    fn csi(&mut self, csi: &Csi, seq: &[u8]) {
+        let (x, y) = self.cursor;
+        let (top, bottom) = self.scroll_region;
+        let param = |i: usize| csi.params.get(i).cloned().unwrap_or(0);
+        let n = param(0).max(1);
+
+        match (csi.private, &csi.intermediates[..], csi.final_byte) {
+            (None, b"", b'm') => self.style.apply_sgr(&seq[2..seq.len() - 1]),
+            (None, b"", b'A') => {
+                let min = if y >= top { top } else { 1 };
+                self.set_pos(x, y.saturating_sub(n).max(min));
+            }
+            (None, b"", b'B') | (None, b"", b'e') => {
+                let max = if y <= bottom { bottom } else { self.height };
+                self.set_pos(x, y.saturating_add(n).min(max));
+            }
+            (None, b"", b'C') | (None, b"", b'a') => self.set_pos(x.saturating_add(n), y),
+            (None, b"", b'D') => self.set_pos(x.saturating_sub(n), y),
+            (None, b"", b'E') => self.set_pos(1, y.saturating_add(n)),
+            (None, b"", b'F') => self.set_pos(1, y.saturating_sub(n)),
+            (None, b"", b'G') | (None, b"", b'`') => self.set_pos(n, y),
+            (None, b"", b'd') => self.goto(x, n),
+            (None, b"", b'H') | (None, b"", b'f') => self.goto(param(1).max(1), n),
+            (None, b"", b'I') => {
+                for _ in 0..n {
+                    self.cursor.0 = self.tabs.next_stop(self.cursor.0);
+                }
+                self.wrap_pending = false;
+            }
+            (None, b"", b'Z') => {
+                for _ in 0..n {
+                    let x = self.cursor.0;
+                    self.cursor.0 = self.tabs.stops().iter().rev().find(|&&s| s < x).cloned()
+                        .unwrap_or(1);
+                }
+                self.wrap_pending = false;
+            }
+            (None, b"", b'J') => {
+                match param(0) {
+                    0 => {
+                        self.erase(y, x, self.width);
+                        if y < self.height {
+                            self.erase_rows(y + 1, self.height);
+                        }
+                    }
+                    1 => {
+                        if y > 1 {
+                            self.erase_rows(1, y - 1);
+                        }
+                        self.erase(y, 1, x);
+                    }
+                    2 => self.erase_rows(1, self.height),
+                    _ => (),
+                }
+            }
+            (None, b"", b'K') => {
+                match param(0) {
+                    0 => self.erase(y, x, self.width),
+                    1 => self.erase(y, 1, x),
+                    2 => self.erase(y, 1, self.width),
+                    _ => (),
+                }
+            }
+            (None, b"", b'X') => {
+                let to = x.saturating_add(n - 1).min(self.width);
+                self.erase(y, x, to);
+            }
+            (None, b"", b'@') => self.shift_chars(n, true),
+            (None, b"", b'P') => self.shift_chars(n, false),
+            (None, b"", b'L') => self.shift_lines(n, true),
+            (None, b"", b'M') => self.shift_lines(n, false),
+            (None, b"", b'S') => self.scroll_up(n),
+            (None, b"", b'T') => self.scroll_down(n),
+            (None, b"", b'b') => {
+                if let Some(c) = self.last_char {
+                    for _ in 0..n {
+                        self.print(c);
+                    }
+                }
+            }
+            (None, b"", b'r') => {
+                let top = param(0).max(1);
+                let bottom = if param(1) == 0 { self.height } else { param(1) };
+                if top < bottom && bottom <= self.height {
+                    self.scroll_region = (top, bottom);
+                    self.goto(1, 1);
+                }
+            }
+            (None, b"", b'g') => {
+                match param(0) {
+                    0 => self.tabs.clear(x),
+                    3 => self.tabs.clear_all(),
+                    _ => (),
+                }
+            }
+            (None, b"", b's') => self.saved = self.save_cursor(),
+            (None, b"", b'u') => {
+                let saved = self.saved;
+                self.restore_cursor(saved);
+            }
+            (None, b"", b'n') => {
+                match param(0) {
+                    5 => self.replies.extend_from_slice(b"\x1B[0n"),
+                    6 => {
+                        let y = if self.modes.origin { y.saturating_sub(top) + 1 } else { y };
+                        let _ = write!(self.replies, "\x1B[{};{}R", y, x);
+                    }
+                    _ => (),
+                }
+            }
+            (None, b"", b'c') if param(0) == 0 => self.replies.extend_from_slice(b"\x1B[?62;22c"),
+            (None, b"", b'h') | (None, b"", b'l') => {
+                let set = csi.final_byte == b'h';
+                for &mode in &csi.params {
+                    match mode {
+                        4 => self.modes.insert = set,
+                        20 => self.modes.newline = set,
+                        _ => (),
+                    }
+                }
+            }
+            (Some(b'?'), b"", b'h') | (Some(b'?'), b"", b'l') => {
+                let set = csi.final_byte == b'h';
+                for &mode in &csi.params {
+                    self.set_private_mode(mode, set);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn set_private_mode(&mut self, mode: u16, set: bool) {
+        match mode {
+            1 => self.modes.application_cursor = set,
+            5 => self.modes.reverse_video = set,
+            6 => {
+                self.modes.origin = set;
+                self.goto(1, 1);
+            }
+            7 => self.modes.auto_wrap = set,
+            25 => self.modes.cursor_visible = set,
+            47 => self.set_alternate_screen(set, false),
+            1047 => {
+                if !set && self.modes.alternate_screen {
+                    self.erase_rows(1, self.height);
+                }
+                self.set_alternate_screen(set, false);
+            }
+            1049 => self.set_alternate_screen(set, true),
+            1000 => self.modes.mouse_press = set,
+            1002 => self.modes.mouse_drag = set,
+            1003 => self.modes.mouse_motion = set,
+            1004 => self.modes.focus_events = set,
+            1006 => self.modes.mouse_sgr = set,
+            1007 => self.modes.alternate_scroll = set,
+            1015 => self.modes.mouse_urxvt = set,
+            2004 => self.modes.bracketed_paste = set,
+            _ => (),
+        }
+    }
+}
+
+/// Render `bytes` on a terminal of `width` columns and `height` rows, returning a plain-text
+/// screenshot of the screen, as given by `Emulator::screenshot`.
+pub fn render_to_string(bytes: &[u8], width: u16, height: u16) -> String {
+    let mut term = Emulator::new(width, height);
+    let _ = term.write_all(bytes);
+    term.screenshot()
+}
+
+/// Render `bytes` on a terminal of `width` columns and `height` rows, returning a screenshot
+/// marking up the styled text, as given by `Emulator::styled_screenshot`.
+pub fn render_to_styled_string(bytes: &[u8], width: u16, height: u16) -> String {
+    let mut term = Emulator::new(width, height);
+    let _ = term.write_all(bytes);
+    term.styled_screenshot()
+}
+
+/// The names of the attributes in the markup of `Emulator::styled_screenshot`, in the order of
+/// `CellStyle`.
+const ATTRIBUTE_NAMES: [&str; 7] =
+    ["bold", "faint", "italic", "underline", "blink", "invert", "crossed_out"];
+
+/// The opening tag of `style` in the markup of `Emulator::styled_screenshot`.
+fn style_markup(style: &CellStyle) -> String {
+    let mut parts: Vec<String> = style.attributes()
+        .iter()
+        .zip(&ATTRIBUTE_NAMES)
+        .filter(|&(&set, _)| set)
+        .map(|(_, name)| name.to_string())
+        .collect();
+    for &(name, color) in &[("fg", style.fg), ("bg", style.bg)] {
+        match color {
+            CellColor::Reset => (),
+            CellColor::Ansi(n) => parts.push(format!("{}={}", name, n)),
+            CellColor::Rgb(Rgb(r, g, b)) => {
+                parts.push(format!("{}=#{:02x}{:02x}{:02x}", name, r, g, b))
+            }
+        }
+    }
+    format!("[{}]", parts.join(" "))
+}
+
+/// The body of the string sequence `seq`, without its introducer and terminator.
+fn string_body(seq: &[u8]) -> &[u8] {
+    let body = &seq[2..];
+    body.strip_suffix(b"\x07")
+        .or_else(|| body.strip_suffix(b"\x1B\\"))
+        .unwrap_or(body)
+}
+
+fn blank_row(width: u16) -> Vec<Cell> {
+    vec![Cell::default(); width as usize]
+}
+
+fn blank_rows(width: u16, height: u16) -> Vec<Vec<Cell>> {
+    vec![blank_row(width); height as usize]
+}
+
+/// Blank the halves of wide characters left alone by a change of the cells `from..to` of `row`.
+fn repair_wide(row: &mut [Cell], from: usize, to: usize) {
+    let end = (to + 1).min(row.len());
+    for i in from.saturating_sub(1)..end {
+        let orphan = if row[i].symbol.is_empty() {
+            i == 0 || row[i - 1].width() != 2
+        } else {
+            row[i].width() == 2 && row.get(i + 1).is_none_or(|next| !next.symbol.is_empty())
+        };
+        if orphan {
+            row[i].symbol = " ".to_string();
+        }
+    }
+}
+
+impl fmt::Debug for Emulator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Emulator")
+            .field("size", &(self.width, self.height))
+            .field("cursor", &self.cursor)
+            .field("contents", &self.contents())
+            .finish()
+    }
+}
+
+impl Write for Emulator {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        // The splitter is moved out for the tokens to update the rest of the emulator.
+        let mut splitter = mem::replace(&mut self.splitter, Splitter::new());
+        let result = splitter.split(buf, |token| {
+            match token {
+                Token::Text(text) => self.text(text),
+                Token::Sequence(seq) => self.sequence(seq),
+            }
+            Ok(())
+        });
+        self.splitter = splitter;
+        result?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use bell;
+    use buffer::Buffer;
+    use clear;
+    use edit;
+    use cursor;
+    use screen;
+    use scroll;
+    use style;
+    use terminal::{RequestSetting, Setting};
+
+    fn emulated(width: u16, height: u16, written: &[u8]) -> Emulator {
+        let mut term = Emulator::new(width, height);
+        term.write_all(written).unwrap();
+        term
+    }
+
+    #[test]
+    fn test_text_and_wrap() {
+        let term = emulated(5, 3, b"abcdefg\r\nh\ti");
+        assert_eq!(term.contents(), "abcde\nfg\nh   i");
+        assert_eq!(term.cursor_pos(), (5, 3));
+
+        // Writing past the bottom scrolls up.
+        let term = emulated(5, 2, b"one\r\ntwo\r\nsix");
+        assert_eq!(term.contents(), "two\nsix");
+
+        let term = emulated(5, 3, b"\x1B[?7labcdefg");
+        assert_eq!(term.contents(), "abcdg");
+    }
+
+    #[test]
+    fn test_wide_and_combining() {
+        let term = emulated(6, 2, "日本e\u{301}".as_bytes());
+        assert_eq!(term.line(1), "日本e\u{301}");
+        assert_eq!(term.cell(2, 1).unwrap().symbol, "");
+        assert_eq!(term.cursor_pos(), (6, 1));
+
+        // Overwriting half of a wide character blanks the other half.
+        let mut term = emulated(6, 2, "日本".as_bytes());
+        write!(term, "{}x", cursor::Goto(2, 1)).unwrap();
+        assert_eq!(term.line(1), " x本");
+
+        // A wide character doesn't fit in the last column.
+        let term = emulated(5, 2, "abcd日".as_bytes());
+        assert_eq!(term.contents(), "abcd\n日");
+    }
+
+    #[test]
+    fn test_graphemes() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let written = format!("{}\u{1F1EB}\u{1F1F7}\u{2764}\u{FE0F}x", family);
+        let term = emulated(8, 2, written.as_bytes());
+        assert_eq!(term.cell(1, 1).unwrap().symbol, family);
+        assert_eq!(term.cell(3, 1).unwrap().symbol, "\u{1F1EB}\u{1F1F7}");
+        assert_eq!(term.cell(5, 1).unwrap().symbol, "\u{2764}\u{FE0F}");
+        assert_eq!(term.cell(6, 1).unwrap().symbol, "");
+        assert_eq!(term.cursor_pos(), (8, 1));
+
+        // A cluster isn't extended once the cursor moved away from it.
+        let written = "a\u{200D}\x1B[3;5Hb";
+        assert_eq!(render_to_string(written.as_bytes(), 10, 3), "a\u{200D}\n\n    b");
+        assert_eq!(render_to_string("a\x1B[3;5H\u{301}".as_bytes(), 10, 3), "a\n\n");
+    }
+
+    #[test]
+    fn test_erase_and_edit() {
+        let mut term = emulated(10, 3, b"0123456789\r\nabcdefghij\r\nABCDEFGHIJ");
+        write!(term, "{}{}", cursor::Goto(5, 2), clear::UntilNewline).unwrap();
+        assert_eq!(term.line(2), "abcd");
+        write!(term, "{}{}", cursor::Goto(1, 1), edit::DeleteChars(2)).unwrap();
+        assert_eq!(term.line(1), "23456789");
+        write!(term, "{}", edit::InsertChars(3)).unwrap();
+        assert_eq!(term.line(1), "   2345678");
+        write!(term, "{}ab{}c", edit::EnableInsertMode, edit::DisableInsertMode).unwrap();
+        assert_eq!(term.line(1), "abc  23456");
+        write!(term, "{}\x1B[1J", cursor::Goto(3, 3)).unwrap();
+        assert_eq!(term.contents(), "\n\n   DEFGHIJ");
+        write!(term, "{}", clear::All).unwrap();
+        assert_eq!(term.contents(), "");
+    }
+
+    #[test]
+    fn test_scroll_region() {
+        let mut term = emulated(5, 5, b"1\r\n2\r\n3\r\n4\r\n5");
+        write!(term, "{}", scroll::Region(2, 4)).unwrap();
+        assert_eq!(term.scroll_region(), (2, 4));
+        assert_eq!(term.cursor_pos(), (1, 1));
+
+        writeln!(term, "{}", cursor::Goto(1, 4)).unwrap();
+        assert_eq!(term.contents(), "1\n3\n4\n\n5");
+        write!(term, "{}", scroll::Down(1)).unwrap();
+        assert_eq!(term.contents(), "1\n\n3\n4\n5");
+        write!(term, "{}\x1B[L", cursor::Goto(1, 3)).unwrap();
+        assert_eq!(term.contents(), "1\n\n\n3\n5");
+        write!(term, "\x1B[M").unwrap();
+        assert_eq!(term.contents(), "1\n\n3\n\n5");
+
+        // Origin mode makes rows relative to the region.
+        write!(term, "{}{}\x1B[6n", cursor::EnableOriginMode, cursor::Goto(3, 2)).unwrap();
+        assert_eq!(term.cursor_pos(), (3, 3));
+        assert_eq!(term.take_replies(), b"\x1B[2;3R".to_vec());
+        // It can't leave the region, even when restored after the region changed.
+        write!(term, "\x1B[10E").unwrap();
+        assert_eq!(term.cursor_pos(), (1, 4));
+        write!(term, "\x1B7{}\x1B8", scroll::Region(2, 3)).unwrap();
+        assert_eq!(term.cursor_pos(), (1, 3));
+        write!(term, "{}", scroll::Region(2, 4)).unwrap();
+
+        // Outside of origin mode, the same row is addressed through the region.
+        let region = scroll::Region(2, 4);
+        write!(term, "{}{}", cursor::DisableOriginMode, cursor::Goto::in_region(region, 3, 2))
+            .unwrap();
+        assert_eq!(term.cursor_pos(), (3, 3));
+
+        write!(term, "{}{}", scroll::ResetRegion, scroll::Up(2)).unwrap();
+        assert_eq!(term.scroll_region(), (1, 5));
+        assert_eq!(term.contents(), "3\n\n5");
+    }
+
+    #[test]
+    fn test_report_above_origin() {
+        let mut term = emulated(5, 5, b"\x1B[2;4r\x1B[?6h\x1B[5F\x1B[6n");
+        assert_eq!(term.cursor_pos(), (1, 2));
+        assert_eq!(term.take_replies(), b"\x1B[1;1R".to_vec());
+    }
+
+    #[test]
+    fn test_sgr() {
+        let mut term = emulated(10, 1, b"\x1B[1;4;31;48;5;200ma\x1B[22;38:2::1:2:3mb\x1B[0mc");
+        let style = term.cell(1, 1).unwrap().style;
+        assert!(style.bold && style.underline);
+        assert_eq!(style.fg, CellColor::Ansi(1));
+        assert_eq!(style.bg, CellColor::Ansi(200));
+        let style = term.cell(2, 1).unwrap().style;
+        assert!(!style.bold && style.underline);
+        assert_eq!(style.fg, CellColor::Rgb(Rgb(1, 2, 3)));
+        assert_eq!(term.cell(3, 1).unwrap().style, CellStyle::default());
+
+        write!(term, "\x1B[38;2;10;20;30;1m").unwrap();
+        assert_eq!(term.style().fg, CellColor::Rgb(Rgb(10, 20, 30)));
+        assert!(term.style().bold);
+
+        // The rendition can be requested with DECRQSS.
+        write!(term, "{}", RequestSetting(Setting::GraphicRendition)).unwrap();
+        assert_eq!(term.take_replies(), b"\x1BP1$r0;1;38;2;10;20;30m\x1B\\".to_vec());
+        write!(term, "{}", RequestSetting(Setting::CursorStyle)).unwrap();
+        assert_eq!(term.take_replies(), b"\x1BP0$r\x1B\\".to_vec());
+    }
+
+    #[test]
+    fn test_modes_and_screens() {
+        let mut term = emulated(10, 3, b"main\x1B[?1049h\x1B[?25l\x1B[?1000h\x1B[?2004halt");
+        assert!(term.modes().alternate_screen);
+        assert!(!term.modes().cursor_visible);
+        assert!(term.modes().mouse_press && term.modes().bracketed_paste);
+        assert_eq!(term.contents(), "    alt");
+
+        write!(term, "{}", screen::EnableAlternateScroll).unwrap();
+        assert!(term.modes().alternate_scroll);
+
+        write!(term, "{}", screen::ToMainScreen).unwrap();
+        assert_eq!(term.contents(), "main");
+        assert_eq!(term.cursor_pos(), (5, 1));
+
+        write!(term, "\x1B]2;my title\x07{}{}", bell::Bell, screen::ReverseScreen).unwrap();
+        assert_eq!(term.title(), "my title");
+        assert_eq!(term.bells(), 1);
+        assert!(term.modes().reverse_video);
+
+        write!(term, "\x1Bc").unwrap();
+        assert_eq!(term.title(), "");
+        assert_eq!(*term.modes(), Modes::default());
+        assert_eq!(term.contents(), "");
+    }
+
+    #[test]
+    fn test_screenshots() {
+        let written = b"\x1B[2;3Hok \x1B[1;31mfail\x1B[m [x]\x1B[3;1H\x1B[48;2;255;128;0m  ";
+        assert_eq!(render_to_string(written, 14, 4), "\n  ok fail [x]\n\n");
+        assert_eq!(render_to_styled_string(written, 14, 4),
+                   "\n  ok [bold fg=1]fail[/] [[x]\n[bg=#ff8000]  [/]\n");
+    }
+
+    #[test]
+    fn test_dec_graphics() {
+        let term = emulated(5, 1, b"\x1B(0lqk\x1B(Bq");
+        assert_eq!(term.line(1), "┌─┐q");
+    }
+
+    #[test]
+    fn test_buffer_round_trip() {
+        let mut buffer = Buffer::new(8, 2);
+        let bold = CellStyle {
+            bold: true,
+            ..CellStyle::default()
+        };
+        buffer.set_str(2, 1, "bold", bold);
+        buffer.set_str(1, 2, "日本", CellStyle::default());
+
+        let mut term = Emulator::new(8, 2);
+        buffer.flush(&mut term).unwrap();
+        buffer.set_str(3, 2, "x", CellStyle::default());
+        buffer.flush(&mut term).unwrap();
+
+        assert_eq!(term.contents(), " bold\n日x");
+        assert_eq!(term.cell(2, 1).unwrap().style, bold);
+        assert_eq!(term.style(), CellStyle::default());
+
+        let mut guarded = style::StyleGuard::new(&mut term);
+        write!(guarded, "\x1B[1m").unwrap();
+        drop(guarded);
+        assert_eq!(term.style(), CellStyle::default());
+    }
+}
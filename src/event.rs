@@ -1,5 +1,7 @@
 //! Mouse and key events.
 
+use std::error;
+use std::fmt;
 use std::io::{Error, ErrorKind};
 use std::ascii::AsciiExt;
 use std::str;
@@ -11,6 +13,16 @@ pub enum Event {
     Key(Key),
     /// A mouse button press, release or wheel use at specific coordinates.
     Mouse(MouseEvent),
+    /// Text pasted while bracketed paste is enabled (see `BracketedPasteTerminal`).
+    ///
+    /// Invalid UTF-8 is replaced with `U+FFFD`. `Events` sanitizes it by default, see
+    /// `PasteSanitization`.
+    Paste(String),
+    /// The terminal gained the focus, while its focus reports are enabled (see
+    /// `Mode::FocusEvents`).
+    FocusGained,
+    /// The terminal lost the focus, while its focus reports are enabled.
+    FocusLost,
     /// An event that cannot currently be evaluated.
     Unsupported(Vec<u8>),
 }
@@ -78,7 +90,8 @@ pub enum Key {
     Insert,
     /// Function keys.
     ///
-    /// Only function keys 1 through 12 are supported.
+    /// Only function keys 1 through 24 are supported, F13 to F24 being typically entered as
+    /// Shift+F1 to Shift+F12.
     F(u8),
     /// Normal character.
     Char(char),
@@ -92,11 +105,130 @@ pub enum Key {
     Null,
     /// Esc key.
     Esc,
+    /// A key of the numeric keypad (digits and `*`, `+`, `,`, `-`, `.`, `/`, `=`).
+    ///
+    /// These are only distinguished in application keypad mode (see `KeypadTerminal`).
+    Keypad(char),
+    /// Enter key of the numeric keypad, in application keypad mode.
+    KeypadEnter,
+    /// A character typed with a combination of modifiers that has no key of its own, e.g.
+    /// Ctrl+Shift+A or Ctrl+`,`.
+    ///
+    /// These are only reported with xterm's `modifyOtherKeys` (see `ModifyOtherKeysTerminal`).
+    /// Enter is reported as `'\n'`.
+    Modified(Modifiers, char),
+    /// An arrow, Home or End pressed with modifiers, e.g. Shift+Left or Ctrl+Shift+End.
+    ModifiedCursor(Modifiers, CursorKey),
 
     #[doc(hidden)]
     __IsNotComplete,
 }
 
+/// A key moving the cursor, which can be reported with modifiers (see `Key::ModifiedCursor`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum CursorKey {
+    /// Left arrow.
+    Left,
+    /// Right arrow.
+    Right,
+    /// Up arrow.
+    Up,
+    /// Down arrow.
+    Down,
+    /// Home key.
+    Home,
+    /// End key.
+    End,
+}
+
+impl From<CursorKey> for Key {
+    fn from(key: CursorKey) -> Key {
+        match key {
+            CursorKey::Left => Key::Left,
+            CursorKey::Right => Key::Right,
+            CursorKey::Up => Key::Up,
+            CursorKey::Down => Key::Down,
+            CursorKey::Home => Key::Home,
+            CursorKey::End => Key::End,
+        }
+    }
+}
+
+/// The modifier keys held while pressing a key.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers {
+    /// Shift.
+    pub shift: bool,
+    /// Alt (or Option).
+    pub alt: bool,
+    /// Ctrl.
+    pub ctrl: bool,
+    /// Meta (or Super, or Command).
+    pub meta: bool,
+}
+
+impl Modifiers {
+    /// Decode the modifiers parameter of xterm's key sequences: 1 plus the sum of 1 for Shift, 2
+    /// for Alt, 4 for Ctrl and 8 for Meta.
+    pub fn from_xterm(param: u32) -> Modifiers {
+        let bits = param.saturating_sub(1);
+        Modifiers {
+            shift: bits & 1 != 0,
+            alt: bits & 2 != 0,
+            ctrl: bits & 4 != 0,
+            meta: bits & 8 != 0,
+        }
+    }
+}
+
+/// An error found in the input: a sequence which is malformed or unknown, or invalid UTF-8.
+///
+/// It is the inner error of the `InvalidData` errors which `Events` yields in strict mode, see
+/// `Events::strict`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ParseError {
+    /// The offset of the first byte of the sequence in the input, counted from 0.
+    pub offset: u64,
+    /// The bytes of the sequence, as far as they were read.
+    pub bytes: Vec<u8>,
+    /// What was expected instead, e.g. "an SGR mouse report".
+    pub expected: &'static str,
+}
+
+impl ParseError {
+    /// The error for `bytes`, read at `offset`, which are invalid UTF-8 if `utf8` is set and an
+    /// unparsable sequence otherwise.
+    pub(crate) fn new(offset: u64, bytes: Vec<u8>, utf8: bool) -> ParseError {
+        let expected = if utf8 {
+            "valid UTF-8"
+        } else {
+            match bytes[..] {
+                [b'\x1B', b'O', ..] => "F1 to F4 or a keypad key after `ESC O`",
+                [b'\x1B', b'[', b'[', ..] => "F1 to F5 as `ESC [ [ A` to `E`",
+                [b'\x1B', b'[', b'<', ..] => "an SGR mouse report, `ESC [ < button ; x ; y M`",
+                [b'\x1B', b'[', b'M', ..] => "an X10 mouse report, `ESC [ M` and 3 bytes",
+                [b'\x1B', b'[', .., b'M'] => "an urxvt mouse report, `ESC [ button ; x ; y M`",
+                [b'\x1B', b'[', .., b'~'] => "a known key code, `ESC [ code ~`",
+                [b'\x1B', b'[', ..] => "a known CSI sequence",
+                _ => "a complete escape sequence",
+            }
+        };
+        ParseError { offset, bytes, expected }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f,
+               "unexpected input \"{}\" at byte {}, expected {}",
+               self.bytes.escape_ascii(),
+               self.offset,
+               self.expected)
+    }
+}
+
+impl error::Error for ParseError {}
+
 /// Parse an Event from `item` and possibly subsequent bytes through `iter`.
 pub fn parse_event<I>(item: u8, iter: &mut I) -> Result<Event, Error>
     where I: Iterator<Item = Result<u8, Error>>
@@ -110,6 +242,16 @@ pub fn parse_event<I>(item: u8, iter: &mut I) -> Result<Event, Error>
                 match iter.next() {
                     // F1-F4
                     Some(Ok(val @ b'P'...b'S')) => Event::Key(Key::F(1 + val - b'P')),
+                    // Shift+F1 to Shift+F4, as sent by some terminals.
+                    Some(Ok(b'2')) => {
+                        match iter.next() {
+                            Some(Ok(val @ b'P'..=b'S')) => Event::Key(Key::F(13 + val - b'P')),
+                            _ => return Err(error),
+                        }
+                    }
+                    // The keypad, in application mode.
+                    Some(Ok(b'M')) => Event::Key(Key::KeypadEnter),
+                    Some(Ok(val)) => Event::Key(Key::Keypad(keypad_char(val).ok_or(error)?)),
                     _ => return Err(error),
                 }
             }
@@ -156,14 +298,16 @@ fn parse_csi<I>(iter: &mut I) -> Option<Event>
              Some(Ok(b'B')) => Event::Key(Key::Down),
              Some(Ok(b'H')) => Event::Key(Key::Home),
              Some(Ok(b'F')) => Event::Key(Key::End),
+             Some(Ok(b'I')) => Event::FocusGained,
+             Some(Ok(b'O')) => Event::FocusLost,
              Some(Ok(b'M')) => {
         // X10 emulation mouse encoding: ESC [ CB Cx Cy (6 characters only).
-        let mut next = || iter.next().unwrap().unwrap();
+        let mut next = || iter.next().and_then(Result::ok);
 
-        let cb = next() as i8 - 32;
+        let cb = (next()? as i8).wrapping_sub(32);
         // (1, 1) are the coords for upper left.
-        let cx = next().saturating_sub(32) as u16;
-        let cy = next().saturating_sub(32) as u16;
+        let cx = next()?.saturating_sub(32) as u16;
+        let cy = next()?.saturating_sub(32) as u16;
         Event::Mouse(match cb & 0b11 {
                          0 => {
                              if cb & 0x40 != 0 {
@@ -188,29 +332,21 @@ fn parse_csi<I>(iter: &mut I) -> Option<Event>
         // xterm mouse encoding:
         // ESC [ < Cb ; Cx ; Cy (;) (M or m)
         let mut buf = Vec::new();
-        let mut c = iter.next().unwrap().unwrap();
+        let mut c = iter.next()?.ok()?;
         while match c {
                   b'm' | b'M' => false,
-                  _ => true,
+                  b'0'..=b'9' | b';' => true,
+                  _ => return None,
               } {
             buf.push(c);
-            c = iter.next().unwrap().unwrap();
+            c = iter.next()?.ok()?;
         }
-        let str_buf = String::from_utf8(buf).unwrap();
+        let str_buf = String::from_utf8(buf).ok()?;
         let nums = &mut str_buf.split(';');
 
-        let cb = nums.next()
-            .unwrap()
-            .parse::<u16>()
-            .unwrap();
-        let cx = nums.next()
-            .unwrap()
-            .parse::<u16>()
-            .unwrap();
-        let cy = nums.next()
-            .unwrap()
-            .parse::<u16>()
-            .unwrap();
+        let cb = nums.next()?.parse::<u16>().ok()?;
+        let cx = nums.next()?.parse::<u16>().ok()?;
+        let cy = nums.next()?.parse::<u16>().ok()?;
 
         let event = match cb {
             0...2 | 64...65 => {
@@ -239,25 +375,42 @@ fn parse_csi<I>(iter: &mut I) -> Option<Event>
         // Numbered escape code.
         let mut buf = Vec::new();
         buf.push(c);
-        let mut c = iter.next().unwrap().unwrap();
+        let mut c = iter.next()?.ok()?;
         // The final byte of a CSI sequence can be in the range 64-126, so
         // let's keep reading anything else.
         while c < 64 || c > 126 {
             buf.push(c);
-            c = iter.next().unwrap().unwrap();
+            c = iter.next()?.ok()?;
         }
 
         match c {
+            // Shift+F1 to Shift+F4: ESC [ 1 ; 2 P (to S).
+            val @ b'P'..=b'S' if buf == b"1;2" => Event::Key(Key::F(13 + val - b'P')),
+            // Arrows, Home and End with modifiers: ESC [ 1 ; modifiers A (to D, H or F).
+            val @ b'A'..=b'D' | val @ b'H' | val @ b'F' if buf.starts_with(b"1;") => {
+                let key = match val {
+                    b'A' => CursorKey::Up,
+                    b'B' => CursorKey::Down,
+                    b'C' => CursorKey::Right,
+                    b'D' => CursorKey::Left,
+                    b'H' => CursorKey::Home,
+                    _ => CursorKey::End,
+                };
+                let modifiers = String::from_utf8(buf[2..].to_vec()).ok()?.parse().ok()?;
+                Event::Key(modified_cursor(modifiers, key))
+            }
             // rxvt mouse encoding:
             // ESC [ Cb ; Cx ; Cy ; M
             b'M' => {
-                let str_buf = String::from_utf8(buf).unwrap();
-
-                let nums: Vec<u16> = str_buf.split(';').map(|n| n.parse().unwrap()).collect();
+                let str_buf = String::from_utf8(buf).ok()?;
 
-                let cb = nums[0];
-                let cx = nums[1];
-                let cy = nums[2];
+                let nums: Vec<u16> = str_buf.split(';')
+                    .map(|n| n.parse().ok())
+                    .collect::<Option<_>>()?;
+                let (cb, cx, cy) = match nums[..] {
+                    [cb, cx, cy] => (cb, cx, cy),
+                    _ => return None,
+                };
 
                 let event = match cb {
                     32 => MouseEvent::Press(MouseButton::Left, cx, cy),
@@ -273,20 +426,43 @@ fn parse_csi<I>(iter: &mut I) -> Option<Event>
             }
             // Special key code.
             b'~' => {
-                let str_buf = String::from_utf8(buf).unwrap();
+                let str_buf = String::from_utf8(buf).ok()?;
 
                 // This CSI sequence can be a list of semicolon-separated
                 // numbers.
-                let nums: Vec<u8> = str_buf.split(';').map(|n| n.parse().unwrap()).collect();
+                let nums: Vec<u32> = str_buf.split(';')
+                    .map(|n| n.parse().ok())
+                    .collect::<Option<_>>()?;
 
                 if nums.is_empty() {
                     return None;
                 }
 
+                // Bracketed paste: ESC [ 200 ~ text ESC [ 201 ~
+                if nums[..] == [200] {
+                    return parse_paste(iter).map(Event::Paste);
+                }
+
+                // xterm's modifyOtherKeys: ESC [ 27 ; modifiers ; code ~
+                if let [27, modifiers, code] = nums[..] {
+                    return modified_key(modifiers, code).map(Event::Key);
+                }
+
                 // TODO: handle multiple values for key modififiers (ex: values
                 // [3, 2] means Shift+Delete)
                 if nums.len() > 1 {
-                    return None;
+                    // Shift+F1 to Shift+F12 are xterm's F13 to F24.
+                    return match (nums[0], function_key(nums[0]), &nums[1..]) {
+                        (_, Some(n @ 1..=12), &[2]) => Some(Event::Key(Key::F(n + 12))),
+                        // Home and End with modifiers, as sent by rxvt and the Linux console.
+                        (1, _, &[modifiers]) | (7, _, &[modifiers]) => {
+                            Some(Event::Key(modified_cursor(modifiers, CursorKey::Home)))
+                        }
+                        (4, _, &[modifiers]) | (8, _, &[modifiers]) => {
+                            Some(Event::Key(modified_cursor(modifiers, CursorKey::End)))
+                        }
+                        _ => None,
+                    };
                 }
 
                 match nums[0] {
@@ -296,10 +472,7 @@ fn parse_csi<I>(iter: &mut I) -> Option<Event>
                     4 | 8 => Event::Key(Key::End),
                     5 => Event::Key(Key::PageUp),
                     6 => Event::Key(Key::PageDown),
-                    v @ 11...15 => Event::Key(Key::F(v - 10)),
-                    v @ 17...21 => Event::Key(Key::F(v - 11)),
-                    v @ 23...24 => Event::Key(Key::F(v - 12)),
-                    _ => return None,
+                    v => Event::Key(Key::F(function_key(v)?)),
                 }
             }
             _ => return None,
@@ -310,32 +483,110 @@ fn parse_csi<I>(iter: &mut I) -> Option<Event>
 
 }
 
+/// Read pasted text, up to the end of the bracketed paste.
+fn parse_paste<I>(iter: &mut I) -> Option<String>
+    where I: Iterator<Item = Result<u8, Error>>
+{
+    const END: &[u8] = b"\x1B[201~";
+
+    let mut buf = Vec::new();
+    while !buf.ends_with(END) {
+        buf.push(iter.next()?.ok()?);
+    }
+    buf.truncate(buf.len() - END.len());
+
+    Some(String::from_utf8_lossy(&buf).into_owned())
+}
+
+/// The function key numbered `code` in `ESC [ code ~` (VT220 numbering, F13 to F20 included).
+fn function_key(code: u32) -> Option<u8> {
+    let n = match code {
+        11..=15 => code - 10,
+        17..=21 => code - 11,
+        23..=26 => code - 12,
+        28..=29 => code - 13,
+        31..=34 => code - 14,
+        _ => return None,
+    };
+    Some(n as u8)
+}
+
+/// The key `code` (a Unicode code point) pressed with the xterm `modifiers` parameter.
+///
+/// The combinations that have a key of their own (e.g. Ctrl+letter) are reported as such.
+fn modified_key(modifiers: u32, code: u32) -> Option<Key> {
+    let modifiers = Modifiers::from_xterm(modifiers);
+    let c = match code {
+        13 => '\n',
+        code => ::std::char::from_u32(code)?,
+    };
+
+    Some(match modifiers {
+        Modifiers { alt: false, ctrl: false, meta: false, .. } => Key::Char(c),
+        Modifiers { shift: false, alt: true, ctrl: false, meta: false } => Key::Alt(c),
+        Modifiers { shift: false, alt: false, ctrl: true, meta: false }
+            if c.is_ascii_lowercase() => Key::Ctrl(c),
+        _ => Key::Modified(modifiers, c),
+    })
+}
+
+/// The cursor `key` pressed with the xterm `modifiers` parameter.
+fn modified_cursor(modifiers: u32, key: CursorKey) -> Key {
+    let modifiers = Modifiers::from_xterm(modifiers);
+    if modifiers == Modifiers::default() {
+        key.into()
+    } else {
+        Key::ModifiedCursor(modifiers, key)
+    }
+}
+
+/// The keypad key sent as `ESC O c` in application keypad mode.
+fn keypad_char(c: u8) -> Option<char> {
+    match c {
+        b'p'..=b'y' => Some((c - b'p' + b'0') as char),
+        b'j' => Some('*'),
+        b'k' => Some('+'),
+        b'l' => Some(','),
+        b'm' => Some('-'),
+        b'n' => Some('.'),
+        b'o' => Some('/'),
+        b'X' => Some('='),
+        _ => None,
+    }
+}
+
 /// Parse `c` as either a single byte ASCII char or a variable size UTF-8 char.
+///
+/// Invalid UTF-8 is reported as an `InvalidData` error, as soon as it is known to be invalid: the
+/// last byte read is then the first one which can't be part of the character.
 fn parse_utf8_char<I>(c: u8, iter: &mut I) -> Result<char, Error>
     where I: Iterator<Item = Result<u8, Error>>
 {
-    let error = Err(Error::new(ErrorKind::Other, "Input character is not valid UTF-8"));
+    let error = Err(Error::new(ErrorKind::InvalidData, "Input character is not valid UTF-8"));
     if c.is_ascii() {
-        Ok(c as char)
-    } else {
-        let bytes = &mut Vec::new();
-        bytes.push(c);
-
-        loop {
-            match iter.next() {
-                Some(Ok(next)) => {
-                    bytes.push(next);
-                    if let Ok(st) = str::from_utf8(bytes) {
-                        return Ok(st.chars().next().unwrap());
-                    }
-                    if bytes.len() >= 4 {
-                        return error;
-                    }
-                }
-                _ => return error,
-            }
+        return Ok(c as char);
+    }
+
+    let len = match c {
+        0xC2..=0xDF => 2,
+        0xE0..=0xEF => 3,
+        0xF0..=0xF4 => 4,
+        _ => return error,
+    };
+
+    let mut bytes = [c, 0, 0, 0];
+    for byte in &mut bytes[1..len] {
+        match iter.next() {
+            // Continuation byte.
+            Some(Ok(next)) if next & 0xC0 == 0x80 => *byte = next,
+            _ => return error,
         }
     }
+
+    match str::from_utf8(&bytes[..len]) {
+        Ok(st) => Ok(st.chars().next().unwrap()),
+        Err(_) => error,
+    }
 }
 
 #[cfg(test)]
@@ -349,3 +600,14 @@ fn test_parse_utf8() {
         assert!(c == parse_utf8_char(b, bytes).unwrap());
     }
 }
+
+#[cfg(test)]
+#[test]
+fn test_parse_invalid_utf8() {
+    for &invalid in &[&b"\xFF"[..], b"\xC3a", b"\xE2\x82", b"\xED\xA0\x80"] {
+        let mut bytes = invalid[1..].iter().map(|&x| Ok(x));
+        let error = parse_utf8_char(invalid[0], &mut bytes).unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::InvalidData);
+        assert!(bytes.next().is_none());
+    }
+}
@@ -0,0 +1,484 @@
+//! A single blocking source of input events, resizes and timers.
+//!
+//! An interactive application has to react to more than key presses: the terminal is resized,
+//! the process is continued after a `^Z`, animations and timeouts are due, and other threads
+//! have results to show. `EventLoop` waits for all of them at once, in a single `poll` on the
+//! terminal and on a pipe the signal handlers write to, so a synchronous application doesn't
+//! need a thread per source.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::event::{Event, Key};
+//! use termion::event_loop::{EventLoop, LoopEvent};
+//! use termion::raw::IntoRawMode;
+//! use std::io::stdout;
+//! use std::time::Duration;
+//!
+//! let _stdout = stdout().into_raw_mode().unwrap();
+//! let mut events = EventLoop::new().unwrap();
+//! let clock = events.add_interval(Duration::from_secs(1));
+//!
+//! while let Some(event) = events.next() {
+//!     match event.unwrap() {
+//!         LoopEvent::Input(Event::Key(Key::Char('q'))) => break,
+//!         LoopEvent::Input(_) => (),
+//!         LoopEvent::Resize(width, height) => {
+//!             // Lay the interface out again.
+//!         }
+//!         LoopEvent::Continue => {
+//!             // Re-enter the modes the shell has reset, and redraw.
+//!         }
+//!         LoopEvent::Timer(id) if id == clock => {
+//!             // Update the clock.
+//!         }
+//!         _ => (),
+//!     }
+//! }
+//! ```
+
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::os::unix::io::AsRawFd;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use event::Event;
+use input::{Events, InputBuffer};
+use raw::CONTROL_SEQUENCE_TIMEOUT;
+use sys::poll::{pipe, poll_readable};
+use sys::signal::{self, Signal};
+use sys::size::terminal_size;
+use sys::tty::get_tty;
+
+/// What the wake pipe carries.
+const WAKE: u8 = b'w';
+const RESIZE: u8 = b'r';
+const CONTINUE: u8 = b'c';
+
+/// An event of an `EventLoop`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LoopEvent {
+    /// An input event.
+    Input(Event),
+    /// The terminal was resized to the given width and height.
+    Resize(u16, u16),
+    /// The process was continued after being stopped (`SIGCONT`).
+    ///
+    /// The shell usually resets the terminal modes when the process is stopped, so they have to
+    /// be entered again, and the screen redrawn.
+    Continue,
+    /// A timer is due.
+    Timer(TimerId),
+    /// A `Waker` was woken. Wakes not handled yet are delivered as one.
+    Wake,
+}
+
+/// The identifier of a timer of an `EventLoop`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TimerId(u64);
+
+struct Timer {
+    id: TimerId,
+    deadline: Instant,
+    /// The interval of a repeating timer.
+    interval: Option<Duration>,
+}
+
+/// The write end of the wake pipe, shared with the wakers and the signal callbacks.
+struct WakePipe {
+    write: File,
+    /// Was the event loop dropped?
+    closed: AtomicBool,
+}
+
+impl WakePipe {
+    fn send(&self, byte: u8) -> bool {
+        if self.closed.load(Ordering::SeqCst) {
+            return false;
+        }
+        // The pipe being full is fine: the loop has wake ups to read already.
+        let _ = (&self.write).write(&[byte]);
+        true
+    }
+}
+
+/// A handle waking an `EventLoop` up from another thread, making it deliver `LoopEvent::Wake`.
+#[derive(Clone)]
+pub struct Waker {
+    pipe: Arc<WakePipe>,
+}
+
+impl Waker {
+    /// Wake the event loop up. This does nothing once it is dropped.
+    pub fn wake(&self) {
+        self.pipe.send(WAKE);
+    }
+}
+
+/// A blocking source of input events, terminal resizes, `SIGCONT`s, timers and wake ups.
+///
+/// As an iterator, it waits for the next event, and never ends.
+///
+/// The `SIGWINCH` and `SIGCONT` handlers are installed on creation, chaining the ones installed
+/// before (see `on_resize`).
+pub struct EventLoop {
+    input: File,
+    /// Did the input end?
+    input_ended: bool,
+    events: Events<InputBuffer>,
+    /// When to give up on the rest of the escape sequence the input read ends in the middle of.
+    release_input: Option<Instant>,
+    wake_read: File,
+    wake_pipe: Arc<WakePipe>,
+    timers: Vec<Timer>,
+    next_timer: u64,
+    /// The events read but not delivered yet.
+    pending: VecDeque<LoopEvent>,
+}
+
+impl EventLoop {
+    /// An event loop reading the input events from the TTY device.
+    pub fn new() -> io::Result<EventLoop> {
+        EventLoop::with_input(get_tty()?)
+    }
+
+    /// An event loop reading the input events from `input`.
+    pub fn with_input(input: File) -> io::Result<EventLoop> {
+        let (wake_read, write) = pipe()?;
+        let wake_pipe = Arc::new(WakePipe {
+            write,
+            closed: AtomicBool::new(false),
+        });
+
+        for &(signal, byte) in &[(Signal::Resize, RESIZE), (Signal::Continue, CONTINUE)] {
+            let pipe = wake_pipe.clone();
+            signal::subscribe(signal, Box::new(move || pipe.send(byte)))?;
+        }
+
+        Ok(EventLoop {
+            input,
+            input_ended: false,
+            events: InputBuffer::new().events(),
+            release_input: None,
+            wake_read,
+            wake_pipe,
+            timers: Vec::new(),
+            next_timer: 0,
+            pending: VecDeque::new(),
+        })
+    }
+
+    /// A handle waking this event loop up from another thread.
+    pub fn waker(&self) -> Waker {
+        Waker { pipe: self.wake_pipe.clone() }
+    }
+
+    fn add_timer(&mut self, after: Duration, interval: Option<Duration>) -> TimerId {
+        let id = TimerId(self.next_timer);
+        self.next_timer += 1;
+        self.timers.push(Timer {
+            id,
+            deadline: Instant::now() + after,
+            interval,
+        });
+        id
+    }
+
+    /// Deliver `LoopEvent::Timer` once, after `after`.
+    pub fn add_timeout(&mut self, after: Duration) -> TimerId {
+        self.add_timer(after, None)
+    }
+
+    /// Deliver `LoopEvent::Timer` every `interval`, until the timer is cancelled.
+    ///
+    /// When the loop falls behind, the occurrences missed are skipped rather than delivered in a
+    /// burst.
+    pub fn add_interval(&mut self, interval: Duration) -> TimerId {
+        self.add_timer(interval, Some(interval))
+    }
+
+    /// Cancel the timer `id`, returning whether it was still due.
+    pub fn cancel_timer(&mut self, id: TimerId) -> bool {
+        let len = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        self.timers.len() != len
+    }
+
+    /// Wait for the next event, for up to `timeout` (forever if `None`).
+    ///
+    /// The input is read as it arrives, so an error such as the input ending is returned once,
+    /// after the events read before it. An escape sequence split across reads is waited for
+    /// up to the control sequence timeout, after which what was read of it is parsed as is (a
+    /// lone ESC being the Esc key). The timers due are delivered before the input read.
+    pub fn next_timeout(&mut self, timeout: Option<Duration>) -> io::Result<Option<LoopEvent>> {
+        let give_up = timeout.map(|timeout| Instant::now() + timeout);
+
+        loop {
+            if let Some(event) = self.pending.pop_front() {
+                return Ok(Some(event));
+            }
+            if let Some(id) = self.fire_timer() {
+                return Ok(Some(LoopEvent::Timer(id)));
+            }
+            if let Some(event) = self.events.next() {
+                return event.map(|event| Some(LoopEvent::Input(event)));
+            }
+
+            let now = Instant::now();
+            if self.release_input.is_some_and(|release| release <= now) {
+                self.events.get_mut().release();
+                self.release_input = None;
+                continue;
+            }
+            if give_up.is_some_and(|give_up| give_up <= now) {
+                return Ok(None);
+            }
+            let deadline = self.timers
+                .iter()
+                .map(|timer| timer.deadline)
+                .chain(give_up)
+                .chain(self.release_input)
+                .min();
+            let wait = deadline.map(|deadline| deadline.saturating_duration_since(now));
+
+            let mut fds = vec![self.wake_read.as_raw_fd()];
+            if !self.input_ended {
+                fds.push(self.input.as_raw_fd());
+            }
+            let readable = match poll_readable(&fds, wait) {
+                Ok(readable) => readable,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            };
+
+            if readable[0] {
+                self.read_wake_pipe()?;
+            }
+            if readable.get(1) == Some(&true) {
+                self.read_input()?;
+            }
+        }
+    }
+
+    /// Take the earliest timer due, rescheduling it if it repeats.
+    fn fire_timer(&mut self) -> Option<TimerId> {
+        let now = Instant::now();
+        let i = self.timers
+            .iter()
+            .enumerate()
+            .filter(|&(_, timer)| timer.deadline <= now)
+            .min_by_key(|&(_, timer)| timer.deadline)?
+            .0;
+
+        let id = self.timers[i].id;
+        match self.timers[i].interval {
+            Some(interval) => {
+                let timer = &mut self.timers[i];
+                timer.deadline += interval;
+                if timer.deadline <= now {
+                    timer.deadline = now + interval;
+                }
+            }
+            None => {
+                self.timers.swap_remove(i);
+            }
+        }
+        Some(id)
+    }
+
+    fn read_wake_pipe(&mut self) -> io::Result<()> {
+        let mut buf = [0u8; 64];
+        let (mut resized, mut continued, mut woken) = (false, false, false);
+
+        loop {
+            match self.wake_read.read(&mut buf) {
+                Ok(0) => break,
+                Ok(n) => {
+                    for &byte in &buf[..n] {
+                        match byte {
+                            RESIZE => resized = true,
+                            CONTINUE => continued = true,
+                            _ => woken = true,
+                        }
+                    }
+                }
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => break,
+                Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e),
+            }
+        }
+
+        if continued {
+            self.pending.push_back(LoopEvent::Continue);
+        }
+        if resized {
+            if let Ok((width, height)) = terminal_size() {
+                self.pending.push_back(LoopEvent::Resize(width, height));
+            }
+        }
+        if woken {
+            self.pending.push_back(LoopEvent::Wake);
+        }
+        Ok(())
+    }
+
+    fn read_input(&mut self) -> io::Result<()> {
+        let mut buf = [0u8; 1024];
+        match self.input.read(&mut buf) {
+            Ok(0) => {
+                self.input_ended = true;
+                self.events.get_mut().release();
+                self.release_input = None;
+                Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The input ended."))
+            }
+            Ok(n) => {
+                let input = self.events.get_mut();
+                input.extend_holding(&buf[..n]);
+                self.release_input = if input.is_holding() {
+                    Some(Instant::now() + Duration::from_millis(CONTROL_SEQUENCE_TIMEOUT))
+                } else {
+                    None
+                };
+                Ok(())
+            }
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted ||
+                          e.kind() == io::ErrorKind::WouldBlock => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Iterator for EventLoop {
+    type Item = io::Result<LoopEvent>;
+
+    /// Wait for the next event. This never returns `None`.
+    fn next(&mut self) -> Option<io::Result<LoopEvent>> {
+        loop {
+            match self.next_timeout(None) {
+                Ok(Some(event)) => return Some(Ok(event)),
+                Ok(None) => (),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl Drop for EventLoop {
+    fn drop(&mut self) {
+        // The signal callbacks unsubscribe on the next signal.
+        self.wake_pipe.closed.store(true, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use event::Key;
+    use std::thread;
+
+    fn event_loop() -> (EventLoop, File) {
+        let (read, write) = pipe().unwrap();
+        (EventLoop::with_input(read).unwrap(), write)
+    }
+
+    /// The next event, skipping the signals other tests may raise.
+    fn next(events: &mut EventLoop, timeout: Option<Duration>) -> io::Result<Option<LoopEvent>> {
+        loop {
+            match events.next_timeout(timeout)? {
+                Some(LoopEvent::Continue) | Some(LoopEvent::Resize(..)) => continue,
+                event => return Ok(event),
+            }
+        }
+    }
+
+    fn key(c: char) -> Option<LoopEvent> {
+        Some(LoopEvent::Input(Event::Key(Key::Char(c))))
+    }
+
+    #[test]
+    fn test_input() {
+        let (mut events, mut input) = event_loop();
+        input.write_all(b"ab\x1B[A").unwrap();
+
+        assert_eq!(next(&mut events, None).unwrap(), key('a'));
+        assert_eq!(next(&mut events, None).unwrap(), key('b'));
+        assert_eq!(next(&mut events, None).unwrap(),
+                   Some(LoopEvent::Input(Event::Key(Key::Up))));
+        assert_eq!(next(&mut events, Some(Duration::from_millis(10))).unwrap(), None);
+
+        drop(input);
+        let err = next(&mut events, None).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_split_sequence() {
+        let (mut events, mut input) = event_loop();
+        input.write_all(b"a\x1B[").unwrap();
+        assert_eq!(next(&mut events, None).unwrap(), key('a'));
+        assert_eq!(next(&mut events, Some(Duration::from_millis(10))).unwrap(), None);
+        input.write_all(b"A").unwrap();
+        assert_eq!(next(&mut events, None).unwrap(),
+                   Some(LoopEvent::Input(Event::Key(Key::Up))));
+
+        // A lone ESC is the Esc key, once the rest of a sequence didn't come in time.
+        let start = Instant::now();
+        input.write_all(b"\x1B").unwrap();
+        assert_eq!(next(&mut events, None).unwrap(),
+                   Some(LoopEvent::Input(Event::Key(Key::Esc))));
+        assert!(start.elapsed() >= Duration::from_millis(CONTROL_SEQUENCE_TIMEOUT));
+    }
+
+    #[test]
+    fn test_timers() {
+        let (mut events, _input) = event_loop();
+        let start = Instant::now();
+        let later = events.add_timeout(Duration::from_millis(60));
+        let sooner = events.add_timeout(Duration::from_millis(20));
+        let cancelled = events.add_timeout(Duration::from_millis(10));
+        assert!(events.cancel_timer(cancelled));
+        assert!(!events.cancel_timer(cancelled));
+
+        assert_eq!(next(&mut events, None).unwrap(), Some(LoopEvent::Timer(sooner)));
+        assert!(start.elapsed() >= Duration::from_millis(20));
+        assert_eq!(next(&mut events, None).unwrap(), Some(LoopEvent::Timer(later)));
+        assert!(start.elapsed() >= Duration::from_millis(60));
+
+        let interval = events.add_interval(Duration::from_millis(5));
+        assert_eq!(next(&mut events, None).unwrap(), Some(LoopEvent::Timer(interval)));
+        assert_eq!(next(&mut events, None).unwrap(), Some(LoopEvent::Timer(interval)));
+    }
+
+    #[test]
+    fn test_timers_during_input() {
+        let (mut events, mut input) = event_loop();
+        input.write_all(b"ab").unwrap();
+        assert_eq!(next(&mut events, None).unwrap(), key('a'));
+
+        // A timer due is delivered before the input read already.
+        let timer = events.add_timeout(Duration::from_millis(0));
+        assert_eq!(next(&mut events, None).unwrap(), Some(LoopEvent::Timer(timer)));
+        assert_eq!(next(&mut events, None).unwrap(), key('b'));
+    }
+
+    #[test]
+    fn test_waker() {
+        let (mut events, _input) = event_loop();
+        let waker = events.waker();
+
+        // Wakes not handled yet are delivered as one.
+        waker.wake();
+        waker.wake();
+        assert_eq!(next(&mut events, None).unwrap(), Some(LoopEvent::Wake));
+        assert_eq!(next(&mut events, Some(Duration::from_millis(10))).unwrap(), None);
+
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            waker.wake();
+        });
+        assert_eq!(next(&mut events, None).unwrap(), Some(LoopEvent::Wake));
+    }
+}
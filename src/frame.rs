@@ -0,0 +1,227 @@
+//! Pacing render loops.
+//!
+//! An animated interface has to redraw at a steady rate, but also to handle input as soon as it
+//! arrives. `Ticks` merges both: it yields the input events as they are read, and a
+//! `Frame::Tick` whenever a frame is due, blocking in between rather than spinning or needing a
+//! timer thread.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::async_stdin;
+//! use termion::event::{Event, Key};
+//! use termion::frame::{Frame, FrameLimiter};
+//! use termion::input::TermRead;
+//!
+//! let mut frame = 0;
+//! for event in FrameLimiter::new(30).ticks(async_stdin().events()) {
+//!     match event.unwrap() {
+//!         Frame::Tick => {
+//!             frame += 1;
+//!             // Draw the next frame of the animation.
+//!         }
+//!         Frame::Event(Event::Key(Key::Char('q'))) => break,
+//!         _ => (),
+//!     }
+//! }
+//! ```
+
+use std::io;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use async::AsyncReader;
+use event::Event;
+use input::Events;
+
+/// An item of `Ticks`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum Frame {
+    /// An input event.
+    Event(Event),
+    /// A frame is due.
+    Tick,
+}
+
+/// A schedule of frames at a fixed rate.
+///
+/// When frames are late, e.g. because drawing one took longer than the interval, the ones
+/// missed are skipped rather than delivered in a burst.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FrameLimiter {
+    interval: Duration,
+    /// When the next frame is due.
+    next_frame: Instant,
+}
+
+impl FrameLimiter {
+    /// A schedule of `fps` frames per second, with the first frame due now.
+    ///
+    /// A rate of zero is taken as one frame per second.
+    pub fn new(fps: u32) -> FrameLimiter {
+        FrameLimiter::with_interval(Duration::from_secs(1) / fps.max(1))
+    }
+
+    /// A schedule of a frame every `interval`, with the first frame due now.
+    pub fn with_interval(interval: Duration) -> FrameLimiter {
+        FrameLimiter {
+            interval,
+            next_frame: Instant::now(),
+        }
+    }
+
+    /// The time between two frames.
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+
+    /// The time left until the next frame is due, zero if it is already due.
+    pub fn until_next_frame(&self) -> Duration {
+        self.until_next_frame_at(Instant::now())
+    }
+
+    fn until_next_frame_at(&self, now: Instant) -> Duration {
+        self.next_frame.saturating_duration_since(now)
+    }
+
+    /// Is a frame due?
+    pub fn is_due(&self) -> bool {
+        Instant::now() >= self.next_frame
+    }
+
+    /// If a frame is due, schedule the next one and return `true`.
+    pub fn tick(&mut self) -> bool {
+        self.tick_at(Instant::now())
+    }
+
+    fn tick_at(&mut self, now: Instant) -> bool {
+        if now < self.next_frame {
+            return false;
+        }
+
+        self.next_frame += self.interval;
+        if self.next_frame <= now {
+            self.next_frame = now + self.interval;
+        }
+        true
+    }
+
+    /// Sleep until a frame is due, and schedule the next one.
+    pub fn wait(&mut self) {
+        thread::sleep(self.until_next_frame());
+        self.tick();
+    }
+
+    /// Merge this schedule with the input `events`.
+    pub fn ticks(self, events: Events<AsyncReader>) -> Ticks {
+        Ticks {
+            limiter: self,
+            events,
+            clock: Instant::now,
+        }
+    }
+}
+
+/// An iterator over input events and `Frame::Tick`s, yielding each as soon as it is due.
+///
+/// A tick is yielded before the input read during the frame, so that a flood of input doesn't
+/// hold the frames back. When the input has ended, only the ticks are left.
+pub struct Ticks {
+    limiter: FrameLimiter,
+    events: Events<AsyncReader>,
+    /// The current time, replaced in the tests.
+    clock: fn() -> Instant,
+}
+
+impl Ticks {
+    /// The schedule of the ticks.
+    pub fn limiter_mut(&mut self) -> &mut FrameLimiter {
+        &mut self.limiter
+    }
+
+    /// The input events.
+    pub fn events_mut(&mut self) -> &mut Events<AsyncReader> {
+        &mut self.events
+    }
+}
+
+impl Iterator for Ticks {
+    type Item = io::Result<Frame>;
+
+    fn next(&mut self) -> Option<io::Result<Frame>> {
+        loop {
+            let now = (self.clock)();
+            if self.limiter.tick_at(now) {
+                return Some(Ok(Frame::Tick));
+            }
+            if let Some(event) = self.events.next() {
+                return Some(event.map(Frame::Event));
+            }
+            let timeout = self.limiter.until_next_frame_at(now);
+            self.events.get_mut().wait_timeout(timeout);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use event::Key;
+    use input::TermRead;
+    use std::cell::Cell;
+    use std::sync::mpsc;
+
+    thread_local! {
+        static NOW: Cell<Option<Instant>> = const { Cell::new(None) };
+    }
+
+    fn now() -> Instant {
+        NOW.with(|now| now.get().unwrap())
+    }
+
+    fn advance(by: Duration) {
+        NOW.with(|now| now.set(Some(now.get().unwrap() + by)));
+    }
+
+    #[test]
+    fn test_frame_limiter() {
+        let mut limiter = FrameLimiter::new(20);
+        assert_eq!(limiter.interval(), Duration::from_millis(50));
+        let start = limiter.next_frame;
+        assert!(limiter.tick_at(start));
+        assert!(!limiter.tick_at(start));
+        assert_eq!(limiter.until_next_frame_at(start + Duration::from_millis(10)),
+                   Duration::from_millis(40));
+
+        assert!(limiter.tick_at(start + Duration::from_millis(50)));
+        assert!(!limiter.tick_at(start + Duration::from_millis(60)));
+
+        // Missed frames are skipped.
+        let late = start + Duration::from_millis(220);
+        assert!(limiter.tick_at(late));
+        assert!(!limiter.tick_at(late));
+        assert_eq!(limiter.until_next_frame_at(late), Duration::from_millis(50));
+    }
+
+    #[test]
+    fn test_ticks() {
+        let (send, recv) = mpsc::channel();
+        let stdin = AsyncReader::from_receiver(recv);
+        let mut ticks = FrameLimiter::with_interval(Duration::from_millis(200))
+            .ticks(stdin.events());
+        NOW.with(|now| now.set(Some(Instant::now())));
+        ticks.clock = now;
+
+        assert_eq!(ticks.next().unwrap().unwrap(), Frame::Tick);
+
+        // Input is yielded before the next frame.
+        send.send(Ok(b'a')).unwrap();
+        assert_eq!(ticks.next().unwrap().unwrap(), Frame::Event(Event::Key(Key::Char('a'))));
+
+        // A due frame comes first.
+        advance(Duration::from_millis(200));
+        send.send(Ok(b'b')).unwrap();
+        assert_eq!(ticks.next().unwrap().unwrap(), Frame::Tick);
+        assert_eq!(ticks.next().unwrap().unwrap(), Frame::Event(Event::Key(Key::Char('b'))));
+    }
+}
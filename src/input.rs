@@ -1,16 +1,41 @@
 //! User input.
 
+use std::collections::VecDeque;
 use std::io::{self, Read, Write};
 use std::ops;
+use std::time::Instant;
 
-use event::{self, Event, Key};
-use raw::IntoRawMode;
+#[cfg(unix)]
+use ansi::Splitter;
+use event::{self, Event, Key, MouseEvent, ParseError};
+use raw::{ExitGuard, IntoRawMode};
+use terminal::Quirks;
 
 /// An iterator over input keys.
 pub struct Keys<R> {
     iter: Events<R>,
 }
 
+impl<R> Keys<R> {
+    /// Handle invalid UTF-8 as given by `mode`, instead of the default.
+    ///
+    /// With `Utf8Mode::Raw`, the invalid bytes are skipped, as `Keys` only yields keys.
+    pub fn utf8_mode(self, mode: Utf8Mode) -> Keys<R> {
+        Keys { iter: self.iter.utf8_mode(mode) }
+    }
+
+    /// Choose whether to yield the malformed and unknown sequences as errors, see
+    /// `Events::strict`.
+    pub fn strict(self, strict: bool) -> Keys<R> {
+        Keys { iter: self.iter.strict(strict) }
+    }
+
+    /// Read the input `size` bytes at a time, see `EventsAndRaw::buffer_size`.
+    pub fn buffer_size(self, size: usize) -> Keys<R> {
+        Keys { iter: self.iter.buffer_size(size) }
+    }
+}
+
 impl<R: Read> Iterator for Keys<R> {
     type Item = Result<Key, io::Error>;
 
@@ -19,7 +44,7 @@ impl<R: Read> Iterator for Keys<R> {
             match self.iter.next() {
                 Some(Ok(Event::Key(k))) => return Some(Ok(k)),
                 Some(Ok(_)) => continue,
-                e @ Some(Err(_)) => e,
+                Some(Err(e)) => return Some(Err(e)),
                 None => return None,
             };
         }
@@ -31,6 +56,124 @@ pub struct Events<R>  {
     inner: EventsAndRaw<R>
 }
 
+/// How to sanitize pasted text before delivering it as `Event::Paste`.
+///
+/// Pasted text is sanitized by default: a malicious web page could have hidden escape sequences
+/// or control characters in the text copied from it, which an application echoing it would then
+/// send to the terminal. Newlines are normalized too, as the clipboard may hold `\r\n`s.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PasteSanitization {
+    /// Remove the C0 and C1 control characters, except tab and newlines.
+    pub strip_controls: bool,
+    /// Remove ESC, even if `strip_controls` is not set.
+    pub strip_escape: bool,
+    /// Replace `\r\n` and lone `\r` with `\n`.
+    pub normalize_newlines: bool,
+}
+
+impl PasteSanitization {
+    /// Deliver pasted text as is.
+    pub fn none() -> PasteSanitization {
+        PasteSanitization {
+            strip_controls: false,
+            strip_escape: false,
+            normalize_newlines: false,
+        }
+    }
+
+    /// Sanitize `text`.
+    pub fn apply(&self, text: &str) -> String {
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            match c {
+                '\r' if self.normalize_newlines => {
+                    if chars.peek() == Some(&'\n') {
+                        chars.next();
+                    }
+                    out.push('\n');
+                }
+                '\x1B' if self.strip_escape || self.strip_controls => (),
+                '\t' | '\n' | '\r' => out.push(c),
+                c if self.strip_controls && c.is_control() => (),
+                c => out.push(c),
+            }
+        }
+
+        out
+    }
+}
+
+impl Default for PasteSanitization {
+    /// Strip the control characters and normalize newlines.
+    fn default() -> PasteSanitization {
+        PasteSanitization {
+            strip_controls: true,
+            strip_escape: true,
+            normalize_newlines: true,
+        }
+    }
+}
+
+impl<R> Events<R> {
+    /// Sanitize pasted text as given by `sanitization`, instead of the default.
+    pub fn sanitize_paste(mut self, sanitization: PasteSanitization) -> Events<R> {
+        self.inner.paste = sanitization;
+        self
+    }
+
+    /// Handle invalid UTF-8 as given by `mode`, instead of the default.
+    pub fn utf8_mode(mut self, mode: Utf8Mode) -> Events<R> {
+        self.inner.utf8 = mode;
+        self
+    }
+
+    /// Coalesce the mouse motion reports read in a row, as given by `coalesce`.
+    ///
+    /// See `EventsAndRaw::coalesce_motion`.
+    pub fn coalesce_motion(mut self, coalesce: bool) -> Events<R> {
+        self.inner.coalesce_motion = coalesce;
+        self
+    }
+
+    /// Choose whether to yield the malformed and unknown sequences as errors, rather than as
+    /// `Event::Unsupported`.
+    ///
+    /// See `EventsAndRaw::strict`.
+    pub fn strict(mut self, strict: bool) -> Events<R> {
+        self.inner.strict = strict;
+        self
+    }
+
+    /// Read the input `size` bytes at a time.
+    ///
+    /// See `EventsAndRaw::buffer_size`.
+    pub fn buffer_size(self, size: usize) -> Events<R> {
+        Events { inner: self.inner.buffer_size(size) }
+    }
+}
+
+impl<R> Events<R> {
+    /// The underlying reader.
+    pub fn get_ref(&self) -> &R {
+        &self.inner.input.source
+    }
+
+    /// The underlying reader, e.g. for reading the reply to a query.
+    ///
+    /// The input already read into the buffer, but not parsed into events yet, isn't read from
+    /// it again.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner.input.source
+    }
+
+    /// Unwrap the underlying reader, dropping the input read but not parsed yet.
+    pub fn into_inner(self) -> R {
+        self.inner.input.source
+    }
+}
+
 impl<R: Read> Iterator for Events<R> {
     type Item = Result<Event, io::Error>;
 
@@ -39,56 +182,256 @@ impl<R: Read> Iterator for Events<R> {
     }
 }
 
+/// An iterator over input events and the (monotonic) time they were read at.
+pub struct EventsWithTime<R> {
+    inner: Events<R>,
+}
+
+impl<R: Read> Iterator for EventsWithTime<R> {
+    type Item = Result<(Event, Instant), io::Error>;
+
+    fn next(&mut self) -> Option<Result<(Event, Instant), io::Error>> {
+        self.inner.next().map(|res| res.map(|event| (event, Instant::now())))
+    }
+}
+
+/// What to do with input which is not valid UTF-8, e.g. when a binary file is `cat`ed, or with a
+/// terminal sending a legacy 8-bit encoding.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Utf8Mode {
+    /// Report the invalid bytes as `Event::Unsupported` (the default).
+    #[default]
+    Raw,
+    /// Report the invalid bytes as a `Key::Char('\u{FFFD}')` (the replacement character).
+    Lossy,
+    /// Report the invalid bytes as an `InvalidData` error.
+    Strict,
+}
+
 /// An iterator over input events and the bytes that define them.
+///
+/// Pasted text is sanitized in the event only, the bytes are left as read.
 pub struct EventsAndRaw<R> {
+    input: BufferedInput<R>,
+    paste: PasteSanitization,
+    utf8: Utf8Mode,
+    coalesce_motion: bool,
+    strict: bool,
+    /// The number of bytes parsed so far.
+    offset: u64,
+    /// The event read after coalesced motion reports, to be yielded next.
+    pending: Option<Result<(Event, Vec<u8>), io::Error>>,
+}
+
+impl<R> EventsAndRaw<R> {
+    /// Sanitize pasted text as given by `sanitization`, instead of the default.
+    pub fn sanitize_paste(mut self, sanitization: PasteSanitization) -> EventsAndRaw<R> {
+        self.paste = sanitization;
+        self
+    }
+
+    /// Handle invalid UTF-8 as given by `mode`, instead of the default.
+    pub fn utf8_mode(mut self, mode: Utf8Mode) -> EventsAndRaw<R> {
+        self.utf8 = mode;
+        self
+    }
+
+    /// Coalesce the mouse motion reports read in a row, as given by `coalesce`.
+    ///
+    /// When the mouse moves faster than the events are handled, the reports pile up in the
+    /// input, and handling each of them leaves the application further and further behind. With
+    /// coalescing, a `MouseEvent::Hold` is only yielded once the following reports are read: the
+    /// motion reports directly following it are skipped, and only the last position is yielded,
+    /// with the bytes of all the reports.
+    ///
+    /// The reports are looked for until the reader has no more input, so this is meant for
+    /// readers which don't block, such as `async_stdin`: with a blocking reader, a motion is only
+    /// yielded once some other input follows it.
+    pub fn coalesce_motion(mut self, coalesce: bool) -> EventsAndRaw<R> {
+        self.coalesce_motion = coalesce;
+        self
+    }
+
+    /// Choose whether to yield the malformed and unknown sequences as errors, rather than as
+    /// `Event::Unsupported`.
+    ///
+    /// The errors are `InvalidData` errors, whose inner error is an `event::ParseError` telling
+    /// where the sequence was in the input and what was expected instead. Invalid UTF-8 is still
+    /// handled as the `Utf8Mode` says, except that `Utf8Mode::Strict` errors are detailed too.
+    pub fn strict(mut self, strict: bool) -> EventsAndRaw<R> {
+        self.strict = strict;
+        self
+    }
+
+    /// Read the input `size` bytes at a time, instead of the default of 1024.
+    ///
+    /// The input is read into a buffer and parsed from there, so that a large paste takes a
+    /// read per buffer rather than per byte. A lone ESC is told from the start of an escape
+    /// sequence by being the last byte of a read which didn't fill the buffer.
+    pub fn buffer_size(mut self, size: usize) -> EventsAndRaw<R> {
+        self.input.size = size.max(1);
+        self
+    }
+}
+
+/// The default size of the buffer the input is read into.
+const DEFAULT_BUFFER_SIZE: usize = 1024;
+
+/// A reader read a buffer at a time, iterating over the bytes.
+struct BufferedInput<R> {
     source: R,
-    leftover: Option<u8>,
+    /// The bytes of the last read.
+    buf: Vec<u8>,
+    /// The first byte of `buf` not iterated over yet.
+    pos: usize,
+    /// The number of bytes to read at once.
+    size: usize,
+}
+
+impl<R> BufferedInput<R> {
+    fn new(source: R) -> BufferedInput<R> {
+        BufferedInput {
+            source,
+            buf: Vec::new(),
+            pos: 0,
+            size: DEFAULT_BUFFER_SIZE,
+        }
+    }
+
+    /// Has all the input available been iterated over, as far as the last read tells?
+    fn is_drained(&self) -> bool {
+        self.pos == self.buf.len() && self.buf.len() < self.size
+    }
+
+    /// Iterate over the last byte again.
+    fn unread(&mut self) {
+        self.pos -= 1;
+    }
+}
+
+impl<R: Read> Iterator for BufferedInput<R> {
+    type Item = io::Result<u8>;
+
+    fn next(&mut self) -> Option<io::Result<u8>> {
+        if self.pos == self.buf.len() {
+            self.buf.resize(self.size, 0);
+            let read = loop {
+                match self.source.read(&mut self.buf) {
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    read => break read,
+                }
+            };
+            self.buf.truncate(*read.as_ref().unwrap_or(&0));
+            self.pos = 0;
+            match read {
+                Ok(0) => return None,
+                Ok(_) => {}
+                Err(e) => return Some(Err(e)),
+            }
+        }
+
+        self.pos += 1;
+        Some(Ok(self.buf[self.pos - 1]))
+    }
 }
 
 impl<R: Read> Iterator for EventsAndRaw<R> {
     type Item = Result<(Event, Vec<u8>), io::Error>;
 
     fn next(&mut self) -> Option<Result<(Event, Vec<u8>), io::Error>> {
-        let mut source = &mut self.source;
+        let first = match self.pending.take() {
+            Some(pending) => pending,
+            None => self.next_event()?,
+        };
+
+        let (mut position, mut raw) = match first {
+            Ok((Event::Mouse(MouseEvent::Hold(x, y)), raw)) if self.coalesce_motion => {
+                ((x, y), raw)
+            }
+            other => return Some(other),
+        };
+        loop {
+            match self.next_event() {
+                Some(Ok((Event::Mouse(MouseEvent::Hold(x, y)), next_raw))) => {
+                    position = (x, y);
+                    raw.extend(next_raw);
+                }
+                Some(other) => {
+                    self.pending = Some(other);
+                    break;
+                }
+                None => break,
+            }
+        }
 
-        if let Some(c) = self.leftover {
-            // we have a leftover byte, use it
-            self.leftover = None;
-            return Some(parse_event(c, &mut source.bytes()));
+        Some(Ok((Event::Mouse(MouseEvent::Hold(position.0, position.1)), raw)))
+    }
+}
+
+impl<R: Read> EventsAndRaw<R> {
+    /// Read an event, as delivered without coalescing.
+    fn next_event(&mut self) -> Option<Result<(Event, Vec<u8>), io::Error>> {
+        let (event, mut raw) = match self.read_event()? {
+            Ok(parsed) => parsed,
+            Err(e) => return Some(Err(e)),
+        };
+        let offset = self.offset;
+        let utf8_error = match event {
+            Err(ref e) => e.kind() == io::ErrorKind::InvalidData,
+            Ok(_) => false,
+        };
+        if utf8_error {
+            // The character was cut short by the last byte, which may start the next one.
+            let n = raw.len();
+            if n >= 2 && raw[n - 2] >= 0x80 && raw[n - 1] & 0xC0 != 0x80 {
+                raw.pop();
+                self.input.unread();
+            }
         }
+        self.offset += raw.len() as u64;
 
-        // Here we read two bytes at a time. We need to distinguish between single ESC key presses,
-        // and escape sequences (which start with ESC or a x1B byte). The idea is that if this is
-        // an escape sequence, we will read multiple bytes (the first byte being ESC) but if this
-        // is a single ESC keypress, we will only read a single byte.
-        let mut buf = [0u8; 2];
-        let res = match source.read(&mut buf) {
-            Ok(0) => return None,
-            Ok(1) => {
-                match buf[0] {
-                    b'\x1B' => Ok((Event::Key(Key::Esc), vec![b'\x1B'])),
-                    c => parse_event(c, &mut source.bytes()),
+        Some(match event {
+            Ok(Event::Paste(text)) => Ok((Event::Paste(self.paste.apply(&text)), raw)),
+            Ok(event) => Ok((event, raw)),
+            Err(_) if utf8_error => {
+                match self.utf8 {
+                    Utf8Mode::Raw => Ok((Event::Unsupported(raw.clone()), raw)),
+                    Utf8Mode::Lossy => Ok((Event::Key(Key::Char('\u{FFFD}')), raw)),
+                    Utf8Mode::Strict => {
+                        let error = ParseError::new(offset, raw, true);
+                        Err(io::Error::new(io::ErrorKind::InvalidData, error))
+                    }
                 }
             }
-            Ok(2) => {
-                let mut option_iter = &mut Some(buf[1]).into_iter();
-                let result = {
-                    let mut iter = option_iter.map(|c| Ok(c)).chain(source.bytes());
-                    parse_event(buf[0], &mut iter)
-                };
-                // If the option_iter wasn't consumed, keep the byte for later.
-                self.leftover = option_iter.next();
-                result
+            Err(_) if self.strict => {
+                let error = ParseError::new(offset, raw, false);
+                Err(io::Error::new(io::ErrorKind::InvalidData, error))
             }
-            Ok(_) => unreachable!(),
-            Err(e) => Err(e),
+            Err(_) => Ok((Event::Unsupported(raw.clone()), raw)),
+        })
+    }
+}
+
+/// An event, or the error parsing it, and the bytes read.
+type Parsed = (Result<Event, io::Error>, Vec<u8>);
+
+impl<R: Read> EventsAndRaw<R> {
+    /// Read an event.
+    fn read_event(&mut self) -> Option<Result<Parsed, io::Error>> {
+        let first = match self.input.next()? {
+            Ok(first) => first,
+            Err(e) => return Some(Err(e)),
         };
 
-        Some(res)
+        // An escape sequence is sent at once, so an ESC with no input after it is the ESC key.
+        if first == b'\x1B' && self.input.is_drained() {
+            return Some(Ok((Ok(Event::Key(Key::Esc)), vec![b'\x1B'])));
+        }
+        Some(Ok(parse_event(first, &mut self.input)))
     }
 }
 
-fn parse_event<I>(item: u8, iter: &mut I) -> Result<(Event, Vec<u8>), io::Error>
+fn parse_event<I>(item: u8, iter: &mut I) -> Parsed
     where I: Iterator<Item = Result<u8, io::Error>>
 {
     let mut buf = vec![item];
@@ -98,9 +441,87 @@ fn parse_event<I>(item: u8, iter: &mut I) -> Result<(Event, Vec<u8>), io::Error>
                                     });
         event::parse_event(item, &mut iter)
     };
-    result.or(Ok(Event::Unsupported(buf.clone()))).map(|e| (e, buf))
+    (result, buf)
+}
+
+
+/// The input read but not parsed into events yet, for the event sources reading it themselves.
+///
+/// Reading it returns `Ok(0)` once it is empty, so `Events` yields `None` until more input is
+/// added.
+pub(crate) struct InputBuffer {
+    pub(crate) buf: VecDeque<u8>,
+    /// The start of an escape sequence or paste the input added ends in the middle of.
+    #[cfg(unix)]
+    held: Vec<u8>,
+}
+
+impl InputBuffer {
+    pub(crate) fn new() -> InputBuffer {
+        InputBuffer {
+            buf: VecDeque::new(),
+            #[cfg(unix)]
+            held: Vec::new(),
+        }
+    }
+
+    /// Parse the input into events.
+    pub(crate) fn events(self) -> Events<InputBuffer> {
+        TermRead::events(self)
+    }
+
+    /// Add `input`, holding back an escape sequence or paste it ends in the middle of, until
+    /// the rest of it is added or `release` is called.
+    #[cfg(unix)]
+    pub(crate) fn extend_holding(&mut self, input: &[u8]) {
+        self.held.extend_from_slice(input);
+        let complete = incomplete_start(&self.held).unwrap_or(self.held.len());
+        self.buf.extend(self.held.drain(..complete));
+    }
+
+    /// Is part of the input held back?
+    #[cfg(unix)]
+    pub(crate) fn is_holding(&self) -> bool {
+        !self.held.is_empty()
+    }
+
+    /// Add the input held back as it is, e.g. once no more of it came in time.
+    #[cfg(unix)]
+    pub(crate) fn release(&mut self) {
+        self.buf.extend(self.held.drain(..));
+    }
+}
+
+/// The start of the escape sequence or paste `input` ends in the middle of, if it does.
+#[cfg(unix)]
+fn incomplete_start(input: &[u8]) -> Option<usize> {
+    const PASTE_START: &[u8] = b"\x1B[200~";
+    const PASTE_END: &[u8] = b"\x1B[201~";
+
+    let find = |pattern: &[u8]| input.windows(pattern.len()).rposition(|w| w == pattern);
+    if let Some(start) = find(PASTE_START) {
+        if find(PASTE_END).is_none_or(|end| end < start) {
+            return Some(start);
+        }
+    }
+
+    let mut splitter = Splitter::new();
+    let _ = splitter.split(input, |_| Ok(()));
+    let pending = splitter.take_pending().len();
+    if pending > 0 {
+        return Some(input.len() - pending);
+    }
+
+    // An X10 mouse report carries three bytes after its `M`, which the splitter takes for text.
+    let len = input.len();
+    (len.saturating_sub(5)..len.saturating_sub(2)).find(|&i| input[i..].starts_with(b"\x1B[M"))
 }
 
+impl Read for InputBuffer {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.buf.read(buf)
+    }
+}
 
 /// Extension to `Read` trait.
 pub trait TermRead {
@@ -110,6 +531,15 @@ pub trait TermRead {
     /// An iterator over key inputs.
     fn keys(self) -> Keys<Self> where Self: Sized;
 
+    /// An iterator over input events and the time they were read at.
+    ///
+    /// This is useful for telling double clicks from single ones, or for measuring input latency.
+    fn events_with_time(self) -> EventsWithTime<Self>
+        where Self: Sized
+    {
+        EventsWithTime { inner: self.events() }
+    }
+
     /// Read a line.
     ///
     /// EOT and ETX will abort the prompt, returning `None`. Newline or carriage return will
@@ -167,36 +597,248 @@ pub trait TermReadEventsAndRaw {
 impl<R: Read> TermReadEventsAndRaw for R {
     fn events_and_raw(self) -> EventsAndRaw<Self> {
         EventsAndRaw {
-            source: self,
-            leftover: None,
+            input: BufferedInput::new(self),
+            paste: PasteSanitization::default(),
+            utf8: Utf8Mode::default(),
+            coalesce_motion: false,
+            strict: false,
+            offset: 0,
+            pending: None,
         }
     }
 }
 
+/// The escape code to enable the application keypad mode (DECKPAM).
+pub(crate) const ENTER_KEYPAD_SEQUENCE: &str = esc!("=");
+
+/// The escape code to restore the numeric keypad mode (DECKPNM).
+pub(crate) const EXIT_KEYPAD_SEQUENCE: &str = esc!(">");
+
+/// A terminal in application keypad mode, telling the keys of the numeric keypad apart.
+///
+/// They are then reported as `Key::Keypad` and `Key::KeypadEnter`. This can be obtained through
+/// the `From` implementations.
+pub struct KeypadTerminal<W: Write> {
+    term: W,
+}
+
+impl<W: Write> From<W> for KeypadTerminal<W> {
+    fn from(mut from: W) -> KeypadTerminal<W> {
+        from.write_all(ENTER_KEYPAD_SEQUENCE.as_bytes()).unwrap();
+
+        KeypadTerminal { term: from }
+    }
+}
+
+impl<W: Write> Drop for KeypadTerminal<W> {
+    fn drop(&mut self) {
+        self.term.write_all(EXIT_KEYPAD_SEQUENCE.as_bytes()).unwrap();
+    }
+}
+
+impl<W: Write> ops::Deref for KeypadTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.term
+    }
+}
+
+impl<W: Write> ops::DerefMut for KeypadTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.term
+    }
+}
+
+impl<W: Write> Write for KeypadTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.term.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.term.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.term.flush()
+    }
+}
+
+/// The escape code to enable the reports of the terminal gaining and losing the focus.
+pub(crate) const ENTER_FOCUS_EVENTS_SEQUENCE: &str = csi!("?1004h");
+
+/// The escape code to disable the focus reports.
+pub(crate) const EXIT_FOCUS_EVENTS_SEQUENCE: &str = csi!("?1004l");
+
+/// The escape code to enable bracketed paste.
+pub(crate) const ENTER_BRACKETED_PASTE_SEQUENCE: &str = csi!("?2004h");
+
+/// The escape code to disable bracketed paste.
+pub(crate) const EXIT_BRACKETED_PASTE_SEQUENCE: &str = csi!("?2004l");
+
+/// A terminal with bracketed paste, which tells pasted text from typed keys.
+///
+/// Pasted text is then reported as a single `Event::Paste`, rather than as keys (which would
+/// e.g. run the commands of a pasted script line by line). This can be obtained through the
+/// `From` implementations.
+///
+/// Bracketed paste is left off on terminals known to break it (see `terminal::Quirks`), where
+/// pasted text keeps coming as keys.
+pub struct BracketedPasteTerminal<W: Write> {
+    term: W,
+    enabled: bool,
+    on_exit: Option<ExitGuard>,
+}
+
+impl<W: Write> From<W> for BracketedPasteTerminal<W> {
+    fn from(mut from: W) -> BracketedPasteTerminal<W> {
+        let enabled = !Quirks::from_env().broken_bracketed_paste;
+        let mut on_exit = None;
+        if enabled {
+            from.write_all(ENTER_BRACKETED_PASTE_SEQUENCE.as_bytes()).unwrap();
+            on_exit = ExitGuard::write(EXIT_BRACKETED_PASTE_SEQUENCE);
+        }
+
+        BracketedPasteTerminal { term: from, enabled, on_exit }
+    }
+}
+
+impl<W: Write> Drop for BracketedPasteTerminal<W> {
+    fn drop(&mut self) {
+        self.on_exit.take();
+        if self.enabled {
+            self.term.write_all(EXIT_BRACKETED_PASTE_SEQUENCE.as_bytes()).unwrap();
+        }
+    }
+}
+
+impl<W: Write> ops::Deref for BracketedPasteTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.term
+    }
+}
+
+impl<W: Write> ops::DerefMut for BracketedPasteTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.term
+    }
+}
+
+impl<W: Write> Write for BracketedPasteTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.term.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.term.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.term.flush()
+    }
+}
+
+/// The escape code to enable xterm's `modifyOtherKeys` (level 2).
+pub(crate) const ENTER_MODIFY_OTHER_KEYS_SEQUENCE: &str = csi!(">4;2m");
+
+/// The escape code to restore the default `modifyOtherKeys` behavior.
+pub(crate) const EXIT_MODIFY_OTHER_KEYS_SEQUENCE: &str = csi!(">4m");
+
+/// A terminal reporting the combinations that are otherwise indistinguishable, e.g. Ctrl+Shift+A
+/// from Ctrl+A, using xterm's `modifyOtherKeys`.
+///
+/// They are then reported as `Key::Modified`. Terminals not supporting `modifyOtherKeys` ignore
+/// it. This can be obtained through the `From` implementations.
+pub struct ModifyOtherKeysTerminal<W: Write> {
+    term: W,
+    on_exit: Option<ExitGuard>,
+}
+
+impl<W: Write> From<W> for ModifyOtherKeysTerminal<W> {
+    fn from(mut from: W) -> ModifyOtherKeysTerminal<W> {
+        from.write_all(ENTER_MODIFY_OTHER_KEYS_SEQUENCE.as_bytes()).unwrap();
+
+        ModifyOtherKeysTerminal {
+            term: from,
+            on_exit: ExitGuard::write(EXIT_MODIFY_OTHER_KEYS_SEQUENCE),
+        }
+    }
+}
+
+impl<W: Write> Drop for ModifyOtherKeysTerminal<W> {
+    fn drop(&mut self) {
+        self.on_exit.take();
+        self.term.write_all(EXIT_MODIFY_OTHER_KEYS_SEQUENCE.as_bytes()).unwrap();
+    }
+}
+
+impl<W: Write> ops::Deref for ModifyOtherKeysTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.term
+    }
+}
+
+impl<W: Write> ops::DerefMut for ModifyOtherKeysTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.term
+    }
+}
+
+impl<W: Write> Write for ModifyOtherKeysTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.term.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.term.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.term.flush()
+    }
+}
+
 /// A sequence of escape codes to enable terminal mouse support.
-const ENTER_MOUSE_SEQUENCE: &'static str = csi!("?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h");
+pub(crate) const ENTER_MOUSE_SEQUENCE: &str = csi!("?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h");
 
 /// A sequence of escape codes to disable terminal mouse support.
-const EXIT_MOUSE_SEQUENCE: &'static str = csi!("?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l");
+pub(crate) const EXIT_MOUSE_SEQUENCE: &str = csi!("?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l");
+
+/// The sequences to enable and disable mouse support without the SGR encoding.
+pub(crate) const ENTER_MOUSE_URXVT_SEQUENCE: &str = csi!("?1000h\x1b[?1002h\x1b[?1015h");
+pub(crate) const EXIT_MOUSE_URXVT_SEQUENCE: &str = csi!("?1015l\x1b[?1002l\x1b[?1000l");
 
 /// A terminal with added mouse support.
 ///
-/// This can be obtained through the `From` implementations.
+/// This can be obtained through the `From` implementations. The encodings of the reports are
+/// chosen from those the terminal is known to support (see `terminal::Quirks`).
 pub struct MouseTerminal<W: Write> {
     term: W,
+    exit: &'static str,
+    on_exit: Option<ExitGuard>,
 }
 
 impl<W: Write> From<W> for MouseTerminal<W> {
     fn from(mut from: W) -> MouseTerminal<W> {
-        from.write_all(ENTER_MOUSE_SEQUENCE.as_bytes()).unwrap();
+        let (enter, exit) = if Quirks::from_env().no_sgr_mouse {
+            (ENTER_MOUSE_URXVT_SEQUENCE, EXIT_MOUSE_URXVT_SEQUENCE)
+        } else {
+            (ENTER_MOUSE_SEQUENCE, EXIT_MOUSE_SEQUENCE)
+        };
+        from.write_all(enter.as_bytes()).unwrap();
 
-        MouseTerminal { term: from }
+        MouseTerminal { term: from, exit, on_exit: ExitGuard::write(exit) }
     }
 }
 
 impl<W: Write> Drop for MouseTerminal<W> {
     fn drop(&mut self) {
-        self.term.write_all(EXIT_MOUSE_SEQUENCE.as_bytes()).unwrap();
+        self.on_exit.take();
+        self.term.write_all(self.exit.as_bytes()).unwrap();
     }
 }
 
@@ -219,6 +861,10 @@ impl<W: Write> Write for MouseTerminal<W> {
         self.term.write(buf)
     }
 
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.term.write_vectored(bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         self.term.flush()
     }
@@ -228,7 +874,8 @@ impl<W: Write> Write for MouseTerminal<W> {
 mod test {
     use super::*;
     use std::io;
-    use event::{Key, Event, MouseEvent, MouseButton};
+    use std::time::Instant;
+    use event::{CursorKey, Key, Event, Modifiers, MouseEvent, MouseButton};
 
     #[test]
     fn test_keys() {
@@ -301,6 +948,20 @@ mod test {
         assert_eq!(input.iter().map(|b| *b).collect::<Vec<u8>>(), output)
     }
 
+    #[test]
+    fn test_events_with_time() {
+        let before = Instant::now();
+        let mut i = b"a\x1B[D".events_with_time();
+
+        let (event, first) = i.next().unwrap().unwrap();
+        assert_eq!(event, Event::Key(Key::Char('a')));
+        let (event, second) = i.next().unwrap().unwrap();
+        assert_eq!(event, Event::Key(Key::Left));
+        assert!(i.next().is_none());
+
+        assert!(before <= first && first <= second);
+    }
+
     #[test]
     fn test_function_keys() {
         let mut st = b"\x1BOP\x1BOQ\x1BOR\x1BOS".keys();
@@ -316,6 +977,246 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_shifted_function_keys() {
+        let mut st = b"\x1B[1;2P\x1B[1;2Q\x1B[1;2R\x1B[1;2S\x1B[15;2~\x1B[17;2~\x1B[18;2~\
+        \x1B[19;2~\x1B[20;2~\x1B[21;2~\x1B[23;2~\x1B[24;2~"
+                .keys();
+        for i in 13..25 {
+            assert_eq!(st.next().unwrap().unwrap(), Key::F(i));
+        }
+
+        // VT220 numbering.
+        let mut st = b"\x1B[25~\x1B[26~\x1B[28~\x1B[29~\x1B[31~\x1B[32~\x1B[33~\x1B[34~\x1BO2P"
+            .keys();
+        for i in 13..21 {
+            assert_eq!(st.next().unwrap().unwrap(), Key::F(i));
+        }
+        assert_eq!(st.next().unwrap().unwrap(), Key::F(13));
+    }
+
+    #[test]
+    fn test_modified_cursor_keys() {
+        let shift = Modifiers { shift: true, ..Modifiers::default() };
+        let alt = Modifiers { alt: true, ..Modifiers::default() };
+        let ctrl = Modifiers { ctrl: true, ..Modifiers::default() };
+        let ctrl_shift = Modifiers { shift: true, ctrl: true, ..Modifiers::default() };
+
+        let mut st = b"\x1B[1;2D\x1B[1;3C\x1B[1;5B\x1B[1;6A\x1B[1;2H\x1B[1;6F\x1B[1;5~\x1B[4;2~\
+        \x1B[1;1A"
+                .keys();
+        for &(modifiers, key) in &[(shift, CursorKey::Left),
+                                   (alt, CursorKey::Right),
+                                   (ctrl, CursorKey::Down),
+                                   (ctrl_shift, CursorKey::Up),
+                                   (shift, CursorKey::Home),
+                                   (ctrl_shift, CursorKey::End),
+                                   (ctrl, CursorKey::Home),
+                                   (shift, CursorKey::End)] {
+            assert_eq!(st.next().unwrap().unwrap(), Key::ModifiedCursor(modifiers, key));
+        }
+        assert_eq!(st.next().unwrap().unwrap(), Key::Up);
+    }
+
+    #[test]
+    fn test_keypad_keys() {
+        let mut st = b"\x1BOp\x1BOy\x1BOj\x1BOk\x1BOm\x1BOo\x1BOn\x1BOM".keys();
+        for c in "09*+-/.".chars() {
+            assert_eq!(st.next().unwrap().unwrap(), Key::Keypad(c));
+        }
+        assert_eq!(st.next().unwrap().unwrap(), Key::KeypadEnter);
+    }
+
+    #[test]
+    fn test_modify_other_keys() {
+        let ctrl_shift = Modifiers { shift: true, ctrl: true, ..Modifiers::default() };
+        let ctrl = Modifiers { ctrl: true, ..Modifiers::default() };
+
+        let mut st = b"\x1B[27;6;65~\x1B[27;5;44~\x1B[27;5;13~\x1B[27;5;97~\x1B[27;3;120~\
+        \x1B[27;2;65~\x1B[27;5;233~"
+                .keys();
+        assert_eq!(st.next().unwrap().unwrap(), Key::Modified(ctrl_shift, 'A'));
+        assert_eq!(st.next().unwrap().unwrap(), Key::Modified(ctrl, ','));
+        assert_eq!(st.next().unwrap().unwrap(), Key::Modified(ctrl, '\n'));
+        assert_eq!(st.next().unwrap().unwrap(), Key::Ctrl('a'));
+        assert_eq!(st.next().unwrap().unwrap(), Key::Alt('x'));
+        assert_eq!(st.next().unwrap().unwrap(), Key::Char('A'));
+        assert_eq!(st.next().unwrap().unwrap(), Key::Modified(ctrl, 'é'));
+    }
+
+    #[test]
+    fn test_paste() {
+        let input = b"a\x1B[200~echo hi\r\n\x1B]0;x\x07\x1B[201~b";
+
+        let mut st = input.events();
+        assert_eq!(st.next().unwrap().unwrap(), Event::Key(Key::Char('a')));
+        assert_eq!(st.next().unwrap().unwrap(), Event::Paste("echo hi\n]0;x".into()));
+        assert_eq!(st.next().unwrap().unwrap(), Event::Key(Key::Char('b')));
+
+        let mut st = input.events().sanitize_paste(PasteSanitization::none());
+        st.next();
+        assert_eq!(st.next().unwrap().unwrap(),
+                   Event::Paste("echo hi\r\n\x1B]0;x\x07".into()));
+
+        let mut st = input.events_and_raw();
+        st.next();
+        let (_, raw) = st.next().unwrap().unwrap();
+        assert_eq!(raw, &input[1..input.len() - 1]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_input_buffer_holding() {
+        let mut st = InputBuffer::new().events();
+        st.get_mut().extend_holding(b"a\x1B[200~pas");
+        assert_eq!(st.next().unwrap().unwrap(), Event::Key(Key::Char('a')));
+        assert!(st.next().is_none());
+        st.get_mut().extend_holding(b"te\x1B[201~\x1B[M ");
+        assert_eq!(st.next().unwrap().unwrap(), Event::Paste("paste".into()));
+        assert!(st.next().is_none());
+        st.get_mut().extend_holding(b"!!");
+        assert_eq!(st.next().unwrap().unwrap(),
+                   Event::Mouse(MouseEvent::Press(MouseButton::Left, 1, 1)));
+        assert!(!st.get_ref().is_holding());
+
+        st.get_mut().extend_holding(b"\x1B");
+        assert!(st.next().is_none());
+        st.get_mut().release();
+        assert_eq!(st.next().unwrap().unwrap(), Event::Key(Key::Esc));
+    }
+
+    #[test]
+    fn test_focus() {
+        let mut st = b"\x1B[Ia\x1B[O".events().strict(true);
+        assert_eq!(st.next().unwrap().unwrap(), Event::FocusGained);
+        assert_eq!(st.next().unwrap().unwrap(), Event::Key(Key::Char('a')));
+        assert_eq!(st.next().unwrap().unwrap(), Event::FocusLost);
+        assert!(st.next().is_none());
+    }
+
+    #[test]
+    fn test_paste_sanitization() {
+        let text = "a\rb\r\nc\x1B[2J\x07\u{9B}d\te";
+        let escape_only = PasteSanitization { strip_escape: true, ..PasteSanitization::none() };
+
+        assert_eq!(PasteSanitization::default().apply(text), "a\nb\nc[2Jd\te");
+        assert_eq!(escape_only.apply(text), "a\rb\r\nc[2J\x07\u{9B}d\te");
+    }
+
+    #[test]
+    fn test_strict() {
+        let input = b"a\x1B[99~b\x1B[<0;x;1Mc\x1B[M";
+
+        let events: Vec<Event> = input.events().map(Result::unwrap).collect();
+        assert_eq!(events[1], Event::Unsupported(b"\x1B[99~".to_vec()));
+        assert_eq!(events[3], Event::Unsupported(b"\x1B[<0;x".to_vec()));
+        assert_eq!(events.last(), Some(&Event::Unsupported(b"\x1B[M".to_vec())));
+
+        let errors: Vec<ParseError> = input.events()
+            .strict(true)
+            .filter_map(|event| event.err())
+            .map(|err| *err.into_inner().unwrap().downcast::<ParseError>().unwrap())
+            .collect();
+        let found: Vec<(u64, &[u8])> = errors.iter().map(|e| (e.offset, &e.bytes[..])).collect();
+        assert_eq!(found,
+                   [(1, &b"\x1B[99~"[..]), (7, &b"\x1B[<0;x"[..]), (17, &b"\x1B[M"[..])]);
+        assert_eq!(errors[0].to_string(),
+                   "unexpected input \"\\x1b[99~\" at byte 1, expected a known key code, \
+                    `ESC [ code ~`");
+        assert_eq!(errors[1].expected, "an SGR mouse report, `ESC [ < button ; x ; y M`");
+
+        let mut keys = b"ab\xFF".keys().utf8_mode(Utf8Mode::Strict);
+        let err = keys.nth(2).unwrap().unwrap_err();
+        let error = err.get_ref().unwrap().downcast_ref::<ParseError>().unwrap();
+        assert_eq!((error.offset, error.expected), (2, "valid UTF-8"));
+    }
+
+    #[test]
+    fn test_utf8_modes() {
+        let input = b"a\xFFb\xC3c\xC3\xA9";
+
+        let events: Vec<Event> = input.events().map(Result::unwrap).collect();
+        assert_eq!(events,
+                   [Event::Key(Key::Char('a')),
+                    Event::Unsupported(vec![0xFF]),
+                    Event::Key(Key::Char('b')),
+                    Event::Unsupported(vec![0xC3]),
+                    Event::Key(Key::Char('c')),
+                    Event::Key(Key::Char('é'))]);
+
+        let keys: String = input.keys()
+            .utf8_mode(Utf8Mode::Lossy)
+            .map(|key| match key.unwrap() {
+                     Key::Char(c) => c,
+                     key => panic!("unexpected key {:?}", key),
+                 })
+            .collect();
+        assert_eq!(keys, "a\u{FFFD}b\u{FFFD}cé");
+
+        let mut keys = input.keys().utf8_mode(Utf8Mode::Strict);
+        assert_eq!(keys.next().unwrap().unwrap(), Key::Char('a'));
+        assert_eq!(keys.next().unwrap().unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(keys.next().unwrap().unwrap(), Key::Char('b'));
+    }
+
+    /// A reader counting the reads, to check that the input is read a buffer at a time.
+    struct CountReads<'a> {
+        input: &'a [u8],
+        reads: usize,
+    }
+
+    impl<'a> Read for CountReads<'a> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.reads += 1;
+            self.input.read(buf)
+        }
+    }
+
+    #[test]
+    fn test_buffer_size() {
+        let text = "x".repeat(1 << 20);
+        let input = format!("\x1B[200~{}\x1B[201~a", text);
+        let mut reader = CountReads { input: input.as_bytes(), reads: 0 };
+        let events: Vec<Event> =
+            (&mut reader).events().buffer_size(1 << 16).map(Result::unwrap).collect();
+        assert_eq!(events, vec![Event::Paste(text), Event::Key(Key::Char('a'))]);
+        assert_eq!(reader.reads, input.len() / (1 << 16) + 2);
+
+        // An ESC ending a full buffer may start a sequence.
+        let mut keys = b"a\x1B[D\x1B".keys().buffer_size(2);
+        assert_eq!(keys.next().unwrap().unwrap(), Key::Char('a'));
+        assert_eq!(keys.next().unwrap().unwrap(), Key::Left);
+        assert_eq!(keys.next().unwrap().unwrap(), Key::Esc);
+        assert!(keys.next().is_none());
+    }
+
+    #[test]
+    fn test_coalesce_motion() {
+        let input = b"\x1B[<32;1;1M\x1B[<32;2;1M\x1B[<32;3;2Ma\x1B[<32;4;2M\x1B[<0;4;2M";
+        let hold = |x, y| Event::Mouse(MouseEvent::Hold(x, y));
+
+        let events: Vec<Event> = input.events().map(Result::unwrap).collect();
+        assert_eq!(events.len(), 6);
+
+        let events: Vec<Event> = input.events().coalesce_motion(true).map(Result::unwrap).collect();
+        assert_eq!(events,
+                   [hold(3, 2),
+                    Event::Key(Key::Char('a')),
+                    hold(4, 2),
+                    Event::Mouse(MouseEvent::Press(event::MouseButton::Left, 4, 2))]);
+
+        let mut st = input.events_and_raw().coalesce_motion(true);
+        let (_, raw) = st.next().unwrap().unwrap();
+        assert_eq!(raw, &input[..30]);
+    }
+
+    #[test]
+    fn test_keypad_terminal() {
+        let mut term = KeypadTerminal::from(Vec::new());
+        term.write_all(b"x").unwrap();
+        assert_eq!(&**term, b"\x1B=x");
+    }
+
     #[test]
     fn test_special_keys() {
         let mut st = b"\x1B[2~\x1B[H\x1B[7~\x1B[5~\x1B[3~\x1B[F\x1B[8~\x1B[6~".keys();
@@ -338,6 +1239,7 @@ mod test {
     }
 
     fn line_match(a: &str, b: Option<&str>) {
+        let _lock = ::raw::TEST_TTY.lock().unwrap_or_else(|e| e.into_inner());
         let mut sink = io::sink();
 
         let line = a.as_bytes().read_line().unwrap();
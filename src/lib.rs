@@ -9,49 +9,132 @@
 //! Supports Redox, Mac OS X, and Linux (or, in general, ANSI terminals).
 //!
 //! For more information refer to the [README](https://github.com/ticki/termion).
+//!
+//! # `no_std`
+//!
+//! Disabling the default `std` feature leaves only the generation of escape sequences (cursor
+//! movement, colors, clearing, styles and so on), which neither needs an operating system nor
+//! allocates. This is useful for driving a terminal over a serial line from embedded code.
 #![warn(missing_docs)]
+// The tests use std even without the feature, e.g. to format the sequences into strings.
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
 
-#[cfg(target_os = "redox")]
-#[path="sys/redox/mod.rs"]
-mod sys;
+#[cfg(not(any(feature = "std", test)))]
+extern crate core as std;
 
-#[cfg(unix)]
+// This includes Redox, through relibc.
+#[cfg(all(feature = "std", unix))]
 #[path="sys/unix/mod.rs"]
 mod sys;
 
+#[cfg(all(feature = "std", target_arch = "wasm32", target_os = "unknown"))]
+#[path="sys/wasm/mod.rs"]
+mod sys;
+
+#[cfg(feature = "std")]
 pub use sys::size::terminal_size;
+#[cfg(feature = "std")]
 pub use sys::tty::{is_tty, get_tty};
+#[cfg(feature = "std")]
+pub use raw::is_raw_mode_enabled;
 
+#[cfg(feature = "std")]
+mod ansi;
+#[cfg(feature = "std")]
 mod async;
+#[cfg(feature = "std")]
 pub use async::{AsyncReader, async_stdin};
 
+#[cfg(feature = "std")]
+mod resize;
+#[cfg(feature = "std")]
+pub use resize::{on_resize, resize_events};
+
 #[macro_use]
 mod macros;
+#[doc(hidden)]
+pub use std::fmt as __fmt;
+#[cfg(feature = "std")]
+mod reply;
+pub mod bell;
+pub mod box_drawing;
+#[cfg(feature = "std")]
+pub mod buffer;
+pub mod charset;
 pub mod clear;
 pub mod color;
+pub mod control;
 pub mod cursor;
+#[cfg(feature = "std")]
+pub mod debug;
+pub mod edit;
+#[cfg(feature = "std")]
+pub mod emulator;
+#[cfg(feature = "std")]
 pub mod event;
+#[cfg(all(feature = "std", unix))]
+pub mod event_loop;
+#[cfg(feature = "std")]
+pub mod frame;
+#[cfg(feature = "std")]
 pub mod input;
+#[cfg(feature = "std")]
+pub mod modes;
+#[cfg(feature = "std")]
+pub mod pager;
+pub mod pointer;
+#[cfg(feature = "std")]
+pub mod progress;
+#[cfg(all(feature = "std", unix))]
+pub mod prompt;
+#[cfg(feature = "std")]
+pub mod pty;
+#[cfg(feature = "std")]
 pub mod raw;
+pub mod rect;
+#[cfg(feature = "std")]
+pub mod remote;
 pub mod screen;
 pub mod scroll;
+#[cfg(feature = "std")]
+pub mod shared;
+pub mod shell;
+#[cfg(feature = "std")]
+pub mod spinner;
+#[cfg(feature = "std")]
+pub mod stream;
 pub mod style;
+#[cfg(feature = "std")]
+pub mod styled;
+pub mod tab;
+#[cfg(feature = "std")]
+pub mod table;
+#[cfg(feature = "std")]
+pub mod terminal;
+#[cfg(feature = "std")]
+pub mod textarea;
+#[cfg(feature = "std")]
+pub mod theme;
+pub mod title;
+pub mod width;
+#[cfg(all(feature = "std", target_arch = "wasm32", target_os = "unknown"))]
+pub mod wasm;
 
-#[cfg(test)]
+#[cfg(all(test, feature = "std", unix))]
 mod test {
     use super::sys;
 
     #[test]
     fn test_get_terminal_attr() {
-        sys::attr::get_terminal_attr().unwrap();
-        sys::attr::get_terminal_attr().unwrap();
-        sys::attr::get_terminal_attr().unwrap();
+        sys::attr::get_terminal_attr_fd(0).unwrap();
+        sys::attr::get_terminal_attr_fd(0).unwrap();
+        sys::attr::get_terminal_attr_fd(0).unwrap();
     }
 
     #[test]
     fn test_set_terminal_attr() {
-        let ios = sys::attr::get_terminal_attr().unwrap();
-        sys::attr::set_terminal_attr(&ios).unwrap();
+        let ios = sys::attr::get_terminal_attr_fd(0).unwrap();
+        sys::attr::set_terminal_attr_fd(0, &ios).unwrap();
     }
 
     #[test]
@@ -1,19 +1,137 @@
+//! The macros building escape sequences, which other crates can use to define their own.
+
 /// Create a CSI-introduced sequence.
+///
+/// The arguments are concatenated after `ESC [`, so the sequence can be used as a format string:
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate termion;
+///
+/// use std::fmt;
+///
+/// /// Scroll the content of the screen left by some columns (SL).
+/// struct ScrollLeft(u16);
+///
+/// impl fmt::Display for ScrollLeft {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, csi!("{} @"), self.0)
+///     }
+/// }
+///
+/// fn main() {
+///     assert_eq!(ScrollLeft(4).to_string(), "\x1B[4 @");
+///     assert_eq!(csi!("?", "25", "l"), "\x1B[?25l");
+/// }
+/// ```
+#[macro_export]
 macro_rules! csi {
     ($( $l:expr ),*) => { concat!("\x1B[", $( $l ),*) };
 }
 
 /// Derive a CSI sequence struct.
+///
+/// This defines the unit struct `$name`, documented by `$doc`, which displays as
+/// `ESC [ $value`:
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate termion;
+///
+/// derive_csi_sequence!("Enable the synchronized output of the screen.", BeginSync, "?2026h");
+///
+/// fn main() {
+///     assert_eq!(BeginSync.to_string(), "\x1B[?2026h");
+/// }
+/// ```
+#[macro_export]
 macro_rules! derive_csi_sequence {
     ($doc:expr, $name:ident, $value:expr) => {
         #[doc = $doc]
         #[derive(Copy, Clone)]
         pub struct $name;
 
-        impl fmt::Display for $name {
-            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-                write!(f, csi!($value))
+        impl $crate::__fmt::Display for $name {
+            fn fmt(&self, f: &mut $crate::__fmt::Formatter) -> $crate::__fmt::Result {
+                write!(f, $crate::csi!($value))
             }
         }
     };
 }
+
+/// Create an ESC-introduced sequence.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate termion;
+///
+/// fn main() {
+///     assert_eq!(esc!("#8"), "\x1B#8");
+/// }
+/// ```
+#[macro_export]
+macro_rules! esc {
+    ($( $l:expr ),*) => { concat!("\x1B", $( $l ),*) };
+}
+
+/// Derive an ESC sequence struct.
+///
+/// Like `derive_csi_sequence`, for a struct which displays as `ESC $value`.
+#[macro_export]
+macro_rules! derive_esc_sequence {
+    ($doc:expr, $name:ident, $value:expr) => {
+        #[doc = $doc]
+        #[derive(Copy, Clone)]
+        pub struct $name;
+
+        impl $crate::__fmt::Display for $name {
+            fn fmt(&self, f: &mut $crate::__fmt::Formatter) -> $crate::__fmt::Result {
+                write!(f, $crate::esc!($value))
+            }
+        }
+    };
+}
+
+/// Create an OSC sequence: the arguments between `ESC ]` and `BEL`.
+///
+/// Vendor-specific settings are often OSC sequences:
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate termion;
+///
+/// use std::fmt;
+///
+/// /// Tell the terminal the current directory (OSC 7).
+/// struct CurrentDir<'a>(&'a str);
+///
+/// impl<'a> fmt::Display for CurrentDir<'a> {
+///     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+///         write!(f, osc!("7;file://{}"), self.0)
+///     }
+/// }
+///
+/// fn main() {
+///     assert_eq!(CurrentDir("/tmp").to_string(), "\x1B]7;file:///tmp\x07");
+/// }
+/// ```
+#[macro_export]
+macro_rules! osc {
+    ($( $l:expr ),*) => { concat!("\x1B]", $( $l, )* "\x07") };
+}
+
+/// Create a DCS sequence: the arguments between `ESC P` and the string terminator `ESC \`.
+///
+/// ```rust
+/// #[macro_use]
+/// extern crate termion;
+///
+/// fn main() {
+///     // Ask for the scrolling margins (DECRQSS).
+///     assert_eq!(dcs!("$qr"), "\x1BP$qr\x1B\\");
+/// }
+/// ```
+#[macro_export]
+macro_rules! dcs {
+    ($( $l:expr ),*) => { concat!("\x1BP", $( $l, )* "\x1B\\") };
+}
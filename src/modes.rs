@@ -0,0 +1,226 @@
+//! Turning several modes of the terminal on at once, and off again.
+//!
+//! Wrappers such as `MouseTerminal` or `AlternateScreen` each turn one mode off when dropped, so
+//! an application using several of them nests the wrappers, and has to get their order right for
+//! the modes to be turned off in the right order. A `TerminalModes` keeps track of the modes it
+//! turned on instead, and turns off exactly those when dropped, the latest first.
+//!
+//! As with the wrappers, `raw::restore_on_exit` makes them turned off when the process exits too.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::modes::{Mode, TerminalModes};
+//! use termion::raw::IntoRawMode;
+//! use std::io::{stdout, Write};
+//!
+//! let mut term = TerminalModes::from(stdout().into_raw_mode().unwrap());
+//! term.enable(Mode::AlternateScreen).unwrap();
+//! term.enable(Mode::Mouse).unwrap();
+//! term.enable(Mode::BracketedPaste).unwrap();
+//! write!(term, "Click anywhere.").unwrap();
+//! term.flush().unwrap();
+//! // Bracketed paste and the mouse are turned off, and the main screen is back, once `term` is
+//! // dropped.
+//! ```
+
+use std::io::{self, Write};
+use std::ops;
+
+use input::{ENTER_BRACKETED_PASTE_SEQUENCE, ENTER_FOCUS_EVENTS_SEQUENCE, ENTER_KEYPAD_SEQUENCE,
+            ENTER_MODIFY_OTHER_KEYS_SEQUENCE, ENTER_MOUSE_SEQUENCE, ENTER_MOUSE_URXVT_SEQUENCE,
+            EXIT_BRACKETED_PASTE_SEQUENCE, EXIT_FOCUS_EVENTS_SEQUENCE, EXIT_KEYPAD_SEQUENCE,
+            EXIT_MODIFY_OTHER_KEYS_SEQUENCE, EXIT_MOUSE_SEQUENCE, EXIT_MOUSE_URXVT_SEQUENCE};
+use raw::ExitGuard;
+use screen::{ENTER_ALTERNATE_SCREEN_SEQUENCE, EXIT_ALTERNATE_SCREEN_SEQUENCE};
+use terminal::Quirks;
+
+/// A mode of the terminal which can be turned on and off.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Mode {
+    /// Mouse reports, as with `input::MouseTerminal`.
+    Mouse,
+    /// Bracketed paste, as with `input::BracketedPasteTerminal`.
+    BracketedPaste,
+    /// Reports of the terminal gaining and losing the focus (`?1004`), read as
+    /// `Event::FocusGained` and `Event::FocusLost`.
+    FocusEvents,
+    /// The alternate screen, as with `screen::AlternateScreen`.
+    AlternateScreen,
+    /// The application keypad mode, as with `input::KeypadTerminal`.
+    ApplicationKeypad,
+    /// xterm's `modifyOtherKeys`, as with `input::ModifyOtherKeysTerminal`.
+    ModifyOtherKeys,
+}
+
+impl Mode {
+    /// The sequences turning the mode on and off, or `None` if it is left off on this terminal.
+    fn sequences(&self, quirks: &Quirks) -> Option<(&'static str, &'static str)> {
+        match *self {
+            Mode::Mouse if quirks.no_sgr_mouse => {
+                Some((ENTER_MOUSE_URXVT_SEQUENCE, EXIT_MOUSE_URXVT_SEQUENCE))
+            }
+            Mode::Mouse => Some((ENTER_MOUSE_SEQUENCE, EXIT_MOUSE_SEQUENCE)),
+            Mode::BracketedPaste if quirks.broken_bracketed_paste => None,
+            Mode::BracketedPaste => {
+                Some((ENTER_BRACKETED_PASTE_SEQUENCE, EXIT_BRACKETED_PASTE_SEQUENCE))
+            }
+            Mode::FocusEvents => Some((ENTER_FOCUS_EVENTS_SEQUENCE, EXIT_FOCUS_EVENTS_SEQUENCE)),
+            Mode::AlternateScreen => {
+                Some((ENTER_ALTERNATE_SCREEN_SEQUENCE, EXIT_ALTERNATE_SCREEN_SEQUENCE))
+            }
+            Mode::ApplicationKeypad => Some((ENTER_KEYPAD_SEQUENCE, EXIT_KEYPAD_SEQUENCE)),
+            Mode::ModifyOtherKeys => {
+                Some((ENTER_MODIFY_OTHER_KEYS_SEQUENCE, EXIT_MODIFY_OTHER_KEYS_SEQUENCE))
+            }
+        }
+    }
+}
+
+/// A mode turned on, with the sequence turning it off.
+struct Enabled {
+    mode: Mode,
+    exit: &'static str,
+    on_exit: Option<ExitGuard>,
+}
+
+/// A terminal restorer, which turns off the modes turned on through it when dropped.
+///
+/// This can be obtained through the `From` implementations. The sequences are chosen from those
+/// the terminal is known to support (see `terminal::Quirks`), as the wrappers do.
+pub struct TerminalModes<W: Write> {
+    output: W,
+    quirks: Quirks,
+    enabled: Vec<Enabled>,
+}
+
+impl<W: Write> TerminalModes<W> {
+    /// Turn `mode` on, until it is turned off or this terminal is dropped.
+    ///
+    /// A mode already on is left as it is, and so is bracketed paste on the terminals known to
+    /// break it.
+    pub fn enable(&mut self, mode: Mode) -> io::Result<()> {
+        if self.is_enabled(mode) {
+            return Ok(());
+        }
+        if let Some((enter, exit)) = mode.sequences(&self.quirks) {
+            self.output.write_all(enter.as_bytes())?;
+            self.enabled.push(Enabled { mode, exit, on_exit: ExitGuard::write(exit) });
+        }
+        self.output.flush()
+    }
+
+    /// Turn `mode` off, if it was turned on through this terminal.
+    pub fn disable(&mut self, mode: Mode) -> io::Result<()> {
+        if let Some(i) = self.enabled.iter().position(|e| e.mode == mode) {
+            let mut enabled = self.enabled.remove(i);
+            enabled.on_exit.take();
+            self.output.write_all(enabled.exit.as_bytes())?;
+        }
+        self.output.flush()
+    }
+
+    /// Was `mode` turned on through this terminal?
+    pub fn is_enabled(&self, mode: Mode) -> bool {
+        self.enabled.iter().any(|e| e.mode == mode)
+    }
+}
+
+impl<W: Write> From<W> for TerminalModes<W> {
+    fn from(output: W) -> TerminalModes<W> {
+        TerminalModes {
+            output,
+            quirks: Quirks::from_env(),
+            enabled: Vec::new(),
+        }
+    }
+}
+
+impl<W: Write> Drop for TerminalModes<W> {
+    fn drop(&mut self) {
+        while let Some(mut enabled) = self.enabled.pop() {
+            enabled.on_exit.take();
+            self.output.write_all(enabled.exit.as_bytes()).expect("restore terminal mode");
+        }
+        self.output.flush().expect("restore terminal mode");
+    }
+}
+
+impl<W: Write> ops::Deref for TerminalModes<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+impl<W: Write> ops::DerefMut for TerminalModes<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+impl<W: Write> Write for TerminalModes<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn modes(output: &mut Vec<u8>, quirks: Quirks) -> TerminalModes<&mut Vec<u8>> {
+        TerminalModes {
+            output,
+            quirks,
+            enabled: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_restore_in_order() {
+        let mut output = Vec::new();
+        {
+            let mut term = modes(&mut output, Quirks::default());
+            term.enable(Mode::AlternateScreen).unwrap();
+            term.enable(Mode::FocusEvents).unwrap();
+            term.enable(Mode::ApplicationKeypad).unwrap();
+            term.enable(Mode::FocusEvents).unwrap();
+            term.disable(Mode::FocusEvents).unwrap();
+            term.disable(Mode::Mouse).unwrap();
+            term.enable(Mode::BracketedPaste).unwrap();
+            assert!(term.is_enabled(Mode::ApplicationKeypad));
+            assert!(!term.is_enabled(Mode::FocusEvents));
+        }
+        assert_eq!(output,
+                   &b"\x1B[?1049h\x1B[?1004h\x1B=\x1B[?1004l\x1B[?2004h\
+                      \x1B[?2004l\x1B>\x1B[?1049l"[..]);
+    }
+
+    #[test]
+    fn test_quirks() {
+        let mut output = Vec::new();
+        let quirks = Quirks {
+            no_sgr_mouse: true,
+            broken_bracketed_paste: true,
+            ..Quirks::default()
+        };
+        {
+            let mut term = modes(&mut output, quirks);
+            term.enable(Mode::BracketedPaste).unwrap();
+            term.enable(Mode::Mouse).unwrap();
+            assert!(!term.is_enabled(Mode::BracketedPaste));
+        }
+        assert_eq!(output,
+                   &b"\x1B[?1000h\x1B[?1002h\x1B[?1015h\x1B[?1015l\x1B[?1002l\x1B[?1000l"[..]);
+    }
+}
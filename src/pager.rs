@@ -0,0 +1,533 @@
+//! A pager, to read long text one screen at a time.
+//!
+//! `Pager` shows lines of styled text on the alternate screen, and lets the user move through
+//! them with the keys of `less`:
+//!
+//! - `↑`, `k` or `Ctrl-P` and `↓`, `j`, `Enter` or `Ctrl-N` scroll by a line.
+//! - `PgUp`, `b` or `Ctrl-B` and `PgDn`, `Space`, `f` or `Ctrl-F` scroll by a page, and `u` or
+//!   `Ctrl-U` and `d` or `Ctrl-D` by half a page.
+//! - `Home` or `g` and `End` or `G` go to the first and last lines.
+//! - `←` and `→` scroll sideways, by half a screen.
+//! - `/` searches for some text, and `n` and `N` go to the next and previous lines holding it.
+//! - `F` follows the end of the text as it grows, until another key is pressed.
+//! - `q`, `Esc` or `Ctrl-C` quit.
+//!
+//! Text read from a `Read` may hold SGR sequences setting its colors and attributes, as the
+//! output of commands such as `grep --color=always` does. Other escape and control sequences
+//! are dropped, and tabs are expanded.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::pager::Pager;
+//! use std::fs::File;
+//!
+//! let mut pager = Pager::from_reader(File::open("Cargo.toml").unwrap()).unwrap();
+//! pager.run().unwrap();
+//! ```
+
+use std::io::{self, Read};
+#[cfg(unix)]
+use std::io::Write;
+use std::mem;
+#[cfg(unix)]
+use std::sync::mpsc::{self, TryRecvError};
+#[cfg(unix)]
+use std::thread;
+#[cfg(unix)]
+use std::time::Duration;
+
+use ansi::{Csi, Splitter, Token, Utf8Decoder};
+use buffer::{Buffer, CellStyle};
+#[cfg(unix)]
+use cursor;
+#[cfg(unix)]
+use event::Event;
+use event::Key;
+#[cfg(unix)]
+use event_loop::{EventLoop, LoopEvent};
+#[cfg(unix)]
+use raw::IntoRawMode;
+#[cfg(unix)]
+use screen::AlternateScreen;
+use styled::StyledStr;
+#[cfg(unix)]
+use terminal_size;
+
+/// How often the followed text is checked, while no key is pressed.
+#[cfg(unix)]
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Turns text with SGR sequences into styled lines.
+struct LineReader {
+    splitter: Splitter,
+    decoder: Utf8Decoder,
+    style: CellStyle,
+    line: StyledStr,
+}
+
+impl LineReader {
+    fn new() -> LineReader {
+        LineReader {
+            splitter: Splitter::new(),
+            decoder: Utf8Decoder::new(),
+            style: CellStyle::default(),
+            line: StyledStr::new(),
+        }
+    }
+
+    /// Read `bytes`, adding the lines they complete to `lines`.
+    fn feed(&mut self, bytes: &[u8], lines: &mut Vec<StyledStr>) {
+        let LineReader { ref mut splitter, ref mut decoder, ref mut style, ref mut line } = *self;
+        let _ = splitter.split(bytes, |token| {
+            match token {
+                Token::Text(text) => {
+                    decoder.decode(text, |c| match c {
+                        '\n' => lines.push(mem::take(line)),
+                        '\t' => line.push_str(&" ".repeat(8 - line.width() % 8), *style),
+                        c if c.is_control() => {}
+                        c => line.push_str(c.encode_utf8(&mut [0; 4]), *style),
+                    })
+                }
+                Token::Sequence(seq) => {
+                    let sgr = Csi::parse(seq).is_some_and(|csi| {
+                        csi.private.is_none() && csi.intermediates.is_empty() &&
+                        csi.final_byte == b'm'
+                    });
+                    if sgr {
+                        style.apply_sgr(&seq[2..seq.len() - 1]);
+                    }
+                }
+            }
+            Ok(())
+        });
+    }
+
+    /// End the text, adding the last line to `lines` if it doesn't end with a newline.
+    fn finish(&mut self, lines: &mut Vec<StyledStr>) {
+        if !self.line.is_empty() {
+            lines.push(mem::take(&mut self.line));
+        }
+    }
+}
+
+/// A pager showing lines of styled text, one screen at a time.
+///
+/// The pager can be driven by hand, through `handle_key` and `render`, or left to run on the
+/// terminal with `run` (on Unix).
+pub struct Pager {
+    lines: Vec<StyledStr>,
+    reader: LineReader,
+    top: usize,
+    left: usize,
+    width: u16,
+    height: u16,
+    follow: bool,
+    search: Option<String>,
+    last_match: Option<usize>,
+    prompt: Option<String>,
+    status: Option<String>,
+}
+
+impl Pager {
+    /// A pager showing `lines`.
+    pub fn new(lines: Vec<StyledStr>) -> Pager {
+        Pager {
+            lines,
+            reader: LineReader::new(),
+            top: 0,
+            left: 0,
+            width: 80,
+            height: 24,
+            follow: false,
+            search: None,
+            last_match: None,
+            prompt: None,
+            status: None,
+        }
+    }
+
+    /// A pager showing the text read from `reader`, up to its end.
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<Pager> {
+        let mut text = Vec::new();
+        reader.read_to_end(&mut text)?;
+        let mut pager = Pager::new(Vec::new());
+        pager.feed(&text);
+        pager.finish();
+        Ok(pager)
+    }
+
+    /// The lines shown.
+    pub fn lines(&self) -> &[StyledStr] {
+        &self.lines
+    }
+
+    /// Add `line` after the others.
+    pub fn push_line(&mut self, line: StyledStr) {
+        self.lines.push(line);
+        self.follow_end();
+    }
+
+    /// Add the lines of the text `bytes`, which may hold SGR sequences.
+    ///
+    /// A line is only added once its newline is fed, or `finish` is called.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.reader.feed(bytes, &mut self.lines);
+        self.follow_end();
+    }
+
+    /// Add the last line fed, if it has no newline.
+    pub fn finish(&mut self) {
+        self.reader.finish(&mut self.lines);
+        self.follow_end();
+    }
+
+    /// The index of the first line on the screen.
+    pub fn top(&self) -> usize {
+        self.top
+    }
+
+    /// Is the pager following the end of the text?
+    pub fn is_following(&self) -> bool {
+        self.follow
+    }
+
+    /// Start or stop following the end of the text as it grows.
+    pub fn set_following(&mut self, follow: bool) {
+        self.follow = follow;
+        self.follow_end();
+    }
+
+    /// Show the lines on a screen of `width` columns and `height` rows, the last of which is
+    /// the status line.
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.width = width;
+        self.height = height;
+        self.top = self.top.min(self.max_top());
+        self.follow_end();
+    }
+
+    /// Handle `key`, returning `false` if it quits the pager.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        self.status = None;
+        if let Some(mut prompt) = self.prompt.take() {
+            match key {
+                Key::Char('\n') => {
+                    if !prompt.is_empty() {
+                        self.search = Some(prompt);
+                        self.last_match = None;
+                    }
+                    self.find(true);
+                }
+                Key::Esc | Key::Ctrl('c') => {}
+                Key::Backspace => {
+                    prompt.pop();
+                    self.prompt = Some(prompt);
+                }
+                Key::Char(c) => {
+                    prompt.push(c);
+                    self.prompt = Some(prompt);
+                }
+                _ => self.prompt = Some(prompt),
+            }
+            return true;
+        }
+
+        let page = self.page_height() as isize;
+        self.follow = false;
+        match key {
+            Key::Char('q') | Key::Esc | Key::Ctrl('c') => return false,
+            Key::Up | Key::Char('k') | Key::Ctrl('p') => self.scroll(-1),
+            Key::Down | Key::Char('j') | Key::Char('\n') | Key::Ctrl('n') => self.scroll(1),
+            Key::PageUp | Key::Char('b') | Key::Ctrl('b') => self.scroll(-page),
+            Key::PageDown | Key::Char(' ') | Key::Char('f') | Key::Ctrl('f') => self.scroll(page),
+            Key::Char('u') | Key::Ctrl('u') => self.scroll(-page / 2),
+            Key::Char('d') | Key::Ctrl('d') => self.scroll(page / 2),
+            Key::Home | Key::Char('g') => self.top = 0,
+            Key::End | Key::Char('G') => self.top = self.max_top(),
+            Key::Left => self.left = self.left.saturating_sub(self.width as usize / 2),
+            Key::Right => self.left += self.width as usize / 2,
+            Key::Char('/') => self.prompt = Some(String::new()),
+            Key::Char('n') => self.find(true),
+            Key::Char('N') => self.find(false),
+            Key::Char('F') => self.set_following(true),
+            _ => {}
+        }
+        true
+    }
+
+    /// Draw the screen into `buffer`, which should be as large as the pager.
+    pub fn render(&self, buffer: &mut Buffer) {
+        buffer.clear();
+        let end = self.left + self.width as usize;
+        let page = self.lines.iter().skip(self.top).take(self.page_height());
+        for (y, line) in (1..).zip(page) {
+            let shown = match self.search {
+                Some(ref pattern) => highlight(line, pattern).slice(self.left, end),
+                None => line.slice(self.left, end),
+            };
+            buffer.set_styled(1, y, &shown);
+        }
+
+        let status = if let Some(ref prompt) = self.prompt {
+            format!("/{}", prompt)
+        } else if let Some(ref status) = self.status {
+            status.clone()
+        } else {
+            let last = (self.top + self.page_height()).min(self.lines.len());
+            format!("lines {}-{} of {}{}",
+                    (self.top + 1).min(last),
+                    last,
+                    self.lines.len(),
+                    if self.follow { " (following)" } else { "" })
+        };
+        let style = CellStyle { invert: true, ..CellStyle::default() };
+        buffer.set_str(1, self.height, &status, style);
+    }
+
+    /// Run the pager on the terminal, until the user quits it.
+    ///
+    /// The keys are read from the TTY device, and only until the key quitting the pager.
+    #[cfg(unix)]
+    pub fn run(&mut self) -> io::Result<()> {
+        self.run_with(None)
+    }
+
+    /// Run the pager on the terminal, adding the text read from `source` as it comes, and
+    /// following it.
+    #[cfg(unix)]
+    pub fn run_following<R: Read + Send + 'static>(&mut self, mut source: R) -> io::Result<()> {
+        let (send, recv) = mpsc::channel();
+        thread::spawn(move || {
+            let mut buf = [0; 4096];
+            while let Ok(n @ 1..) = source.read(&mut buf) {
+                if send.send(buf[..n].to_vec()).is_err() {
+                    return;
+                }
+            }
+        });
+
+        self.set_following(true);
+        self.run_with(Some(recv))
+    }
+
+    #[cfg(unix)]
+    fn run_with(&mut self, source: Option<mpsc::Receiver<Vec<u8>>>) -> io::Result<()> {
+        let mut screen = AlternateScreen::from(io::stdout().into_raw_mode()?);
+        write!(screen, "{}", cursor::Hide)?;
+        let result = EventLoop::new().and_then(|mut events| {
+            let size = terminal_size()?;
+            self.event_loop(&mut screen, &mut events, size, source)
+        });
+        write!(screen, "{}", cursor::Show)?;
+        screen.flush()?;
+        result
+    }
+
+    #[cfg(unix)]
+    fn event_loop<W: Write>(&mut self,
+                            screen: &mut W,
+                            events: &mut EventLoop,
+                            (width, height): (u16, u16),
+                            mut source: Option<mpsc::Receiver<Vec<u8>>>)
+                            -> io::Result<()> {
+        let mut buffer = Buffer::new(width, height);
+        self.resize(width, height);
+
+        loop {
+            while let Some(ref recv) = source {
+                match recv.try_recv() {
+                    Ok(bytes) => self.feed(&bytes),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.finish();
+                        source = None;
+                    }
+                }
+            }
+
+            self.render(&mut buffer);
+            buffer.flush(screen)?;
+
+            let timeout = source.as_ref().map(|_| POLL_INTERVAL);
+            match events.next_timeout(timeout)? {
+                Some(LoopEvent::Input(Event::Key(key))) if !self.handle_key(key) => return Ok(()),
+                Some(LoopEvent::Resize(width, height)) => {
+                    buffer.resize(width, height);
+                    self.resize(width, height);
+                }
+                _ => (),
+            }
+        }
+    }
+
+    /// The number of rows showing lines, above the status line.
+    fn page_height(&self) -> usize {
+        (self.height as usize).saturating_sub(1).max(1)
+    }
+
+    /// The largest index of the first line on the screen, showing the last page.
+    fn max_top(&self) -> usize {
+        self.lines.len().saturating_sub(self.page_height())
+    }
+
+    fn follow_end(&mut self) {
+        if self.follow {
+            self.top = self.max_top();
+        }
+    }
+
+    fn scroll(&mut self, delta: isize) {
+        self.top = (self.top as isize + delta).clamp(0, self.max_top() as isize) as usize;
+    }
+
+    /// Go to the next line holding the search text, or the previous one if `forward` is false.
+    fn find(&mut self, forward: bool) {
+        let pattern = match self.search {
+            Some(ref pattern) => pattern.clone(),
+            None => return,
+        };
+        let holds = |line: &StyledStr| line.unstyled().contains(&pattern[..]);
+        let found = match (self.last_match, forward) {
+            (None, _) => (self.top..self.lines.len()).find(|&i| holds(&self.lines[i])),
+            (Some(last), true) => (last + 1..self.lines.len()).find(|&i| holds(&self.lines[i])),
+            (Some(last), false) => (0..last).rev().find(|&i| holds(&self.lines[i])),
+        };
+        match found {
+            Some(i) => {
+                self.last_match = Some(i);
+                self.top = i.min(self.max_top());
+            }
+            None => self.status = Some(format!("Pattern not found: {}", pattern)),
+        }
+    }
+}
+
+/// `line`, with the text matching `pattern` in inverted colors.
+fn highlight(line: &StyledStr, pattern: &str) -> StyledStr {
+    let text = line.unstyled();
+    let matches: Vec<(usize, usize)> = if pattern.is_empty() {
+        Vec::new()
+    } else {
+        text.match_indices(pattern).map(|(i, m)| (i, i + m.len())).collect()
+    };
+    if matches.is_empty() {
+        return line.clone();
+    }
+
+    let mut highlighted = StyledStr::new();
+    let mut offset = 0;
+    for span in line.spans() {
+        for (i, c) in span.text().char_indices() {
+            let at = offset + i;
+            let mut style = span.style();
+            if matches.iter().any(|&(start, end)| start <= at && at < end) {
+                style.invert = !style.invert;
+            }
+            highlighted.push_str(c.encode_utf8(&mut [0; 4]), style);
+        }
+        offset += span.text().len();
+    }
+    highlighted
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use buffer::CellColor;
+    #[cfg(unix)]
+    use sys::poll::pipe;
+
+    fn pager(lines: usize, height: u16) -> Pager {
+        let text: String = (0..lines).map(|i| format!("line {}\n", i)).collect();
+        let mut pager = Pager::from_reader(text.as_bytes()).unwrap();
+        pager.resize(20, height);
+        pager
+    }
+
+    #[test]
+    fn test_read_styled() {
+        let pager = Pager::from_reader(&b"a\x1B[31mb\x1B[2Kc\x1B[0m\td\r\nlast"[..]).unwrap();
+        let red = CellStyle { fg: CellColor::Ansi(1), ..CellStyle::default() };
+        assert_eq!(pager.lines(),
+                   &[StyledStr::from("a") + StyledStr::styled("bc", red) + "     d",
+                     StyledStr::from("last")]);
+    }
+
+    #[test]
+    fn test_navigation() {
+        let mut pager = pager(10, 4);
+        pager.handle_key(Key::Down);
+        assert_eq!(pager.top(), 1);
+        pager.handle_key(Key::PageDown);
+        assert_eq!(pager.top(), 4);
+        pager.handle_key(Key::Char(' '));
+        assert_eq!(pager.top(), 7);
+        pager.handle_key(Key::Char('u'));
+        assert_eq!(pager.top(), 6);
+        pager.handle_key(Key::Home);
+        assert_eq!(pager.top(), 0);
+        pager.handle_key(Key::Char('G'));
+        assert_eq!(pager.top(), 7);
+        assert!(pager.handle_key(Key::Up));
+        assert!(!pager.handle_key(Key::Char('q')));
+    }
+
+    #[test]
+    fn test_search() {
+        let mut pager = pager(10, 4);
+        for key in "/line 2\n".chars() {
+            pager.handle_key(Key::Char(key));
+        }
+        assert_eq!(pager.top(), 2);
+        pager.handle_key(Key::Char('/'));
+        pager.handle_key(Key::Char('9'));
+        pager.handle_key(Key::Char('\n'));
+        assert_eq!(pager.top(), 7);
+        pager.handle_key(Key::Char('n'));
+        let mut buffer = Buffer::new(20, 4);
+        pager.render(&mut buffer);
+        assert_eq!(buffer.get(1, 4).unwrap().symbol, "P");
+        assert!(buffer.get(6, 3).unwrap().style.invert);
+        assert!(!buffer.get(5, 3).unwrap().style.invert);
+
+        pager.handle_key(Key::Char('N'));
+        assert_eq!(pager.top(), 7);
+    }
+
+    #[test]
+    fn test_follow() {
+        let mut pager = pager(2, 4);
+        pager.handle_key(Key::Char('F'));
+        assert!(pager.is_following());
+        pager.feed(b"more\nand more\nstill more\nlast");
+        assert_eq!(pager.top(), 2);
+        let mut buffer = Buffer::new(20, 4);
+        pager.render(&mut buffer);
+        assert_eq!(buffer.get(1, 3).unwrap().symbol, "s");
+        pager.handle_key(Key::Up);
+        assert!(!pager.is_following());
+        pager.finish();
+        assert_eq!(pager.top(), 1);
+        assert_eq!(pager.lines().len(), 6);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_event_loop() {
+        let (input, mut typed) = pipe().unwrap();
+        let mut tty = input.try_clone().unwrap();
+        let mut events = EventLoop::with_input(input).unwrap();
+        let mut pager = pager(10, 4);
+        typed.write_all(b"jq").unwrap();
+        pager.event_loop(&mut Vec::new(), &mut events, (20, 4), None).unwrap();
+        assert_eq!(pager.top(), 1);
+
+        // Nothing is left reading the input once the pager quit.
+        drop(events);
+        typed.write_all(b"x").unwrap();
+        let mut buf = [0; 2];
+        assert_eq!(tty.read(&mut buf).unwrap(), 1);
+        assert_eq!(buf[0], b'x');
+    }
+}
@@ -0,0 +1,194 @@
+//! Setting the shape of the mouse pointer (OSC 22).
+//!
+//! kitty, foot, WezTerm and recent xterm versions change the mouse pointer shown over the
+//! terminal window on request, e.g. to a hand over something clickable. The shapes are named as
+//! in CSS; other terminals ignore the sequence. `PointerTerminal` restores the default pointer
+//! when dropped.
+//!
+//! # Example
+//!
+#![cfg_attr(feature = "std", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "std"), doc = "```rust,ignore")]
+//! use termion::pointer::{PointerShape, PointerTerminal};
+//! use std::io::stdout;
+//!
+//! let mut stdout = PointerTerminal::from(stdout());
+//! // The mouse hovers a link.
+//! stdout.set_pointer_shape(PointerShape::Pointer).unwrap();
+//! ```
+
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::ops;
+use std::fmt;
+
+/// A shape of the mouse pointer.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PointerShape {
+    /// The default pointer of the terminal, usually an I-beam.
+    #[default]
+    Default,
+    /// An I-beam, over text which can be selected or edited.
+    Text,
+    /// A pointing hand, over a link or a button.
+    Pointer,
+    /// A crosshair.
+    Crosshair,
+    /// Something is going on, and the user has to wait.
+    Wait,
+    /// Something is going on, but the user can still interact.
+    Progress,
+    /// Help is available.
+    Help,
+    /// Something is to be moved.
+    Move,
+    /// The action isn't allowed here.
+    NotAllowed,
+    /// Something can be grabbed.
+    Grab,
+    /// Something is being grabbed.
+    Grabbing,
+    /// A column edge, to be dragged left or right.
+    ColumnResize,
+    /// A row edge, to be dragged up or down.
+    RowResize,
+    /// Something can be copied.
+    Copy,
+    /// Something can be zoomed in.
+    ZoomIn,
+    /// Something can be zoomed out.
+    ZoomOut,
+}
+
+impl PointerShape {
+    /// The CSS name of the shape.
+    pub fn name(&self) -> &'static str {
+        match *self {
+            PointerShape::Default => "default",
+            PointerShape::Text => "text",
+            PointerShape::Pointer => "pointer",
+            PointerShape::Crosshair => "crosshair",
+            PointerShape::Wait => "wait",
+            PointerShape::Progress => "progress",
+            PointerShape::Help => "help",
+            PointerShape::Move => "move",
+            PointerShape::NotAllowed => "not-allowed",
+            PointerShape::Grab => "grab",
+            PointerShape::Grabbing => "grabbing",
+            PointerShape::ColumnResize => "col-resize",
+            PointerShape::RowResize => "row-resize",
+            PointerShape::Copy => "copy",
+            PointerShape::ZoomIn => "zoom-in",
+            PointerShape::ZoomOut => "zoom-out",
+        }
+    }
+}
+
+/// Set the shape of the mouse pointer.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SetPointerShape(pub PointerShape);
+
+impl fmt::Display for SetPointerShape {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\x1B]22;{}\x07", self.0.name())
+    }
+}
+
+/// A terminal restorer, which restores the default mouse pointer when dropped.
+#[cfg(feature = "std")]
+pub struct PointerTerminal<W: Write> {
+    output: W,
+    /// The shape set last.
+    shape: PointerShape,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> PointerTerminal<W> {
+    /// Set the shape of the mouse pointer until this terminal is dropped.
+    ///
+    /// Nothing is written if the pointer already has this shape, so this can be called on every
+    /// mouse move.
+    pub fn set_pointer_shape(&mut self, shape: PointerShape) -> io::Result<()> {
+        if shape != self.shape {
+            write!(self.output, "{}", SetPointerShape(shape))?;
+            self.shape = shape;
+        }
+        self.output.flush()
+    }
+
+    /// The shape of the mouse pointer set last.
+    pub fn pointer_shape(&self) -> PointerShape {
+        self.shape
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> From<W> for PointerTerminal<W> {
+    fn from(output: W) -> PointerTerminal<W> {
+        PointerTerminal {
+            output,
+            shape: PointerShape::Default,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Drop for PointerTerminal<W> {
+    fn drop(&mut self) {
+        self.set_pointer_shape(PointerShape::Default).expect("restore mouse pointer");
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::Deref for PointerTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::DerefMut for PointerTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for PointerTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_pointer_terminal() {
+        let mut output = Vec::new();
+        {
+            let mut term = PointerTerminal::from(&mut output);
+            term.set_pointer_shape(PointerShape::Pointer).unwrap();
+            term.set_pointer_shape(PointerShape::Pointer).unwrap();
+            term.set_pointer_shape(PointerShape::NotAllowed).unwrap();
+        }
+        assert_eq!(output,
+                   b"\x1B]22;pointer\x07\x1B]22;not-allowed\x07\x1B]22;default\x07".to_vec());
+
+        let mut output = Vec::new();
+        drop(PointerTerminal::from(&mut output));
+        assert_eq!(output, b"");
+    }
+}
@@ -0,0 +1,245 @@
+//! A progress bar, redrawn in place.
+//!
+//! The bar takes the line the cursor is on, and is redrawn there whenever the progress changes.
+//! Other output goes through `println_above`, which prints a line where the bar was and draws
+//! the bar again below it, so that the two don't garble each other.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::progress::ProgressBar;
+//! use std::io::stdout;
+//!
+//! let mut bar = ProgressBar::new(stdout(), 3);
+//! for file in &["a.txt", "b.txt", "c.txt"] {
+//!     bar.println_above(&format!("copying {}", file)).unwrap();
+//!     bar.inc(1).unwrap();
+//! }
+//! bar.finish().unwrap();
+//! ```
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use clear;
+use width::{grapheme_width, graphemes, str_width};
+
+/// The template the bar is drawn from, unless another is set.
+pub const DEFAULT_TEMPLATE: &str = "[{bar}] {pos}/{total} ETA {eta}";
+
+/// A progress bar, drawn in place on the current line of `W`.
+pub struct ProgressBar<W: Write> {
+    output: W,
+    position: u64,
+    total: u64,
+    width: Option<u16>,
+    template: String,
+    message: String,
+    started: Instant,
+}
+
+impl<W: Write> ProgressBar<W> {
+    /// A bar counting up to `total`, drawn on `output`.
+    ///
+    /// Nothing is drawn before the progress changes, or `draw` is called.
+    pub fn new(output: W, total: u64) -> ProgressBar<W> {
+        ProgressBar {
+            output,
+            position: 0,
+            total,
+            width: None,
+            template: DEFAULT_TEMPLATE.to_string(),
+            message: String::new(),
+            started: Instant::now(),
+        }
+    }
+
+    /// Draw the line from `template` rather than `DEFAULT_TEMPLATE`.
+    ///
+    /// These placeholders are replaced:
+    ///
+    /// - `{bar}`: the bar itself, taking the columns the rest of the line leaves.
+    /// - `{pos}` and `{total}`: the progress, and what it counts up to.
+    /// - `{percent}`: the progress as a percentage.
+    /// - `{elapsed}`: the time since the bar was created.
+    /// - `{eta}`: the time left, estimated from the rate so far.
+    /// - `{msg}`: the message set by `set_message`.
+    pub fn set_template(&mut self, template: &str) {
+        self.template = template.to_string();
+    }
+
+    /// Draw the line `width` columns wide, rather than as wide as the terminal.
+    pub fn set_width(&mut self, width: u16) {
+        self.width = Some(width);
+    }
+
+    /// Set the message shown by `{msg}`, and redraw the bar.
+    pub fn set_message(&mut self, message: &str) -> io::Result<()> {
+        self.message = message.to_string();
+        self.draw()
+    }
+
+    /// The progress.
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// What the progress counts up to.
+    pub fn total(&self) -> u64 {
+        self.total
+    }
+
+    /// Set the progress, at most `total`, and redraw the bar.
+    pub fn set_position(&mut self, position: u64) -> io::Result<()> {
+        self.position = position.min(self.total);
+        self.draw()
+    }
+
+    /// Advance the progress by `delta`, and redraw the bar.
+    pub fn inc(&mut self, delta: u64) -> io::Result<()> {
+        let position = self.position.saturating_add(delta);
+        self.set_position(position)
+    }
+
+    /// Change what the progress counts up to, and redraw the bar.
+    pub fn set_total(&mut self, total: u64) -> io::Result<()> {
+        self.total = total;
+        self.position = self.position.min(total);
+        self.draw()
+    }
+
+    /// Draw the bar over the current line.
+    pub fn draw(&mut self) -> io::Result<()> {
+        let width = match self.width {
+            Some(width) => width,
+            None => ::terminal_size().map_or(80, |(width, _)| width),
+        };
+        let line = self.render(width, self.started.elapsed());
+        write!(self.output, "\r{}{}", line, clear::UntilNewline)?;
+        self.output.flush()
+    }
+
+    /// Print `line` where the bar is, and draw the bar again on the next line.
+    pub fn println_above(&mut self, line: &str) -> io::Result<()> {
+        write!(self.output, "\r{}{}\r\n", line, clear::UntilNewline)?;
+        self.draw()
+    }
+
+    /// Erase the bar, leaving the cursor at the start of its line.
+    pub fn clear(&mut self) -> io::Result<()> {
+        write!(self.output, "\r{}", clear::CurrentLine)?;
+        self.output.flush()
+    }
+
+    /// Complete the progress, and move past the bar, which is left on the screen.
+    pub fn finish(mut self) -> io::Result<W> {
+        self.position = self.total;
+        self.draw()?;
+        self.output.write_all(b"\r\n")?;
+        self.output.flush()?;
+        Ok(self.output)
+    }
+
+    /// The line of the bar, cut to `width` columns, `elapsed` after it was created.
+    fn render(&self, width: u16, elapsed: Duration) -> String {
+        let ratio = if self.total == 0 {
+            1.0
+        } else {
+            self.position as f64 / self.total as f64
+        };
+        let eta = if self.position == 0 {
+            "--:--".to_string()
+        } else {
+            let left = elapsed.as_secs_f64() * (self.total - self.position) as f64 /
+                       self.position as f64;
+            format_duration(Duration::from_secs_f64(left))
+        };
+
+        let line = self.template
+            .replace("{pos}", &self.position.to_string())
+            .replace("{total}", &self.total.to_string())
+            .replace("{percent}", &((ratio * 100.0) as u64).to_string())
+            .replace("{elapsed}", &format_duration(elapsed))
+            .replace("{eta}", &eta)
+            .replace("{msg}", &self.message);
+        let bars = line.matches("{bar}").count();
+        // The bars share the columns the rest of the line leaves.
+        let left = (width as usize).saturating_sub(str_width(&line) - 5 * bars);
+        let line = match left.checked_div(bars) {
+            Some(left) => line.replace("{bar}", &bar(ratio, left)),
+            None => line,
+        };
+
+        truncate(&line, width as usize)
+    }
+}
+
+/// A bar `width` columns wide, filled to `ratio`.
+fn bar(ratio: f64, width: usize) -> String {
+    let filled = ((ratio * width as f64) as usize).min(width);
+    let mut bar = "=".repeat(filled);
+    if filled < width {
+        bar.push(if filled > 0 { '>' } else { ' ' });
+        bar.push_str(&" ".repeat(width - filled - 1));
+    }
+    bar
+}
+
+/// `duration` as minutes and seconds, with hours if there are any.
+fn format_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{:02}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// The first `width` columns of `s`.
+fn truncate(s: &str, width: usize) -> String {
+    let mut column = 0;
+    let mut truncated = String::new();
+    for grapheme in graphemes(s) {
+        column += grapheme_width(grapheme);
+        if column > width {
+            break;
+        }
+        truncated.push_str(grapheme);
+    }
+    truncated
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let mut bar = ProgressBar::new(Vec::new(), 4);
+        let elapsed = Duration::from_secs(30);
+        assert_eq!(bar.render(26, elapsed), "[          ] 0/4 ETA --:--");
+        bar.position = 1;
+        assert_eq!(bar.render(26, elapsed), "[==>       ] 1/4 ETA 01:30");
+
+        bar.set_template("{msg} {bar} {percent}% {elapsed}");
+        bar.message = "日本".to_string();
+        bar.position = 4;
+        assert_eq!(bar.render(24, Duration::from_secs(3725)), "日本 ====== 100% 1:02:05");
+        assert_eq!(bar.render(3, elapsed), "日");
+    }
+
+    #[test]
+    fn test_output() {
+        let mut bar = ProgressBar::new(Vec::new(), 2);
+        bar.set_width(10);
+        bar.set_template("{bar}");
+        bar.inc(1).unwrap();
+        bar.println_above("log").unwrap();
+        bar.inc(5).unwrap();
+        assert_eq!(bar.position(), 2);
+        let output = bar.finish().unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(),
+                   "\r=====>    \x1B[K\rlog\x1B[K\r\n\r=====>    \x1B[K\r==========\x1B[K\r\
+                    ==========\x1B[K\r\n");
+    }
+}
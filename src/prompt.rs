@@ -0,0 +1,284 @@
+//! Asking the user simple questions.
+//!
+//! `confirm` asks a yes or no question, `select` lets the user pick one of several options with
+//! the arrow keys, and `input` reads a line of text, which can be edited. They talk to the
+//! terminal itself rather than to the standard streams, so they work while the output of a
+//! program is piped, and put the terminal in raw mode only while they wait for an answer.
+//!
+//! Pressing `Ctrl-C` fails them with an `Interrupted` error, since raw mode keeps it from
+//! sending `SIGINT`.
+//!
+//! The keys typed ahead of a prompt are kept for the next one. The `_on` variants read the keys
+//! from any `Keys`, which is to be kept from a prompt to the next for the same reason, and write
+//! to any output, which must already be in raw mode if it is a terminal.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::prompt::{confirm, input, select};
+//!
+//! let name = input("Project name:", "hello").unwrap();
+//! let kind = select("Kind:", &["binary", "library"]).unwrap();
+//! if confirm(&format!("Create {}?", name)).unwrap() {
+//!     println!("Creating the {} {}.", ["binary", "library"][kind.unwrap_or(0)], name);
+//! }
+//! ```
+
+use std::fmt;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::sync::Mutex;
+
+use clear;
+use cursor;
+use event::Key;
+use get_tty;
+use input::{Keys, TermRead};
+use raw::{IntoRawMode, RawTerminal};
+use style;
+use width::str_width;
+
+/// The keys read from the terminal, kept from a prompt to the next.
+static TTY_KEYS: Mutex<Option<Keys<File>>> = Mutex::new(None);
+
+/// Run `f` with the keys read from the terminal, and an output to the terminal in raw mode.
+fn on_tty<T, F>(f: F) -> io::Result<T>
+    where F: FnOnce(&mut Keys<File>, &mut RawTerminal<File>) -> io::Result<T>
+{
+    let tty = get_tty()?;
+    let mut output = tty.try_clone()?.into_raw_mode_on(&tty)?;
+
+    let mut tty_keys = TTY_KEYS.lock().unwrap_or_else(|e| e.into_inner());
+    let mut keys = match tty_keys.take() {
+        Some(keys) => keys,
+        None => tty.keys(),
+    };
+    let result = f(&mut keys, &mut output);
+    *tty_keys = Some(keys);
+    result
+}
+
+/// The next key read from `keys`, failing if the input ends or `Ctrl-C` is pressed.
+fn next_key<I: Iterator<Item = io::Result<Key>>>(keys: &mut I) -> io::Result<Key> {
+    match keys.next() {
+        Some(Ok(Key::Ctrl('c'))) => Err(io::Error::new(io::ErrorKind::Interrupted, "Ctrl-C")),
+        Some(key) => key,
+        None => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "The input ended.")),
+    }
+}
+
+/// Ask the yes or no question `prompt` on the terminal.
+///
+/// `y` answers yes, and `n`, `Enter` or `Esc` answer no.
+pub fn confirm(prompt: &str) -> io::Result<bool> {
+    on_tty(|keys, output| confirm_on(keys, output, prompt))
+}
+
+/// Ask the yes or no question `prompt`, reading the answer from `keys`.
+pub fn confirm_on<R: Read, W: Write>(keys: &mut Keys<R>,
+                                     output: &mut W,
+                                     prompt: &str)
+                                     -> io::Result<bool> {
+    write!(output, "{} [y/N] ", prompt)?;
+    output.flush()?;
+
+    let answer = loop {
+        match next_key(keys)? {
+            Key::Char('y') | Key::Char('Y') => break true,
+            Key::Char('n') | Key::Char('N') | Key::Char('\n') | Key::Esc => break false,
+            _ => {}
+        }
+    };
+
+    write!(output, "{}\r\n", if answer { "yes" } else { "no" })?;
+    output.flush()?;
+    Ok(answer)
+}
+
+/// Let the user pick one of `options` on the terminal, returning its index, or `None` if the
+/// choice is cancelled.
+///
+/// The arrow keys, `j` and `k`, or `Tab` move between the options, `Enter` picks one, and `Esc`
+/// or `q` cancel the choice. The options are listed below the prompt, so there should be fewer
+/// of them than rows on the terminal.
+pub fn select<T: fmt::Display>(prompt: &str, options: &[T]) -> io::Result<Option<usize>> {
+    on_tty(|keys, output| select_on(keys, output, prompt, options))
+}
+
+/// Let the user pick one of `options`, reading the keys from `keys`.
+pub fn select_on<R: Read, W: Write, T: fmt::Display>(keys: &mut Keys<R>,
+                                                       output: &mut W,
+                                                       prompt: &str,
+                                                       options: &[T])
+                                                       -> io::Result<Option<usize>> {
+    if options.is_empty() {
+        return Ok(None);
+    }
+
+    write!(output, "{}{}\r\n", cursor::Hide, prompt)?;
+    let mut selected = 0;
+    let result = loop {
+        for (i, option) in options.iter().enumerate() {
+            if i > 0 {
+                output.write_all(b"\r\n")?;
+            }
+            if i == selected {
+                write!(output, "{}> {}{}", style::Invert, option, style::Reset)?;
+            } else {
+                write!(output, "  {}", option)?;
+            }
+            write!(output, "{}", clear::UntilNewline)?;
+        }
+        // Go back to the first option, to draw them again.
+        output.write_all(b"\r")?;
+        if options.len() > 1 {
+            write!(output, "{}", cursor::Up(options.len() as u16 - 1))?;
+        }
+        output.flush()?;
+
+        let last = options.len() - 1;
+        let key = match next_key(keys) {
+            Ok(key) => key,
+            Err(err) => break Err(err),
+        };
+        match key {
+            Key::Up | Key::Char('k') | Key::Ctrl('p') => {
+                selected = if selected == 0 { last } else { selected - 1 };
+            }
+            Key::Down | Key::Char('j') | Key::Ctrl('n') | Key::Char('\t') => {
+                selected = if selected == last { 0 } else { selected + 1 };
+            }
+            Key::Home => selected = 0,
+            Key::End => selected = last,
+            Key::Char('\n') => break Ok(Some(selected)),
+            Key::Esc | Key::Char('q') => break Ok(None),
+            _ => {}
+        }
+    };
+
+    // Replace the options by the answer, on the line of the prompt.
+    write!(output, "{}{}\r{}{}", clear::AfterCursor, cursor::Up(1), clear::CurrentLine, prompt)?;
+    if let Ok(Some(i)) = result {
+        write!(output, " {}", options[i])?;
+    }
+    write!(output, "\r\n{}", cursor::Show)?;
+    output.flush()?;
+    result
+}
+
+/// Read a line of text on the terminal, after `prompt`, returning `default` if it is left empty.
+///
+/// The line can be edited with the arrow keys, `Home` and `End`, `Backspace` and `Delete`, and
+/// `Ctrl-U`, which clears it.
+pub fn input(prompt: &str, default: &str) -> io::Result<String> {
+    on_tty(|keys, output| input_on(keys, output, prompt, default))
+}
+
+/// Read a line of text from `keys`, after `prompt`, returning `default` if it is left empty.
+pub fn input_on<R: Read, W: Write>(keys: &mut Keys<R>,
+                                   output: &mut W,
+                                   prompt: &str,
+                                   default: &str)
+                                   -> io::Result<String> {
+    let prompt = if default.is_empty() {
+        format!("{} ", prompt)
+    } else {
+        format!("{} [{}] ", prompt, default)
+    };
+
+    let mut line: Vec<char> = Vec::new();
+    let mut pos = 0;
+    loop {
+        let text: String = line.iter().collect();
+        write!(output, "\r{}{}{}", prompt, text, clear::UntilNewline)?;
+        let after: String = line[pos..].iter().collect();
+        let back = str_width(&after) as u16;
+        if back > 0 {
+            write!(output, "{}", cursor::Left(back))?;
+        }
+        output.flush()?;
+
+        match next_key(keys)? {
+            Key::Char('\n') => break,
+            Key::Char(c) if !c.is_control() => {
+                line.insert(pos, c);
+                pos += 1;
+            }
+            Key::Backspace if pos > 0 => {
+                pos -= 1;
+                line.remove(pos);
+            }
+            Key::Delete if pos < line.len() => {
+                line.remove(pos);
+            }
+            Key::Left => pos = pos.saturating_sub(1),
+            Key::Right => pos = (pos + 1).min(line.len()),
+            Key::Home | Key::Ctrl('a') => pos = 0,
+            Key::End | Key::Ctrl('e') => pos = line.len(),
+            Key::Ctrl('u') => {
+                line.clear();
+                pos = 0;
+            }
+            _ => {}
+        }
+    }
+
+    output.write_all(b"\r\n")?;
+    output.flush()?;
+    if line.is_empty() {
+        Ok(default.to_string())
+    } else {
+        Ok(line.into_iter().collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn keys(input: &'static [u8]) -> Keys<&'static [u8]> {
+        input.keys()
+    }
+
+    #[test]
+    fn test_confirm() {
+        let mut out = Vec::new();
+        assert!(confirm_on(&mut keys(b"xy"), &mut out, "Sure?").unwrap());
+        assert_eq!(out, b"Sure? [y/N] yes\r\n");
+        assert!(!confirm_on(&mut keys(b"\r"), &mut Vec::new(), "Sure?").unwrap());
+        let err = confirm_on(&mut keys(b"\x03"), &mut Vec::new(), "Sure?").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Interrupted);
+    }
+
+    #[test]
+    fn test_select() {
+        let options = ["a", "b", "c"];
+        let mut out = Vec::new();
+        assert_eq!(select_on(&mut keys(b"\x1B[A\x1B[Aj\r"), &mut out, "Pick:", &options).unwrap(),
+                   Some(2));
+        let out = String::from_utf8(out).unwrap();
+        let first = "\x1B[?25lPick:\r\n\x1B[7m> a\x1B[m\x1B[K\r\n  b\x1B[K\r\n  c\x1B[K\r\x1B[2A";
+        assert!(out.starts_with(first));
+        assert!(out.ends_with("\x1B[J\x1B[1A\r\x1B[2KPick: c\r\n\x1B[?25h"));
+
+        assert_eq!(select_on(&mut keys(b"q"), &mut Vec::new(), "Pick:", &options).unwrap(), None);
+        let err = select_on(&mut keys(b""), &mut Vec::new(), "Pick:", &options).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn test_input() {
+        let mut out = Vec::new();
+        let typed = b"ac\x1B[Db\x1B[H\x1B[3~\x7Fz\r";
+        assert_eq!(input_on(&mut keys(typed), &mut out, "Name:", "").unwrap(), "zbc");
+        assert!(String::from_utf8(out).unwrap().ends_with("\rName: zbc\x1B[K\x1B[2D\r\n"));
+        assert_eq!(input_on(&mut keys(b"\r"), &mut Vec::new(), "Name:", "x").unwrap(), "x");
+    }
+
+    #[test]
+    fn test_typed_ahead() {
+        let mut keys = keys(b"yname\r");
+        assert!(confirm_on(&mut keys, &mut Vec::new(), "Sure?").unwrap());
+        assert_eq!(input_on(&mut keys, &mut Vec::new(), "Name:", "").unwrap(), "name");
+    }
+}
@@ -0,0 +1,253 @@
+//! Pseudo-terminals, for running programs in a terminal of your own.
+//!
+//! A pseudo-terminal is a pair of devices: the program runs on the slave side, as if it were a
+//! terminal, and its output is read from (and its input written to) the master side. This is how
+//! terminal emulators and multiplexers are built.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use std::io::{Read, Write};
+//! use termion::pty::Command;
+//!
+//! let mut child = Command::new("sh").size(80, 24).spawn().unwrap();
+//! child.write_all(b"echo hello; exit\n").unwrap();
+//!
+//! let mut output = Vec::new();
+//! // Reading the master side fails once the child has exited.
+//! let _ = child.read_to_end(&mut output);
+//! child.wait().unwrap();
+//! ```
+
+use std::ffi::OsStr;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::Path;
+use std::process::{self, ExitStatus, Stdio};
+
+use sys;
+
+/// The two sides of a pseudo-terminal.
+pub struct Pty {
+    master: File,
+    slave: File,
+}
+
+/// Open a pseudo-terminal `width` columns wide and `height` rows high.
+pub fn openpty(width: u16, height: u16) -> io::Result<Pty> {
+    let (master, slave) = sys::pty::openpty(width, height)?;
+    Ok(Pty { master, slave })
+}
+
+impl Pty {
+    /// The master side, to read the output of the program and to write its input.
+    pub fn master(&self) -> &File {
+        &self.master
+    }
+
+    /// The slave side, which the program uses as its terminal.
+    pub fn slave(&self) -> &File {
+        &self.slave
+    }
+
+    /// Resize the pseudo-terminal, which notifies the program with `SIGWINCH`.
+    pub fn resize(&self, width: u16, height: u16) -> io::Result<()> {
+        sys::pty::set_size(&self.master, width, height)
+    }
+
+    /// Unwrap the master and slave sides.
+    pub fn into_inner(self) -> (File, File) {
+        (self.master, self.slave)
+    }
+}
+
+/// A builder for a process running in a new pseudo-terminal.
+///
+/// The process gets the pseudo-terminal as its standard streams and controlling terminal, in a
+/// session of its own.
+pub struct Command {
+    command: process::Command,
+    size: (u16, u16),
+}
+
+impl Command {
+    /// Run `program`, in an 80×24 terminal unless `size` is called.
+    pub fn new<S: AsRef<OsStr>>(program: S) -> Command {
+        let mut command = process::Command::new(program);
+        // Once only, as every hook runs on each spawn.
+        sys::pty::set_controlling_terminal(&mut command);
+
+        Command {
+            command,
+            size: (80, 24),
+        }
+    }
+
+    /// Add an argument.
+    pub fn arg<S: AsRef<OsStr>>(&mut self, arg: S) -> &mut Command {
+        self.command.arg(arg);
+        self
+    }
+
+    /// Add arguments.
+    pub fn args<I, S>(&mut self, args: I) -> &mut Command
+        where I: IntoIterator<Item = S>,
+              S: AsRef<OsStr>
+    {
+        self.command.args(args);
+        self
+    }
+
+    /// Set an environment variable, e.g. `TERM`.
+    pub fn env<K: AsRef<OsStr>, V: AsRef<OsStr>>(&mut self, key: K, value: V) -> &mut Command {
+        self.command.env(key, value);
+        self
+    }
+
+    /// Set the working directory.
+    pub fn current_dir<P: AsRef<Path>>(&mut self, dir: P) -> &mut Command {
+        self.command.current_dir(dir);
+        self
+    }
+
+    /// Set the initial size of the terminal.
+    pub fn size(&mut self, width: u16, height: u16) -> &mut Command {
+        self.size = (width, height);
+        self
+    }
+
+    /// The underlying command, for the settings not covered here.
+    ///
+    /// Its standard streams are overridden by `spawn`.
+    pub fn command_mut(&mut self) -> &mut process::Command {
+        &mut self.command
+    }
+
+    /// Open a pseudo-terminal and spawn the process in it.
+    pub fn spawn(&mut self) -> io::Result<Child> {
+        let (width, height) = self.size;
+        let pty = openpty(width, height)?;
+
+        self.command
+            .stdin(Stdio::from(pty.slave.try_clone()?))
+            .stdout(Stdio::from(pty.slave.try_clone()?))
+            .stderr(Stdio::from(pty.slave.try_clone()?));
+
+        let process = self.command.spawn();
+        // The slave side is closed here, so that reading the master fails once the child is gone.
+        self.command.stdin(Stdio::null()).stdout(Stdio::null()).stderr(Stdio::null());
+        let process = process?;
+        let (master, _) = pty.into_inner();
+
+        Ok(Child { process, master })
+    }
+}
+
+/// A process running in a pseudo-terminal.
+///
+/// Reading gives its output, and writing sends it input, through the master side.
+pub struct Child {
+    process: process::Child,
+    master: File,
+}
+
+impl Child {
+    /// The process ID.
+    pub fn id(&self) -> u32 {
+        self.process.id()
+    }
+
+    /// The master side of the pseudo-terminal.
+    ///
+    /// Use `File::try_clone` to read the output from another thread.
+    pub fn master(&self) -> &File {
+        &self.master
+    }
+
+    /// Resize the terminal, which notifies the process with `SIGWINCH`.
+    pub fn resize(&self, width: u16, height: u16) -> io::Result<()> {
+        sys::pty::set_size(&self.master, width, height)
+    }
+
+    /// Wait for the process to exit.
+    pub fn wait(&mut self) -> io::Result<ExitStatus> {
+        self.process.wait()
+    }
+
+    /// Check whether the process has exited, without blocking.
+    pub fn try_wait(&mut self) -> io::Result<Option<ExitStatus>> {
+        self.process.try_wait()
+    }
+
+    /// Kill the process.
+    pub fn kill(&mut self) -> io::Result<()> {
+        self.process.kill()
+    }
+}
+
+impl Read for Child {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.master.read(buf)
+    }
+}
+
+impl Write for Child {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.master.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.master.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.master.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use is_tty;
+
+    /// Read all the output of `child`, up to the error signaling the slave side was closed.
+    fn output(child: &mut Child) -> String {
+        let mut output = Vec::new();
+        let _ = child.read_to_end(&mut output);
+        child.wait().unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn test_openpty() {
+        let pty = openpty(80, 24).unwrap();
+        assert!(is_tty(pty.slave()));
+        pty.resize(100, 30).unwrap();
+    }
+
+    #[test]
+    fn test_spawn() {
+        let mut child = Command::new("sh").args(["-c", "tty -s && stty size"]).size(120, 40)
+            .spawn()
+            .unwrap();
+        assert_eq!(output(&mut child).trim(), "40 120");
+    }
+
+    #[test]
+    fn test_spawn_twice() {
+        let mut command = Command::new("sh");
+        command.args(["-c", "tty -s && echo ok"]);
+        for _ in 0..2 {
+            let mut child = command.spawn().unwrap();
+            assert_eq!(output(&mut child).trim(), "ok");
+        }
+    }
+
+    #[test]
+    fn test_resize() {
+        let mut child = Command::new("sh").args(["-c", "read x; stty size"]).spawn().unwrap();
+        child.resize(90, 20).unwrap();
+        child.write_all(b"\n").unwrap();
+        assert!(output(&mut child).ends_with("20 90\r\n"));
+    }
+}
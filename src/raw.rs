@@ -9,6 +9,14 @@
 //!
 //! It is essential to design terminal programs.
 //!
+//! # Which terminal is affected?
+//!
+//! Raw mode is a property of the terminal device, not of the writer it is requested on.
+//! `into_raw_mode` affects the terminal attached to the standard input (file descriptor 0), and
+//! restores that same terminal on drop, regardless of where the writer points to. Use
+//! `into_raw_mode_on` to pick the terminal explicitly, e.g. `/dev/tty` (see `get_tty`) when the
+//! standard input is redirected, or a pseudo-terminal.
+//!
 //! # Example
 //!
 //! ```rust,no_run
@@ -24,9 +32,12 @@
 
 use std::io::{self, Write};
 use std::ops;
+use std::sync::{Mutex, TryLockError};
+use sys::{AsRawFd, RawFd};
 
 use sys::Termios;
-use sys::attr::{get_terminal_attr, raw_terminal_attr, set_terminal_attr};
+use sys::attr::{at_exit, close_fd, dup_fd, get_terminal_attr_fd, is_raw, keep_output_processing,
+                raw_terminal_attr, set_terminal_attr_fd};
 
 /// The timeout of an escape code control sequence, in milliseconds.
 pub const CONTROL_SEQUENCE_TIMEOUT: u64 = 100;
@@ -34,15 +45,34 @@ pub const CONTROL_SEQUENCE_TIMEOUT: u64 = 100;
 /// A terminal restorer, which keeps the previous state of the terminal, and restores it, when
 /// dropped.
 ///
-/// Restoring will entirely bring back the old TTY state.
+/// Restoring will entirely bring back the old TTY state. The terminal is restored through a
+/// duplicate of its file descriptor, taken on creation, so closing or redirecting the original
+/// one (e.g. the standard input of a daemon) doesn't keep it from being restored.
 pub struct RawTerminal<W: Write> {
     prev_ios: Termios,
+    /// The terminal in raw mode.
+    tty: RawFd,
+    /// The duplicate of `tty` the terminal is restored through.
+    restore_fd: RawFd,
+    /// The restoration on exit, if `restore_on_exit` was called.
+    on_exit: Option<ExitGuard>,
     output: W,
 }
 
+impl<W: Write> RawTerminal<W> {
+    /// The file descriptor of the terminal in raw mode, as given on creation.
+    pub fn tty_fd(&self) -> RawFd {
+        self.tty
+    }
+}
+
 impl<W: Write> Drop for RawTerminal<W> {
     fn drop(&mut self) {
-        set_terminal_attr(&self.prev_ios).unwrap();
+        // The exit hook must not use the file descriptor once it's closed.
+        self.on_exit.take();
+        let restored = set_terminal_attr_fd(self.restore_fd, &self.prev_ios);
+        close_fd(self.restore_fd);
+        restored.unwrap();
     }
 }
 
@@ -65,6 +95,10 @@ impl<W: Write> Write for RawTerminal<W> {
         self.output.write(buf)
     }
 
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         self.output.flush()
     }
@@ -76,31 +110,191 @@ impl<W: Write> Write for RawTerminal<W> {
 ///
 /// TTYs has their state controlled by the writer, not the reader. You use the writer to clear the
 /// screen, move the cursor and so on, so naturally you use the writer to change the mode as well.
+/// Note that the terminal switched to raw mode isn't inferred from the writer, see the module
+/// documentation.
 pub trait IntoRawMode: Write + Sized {
-    /// Switch to raw mode.
+    /// Switch the terminal attached to the standard input to raw mode.
     ///
     /// Raw mode means that stdin won't be printed (it will instead have to be written manually by
     /// the program). Furthermore, the input isn't canonicalised or buffered (that is, you can
     /// read from stdin one byte of a time). The output is neither modified in any way.
-    fn into_raw_mode(self) -> io::Result<RawTerminal<Self>>;
+    fn into_raw_mode(self) -> io::Result<RawTerminal<Self>> {
+        enter_raw_mode(self, 0, false)
+    }
+
+    /// Switch the terminal `tty` to raw mode.
+    ///
+    /// This fails if `tty` isn't a terminal. The terminal is restored on drop through a duplicate
+    /// of the file descriptor of `tty`, so `tty` may be closed before then.
+    fn into_raw_mode_on<T: AsRawFd>(self, tty: &T) -> io::Result<RawTerminal<Self>> {
+        enter_raw_mode(self, tty.as_raw_fd(), false)
+    }
+
+    /// Switch the terminal attached to the standard input to raw mode, except for the output.
+    ///
+    /// The input is raw, but the output is still processed: `\n` goes to the start of the next
+    /// line (as `\r\n`), so that `println!` and friends don't produce stair-stepped output.
+    fn into_raw_mode_keep_opost(self) -> io::Result<RawTerminal<Self>> {
+        enter_raw_mode(self, 0, true)
+    }
+}
+
+impl<W: Write> IntoRawMode for W {}
+
+/// Is the terminal attached to the standard input in raw mode?
+///
+/// This compares the current input flags of the terminal with those raw mode sets (the input is
+/// neither canonicalised nor echoed, and sends no signals), rather than with the state saved by a
+/// `RawTerminal`, so it also tells about raw mode entered by other code, e.g. by a library or by
+/// the parent process. A mode only close to raw, such as cbreak mode, isn't taken for it. It is
+/// `false` if the standard input is not a terminal.
+pub fn is_raw_mode_enabled() -> bool {
+    is_raw_mode_enabled_on_fd(0)
+}
+
+/// Is the terminal `tty` in raw mode?
+///
+/// See `is_raw_mode_enabled`.
+pub fn is_raw_mode_enabled_on<T: AsRawFd>(tty: &T) -> bool {
+    is_raw_mode_enabled_on_fd(tty.as_raw_fd())
+}
+
+fn is_raw_mode_enabled_on_fd(tty: RawFd) -> bool {
+    get_terminal_attr_fd(tty).map(|ios| is_raw(&ios)).unwrap_or(false)
+}
+
+/// Switch the terminal `tty` to raw mode, for writing to `output`.
+///
+/// With `keep_opost`, the output is still processed.
+fn enter_raw_mode<W: Write>(output: W, tty: RawFd, keep_opost: bool) -> io::Result<RawTerminal<W>> {
+    let mut ios = get_terminal_attr_fd(tty)?;
+    let prev_ios = ios;
+
+    raw_terminal_attr(&mut ios);
+    if keep_opost {
+        keep_output_processing(&mut ios);
+    }
+
+    let restore_fd = dup_fd(tty)?;
+    if let Err(err) = set_terminal_attr_fd(tty, &ios) {
+        close_fd(restore_fd);
+        return Err(err);
+    }
+
+    Ok(RawTerminal {
+        prev_ios,
+        tty,
+        restore_fd,
+        on_exit: ExitGuard::new(ExitTask::Attr(restore_fd, prev_ios)),
+        output,
+    })
+}
+
+/// What to do to restore the terminal on exit.
+#[derive(Clone, Copy)]
+enum ExitTask {
+    /// Write this sequence, such as the one leaving the alternate screen.
+    Write(&'static str),
+    /// Set the attributes of this terminal back.
+    Attr(RawFd, Termios),
+}
+
+/// The restoration on exit: whether it is on, and the tasks of the restorers alive, by id.
+struct ExitTasks {
+    enabled: bool,
+    next_id: usize,
+    tasks: Vec<(usize, ExitTask)>,
+}
+
+static EXIT_TASKS: Mutex<ExitTasks> = Mutex::new(ExitTasks {
+    enabled: false,
+    next_id: 0,
+    tasks: Vec::new(),
+});
+
+fn exit_tasks() -> ::std::sync::MutexGuard<'static, ExitTasks> {
+    EXIT_TASKS.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Restore the terminal when the process exits, even through `std::process::exit`, which
+/// doesn't run the destructors of the restorers.
+///
+/// Once this is called, the restorers created afterwards (`RawTerminal`, `AlternateScreen`,
+/// `MouseTerminal`, `BracketedPasteTerminal` and `ModifyOtherKeysTerminal`) also register what
+/// they undo on drop, and a hook run by `exit` undoes what those still alive did: the
+/// sequences are written to the terminal, and then its attributes are set back. Restorers
+/// created earlier are not covered, so call this first thing. Calling it again does nothing.
+pub fn restore_on_exit() -> io::Result<()> {
+    let mut tasks = exit_tasks();
+    if !tasks.enabled {
+        at_exit(run_exit_tasks_hook)?;
+        tasks.enabled = true;
+    }
+    Ok(())
 }
 
-impl<W: Write> IntoRawMode for W {
-    fn into_raw_mode(self) -> io::Result<RawTerminal<W>> {
-        let mut ios = get_terminal_attr()?;
-        let prev_ios = ios;
+extern "C" fn run_exit_tasks_hook() {
+    match ::get_tty() {
+        Ok(mut tty) => run_exit_tasks(&mut tty),
+        Err(_) => run_exit_tasks(&mut io::stdout()),
+    }
+}
 
-        raw_terminal_attr(&mut ios);
+/// Undo what the restorers alive did, the latest first.
+///
+/// Nothing is done if the tasks are locked, as `exit` may be called from a thread holding the
+/// lock, or while another one does.
+fn run_exit_tasks(output: &mut dyn Write) {
+    let tasks = match EXIT_TASKS.try_lock() {
+        Ok(tasks) => tasks,
+        Err(TryLockError::Poisoned(e)) => e.into_inner(),
+        Err(TryLockError::WouldBlock) => return,
+    };
+    for &(_, task) in tasks.tasks.iter().rev() {
+        if let ExitTask::Write(sequence) = task {
+            let _ = output.write_all(sequence.as_bytes());
+        }
+    }
+    let _ = output.flush();
+    for &(_, task) in tasks.tasks.iter().rev() {
+        if let ExitTask::Attr(fd, ref ios) = task {
+            let _ = set_terminal_attr_fd(fd, ios);
+        }
+    }
+}
+
+/// The registration of a task with the restoration on exit, removed when dropped.
+pub(crate) struct ExitGuard(usize);
 
-        set_terminal_attr(&ios)?;
+impl ExitGuard {
+    /// Register `task`, if the restoration on exit is on.
+    fn new(task: ExitTask) -> Option<ExitGuard> {
+        let mut tasks = exit_tasks();
+        if !tasks.enabled {
+            return None;
+        }
+        let id = tasks.next_id;
+        tasks.next_id += 1;
+        tasks.tasks.push((id, task));
+        Some(ExitGuard(id))
+    }
 
-        Ok(RawTerminal {
-            prev_ios: prev_ios,
-            output: self,
-        })
+    /// Register writing `sequence` on exit, if the restoration on exit is on.
+    pub(crate) fn write(sequence: &'static str) -> Option<ExitGuard> {
+        ExitGuard::new(ExitTask::Write(sequence))
     }
 }
 
+impl Drop for ExitGuard {
+    fn drop(&mut self) {
+        exit_tasks().tasks.retain(|&(id, _)| id != self.0);
+    }
+}
+
+/// Held by the tests changing the mode of the terminal, which would otherwise race.
+#[cfg(test)]
+pub(crate) static TEST_TTY: ::std::sync::Mutex<()> = ::std::sync::Mutex::new(());
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -108,10 +302,69 @@ mod test {
 
     #[test]
     fn test_into_raw_mode() {
+        let _lock = TEST_TTY.lock().unwrap_or_else(|e| e.into_inner());
         let mut out = stdout().into_raw_mode().unwrap();
 
         out.write_all(b"this is a test, muahhahahah\r\n").unwrap();
 
         drop(out);
     }
+
+    #[test]
+    fn test_into_raw_mode_on() {
+        let _lock = TEST_TTY.lock().unwrap_or_else(|e| e.into_inner());
+        let tty = ::get_tty().unwrap();
+        let out = stdout().into_raw_mode_on(&tty).unwrap();
+        assert_eq!(out.tty_fd(), tty.as_raw_fd());
+
+        drop(out);
+    }
+
+    #[test]
+    fn test_restore_after_close() {
+        let _lock = TEST_TTY.lock().unwrap_or_else(|e| e.into_inner());
+        let tty = ::get_tty().unwrap();
+        let out = stdout().into_raw_mode_on(&tty).unwrap();
+        drop(tty);
+        assert!(is_raw_mode_enabled_on(&::get_tty().unwrap()));
+
+        drop(out);
+        assert!(!is_raw_mode_enabled_on(&::get_tty().unwrap()));
+    }
+
+    #[test]
+    fn test_into_raw_mode_keep_opost() {
+        let _lock = TEST_TTY.lock().unwrap_or_else(|e| e.into_inner());
+        let before = get_terminal_attr_fd(0).unwrap();
+        let out = stdout().into_raw_mode_keep_opost().unwrap();
+        let during = get_terminal_attr_fd(0).unwrap();
+
+        assert_ne!(during.c_lflag, before.c_lflag);
+        assert_eq!(during.c_oflag & before.c_oflag, before.c_oflag);
+
+        drop(out);
+        assert_eq!(get_terminal_attr_fd(0).unwrap().c_lflag, before.c_lflag);
+    }
+
+    #[test]
+    fn test_is_raw_mode_enabled() {
+        let _lock = TEST_TTY.lock().unwrap_or_else(|e| e.into_inner());
+        assert!(!is_raw_mode_enabled());
+
+        let out = stdout().into_raw_mode().unwrap();
+        assert!(is_raw_mode_enabled());
+        assert!(is_raw_mode_enabled_on(&::get_tty().unwrap()));
+
+        drop(out);
+        assert!(!is_raw_mode_enabled());
+
+        let file = ::std::fs::File::open("Cargo.toml").unwrap();
+        assert!(!is_raw_mode_enabled_on(&file));
+    }
+
+    #[test]
+    fn test_into_raw_mode_on_non_tty() {
+        let file = ::std::fs::File::open("Cargo.toml").unwrap();
+        assert!(stdout().into_raw_mode_on(&file).is_err());
+    }
 }
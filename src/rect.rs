@@ -0,0 +1,165 @@
+//! Rectangular area operations (VT420).
+//!
+//! These copy, fill or erase a rectangular area of the screen in a single sequence, instead of a
+//! write per line. Terminals that don't support them (see
+//! `DeviceAttributes::rectangular_editing`) ignore them.
+//!
+//! # Example
+//!
+#![cfg_attr(feature = "std", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "std"), doc = "```rust,ignore")]
+//! use termion::rect::{EraseRect, Rect};
+//! use termion::terminal::DetectDeviceAttributes;
+//! use std::io::{Write, stdout};
+//!
+//! let mut stdout = stdout();
+//! if stdout.device_attributes().map(|attrs| attrs.rectangular_editing()).unwrap_or(false) {
+//!     // Clear a popup.
+//!     write!(stdout, "{}", EraseRect(Rect::new(10, 5, 40, 8))).unwrap();
+//! }
+//! ```
+
+use std::fmt;
+
+/// A rectangular area of the screen, with (1,1)-based coordinates, like `cursor::Goto`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    /// The leftmost column.
+    pub x: u16,
+    /// The top row.
+    pub y: u16,
+    /// The number of columns.
+    pub width: u16,
+    /// The number of rows.
+    pub height: u16,
+}
+
+impl Rect {
+    /// The area `width` columns wide and `height` rows high, at column `x` and row `y`.
+    pub fn new(x: u16, y: u16, width: u16, height: u16) -> Rect {
+        Rect { x, y, width, height }
+    }
+
+    /// Does the area contain no cell?
+    pub fn is_empty(&self) -> bool {
+        self.width == 0 || self.height == 0
+    }
+
+    /// Does the area contain the cell at column `x` and row `y`?
+    pub fn contains(&self, x: u16, y: u16) -> bool {
+        !self.is_empty() && x >= self.x && y >= self.y && x - self.x < self.width &&
+        y - self.y < self.height
+    }
+
+    /// Do the areas share a cell?
+    pub fn intersects(&self, other: Rect) -> bool {
+        if self.is_empty() || other.is_empty() {
+            return false;
+        }
+        let (top, left, bottom, right) = self.edges();
+        let (other_top, other_left, other_bottom, other_right) = other.edges();
+        left <= other_right && other_left <= right && top <= other_bottom && other_top <= bottom
+    }
+
+    /// The smallest area containing both areas. An empty area is ignored.
+    pub fn union(&self, other: Rect) -> Rect {
+        if other.is_empty() {
+            return *self;
+        }
+        if self.is_empty() {
+            return other;
+        }
+        let (top, left, bottom, right) = self.edges();
+        let (other_top, other_left, other_bottom, other_right) = other.edges();
+        let (top, left) = (top.min(other_top), left.min(other_left));
+        let (bottom, right) = (bottom.max(other_bottom), right.max(other_right));
+        Rect::new(left, top, right - left + 1, bottom - top + 1)
+    }
+
+    /// The top, left, bottom and right edges, inclusive.
+    fn edges(&self) -> (u16, u16, u16, u16) {
+        (self.y,
+         self.x,
+         self.y.saturating_add(self.height - 1),
+         self.x.saturating_add(self.width - 1))
+    }
+}
+
+/// Copy the area to column `.1` and row `.2` (DECCRA). Nothing is written for an empty area.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CopyRect(pub Rect, pub u16, pub u16);
+
+impl fmt::Display for CopyRect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let (top, left, bottom, right) = self.0.edges();
+        write!(f, csi!("{};{};{};{};1;{};{};1$v"), top, left, bottom, right, self.2, self.1)
+    }
+}
+
+/// Fill the area with a character (DECFRA), keeping the current graphic rendition. Nothing is
+/// written for an empty area.
+///
+/// VT420 only accepts the printable characters of Latin-1; xterm accepts any character it can
+/// display.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FillRect(pub Rect, pub char);
+
+impl fmt::Display for FillRect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let (top, left, bottom, right) = self.0.edges();
+        write!(f, csi!("{};{};{};{};{}$x"), self.1 as u32, top, left, bottom, right)
+    }
+}
+
+/// Erase the area (DECERA). Nothing is written for an empty area.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EraseRect(pub Rect);
+
+impl fmt::Display for EraseRect {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return Ok(());
+        }
+        let (top, left, bottom, right) = self.0.edges();
+        write!(f, csi!("{};{};{};{}$z"), top, left, bottom, right)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rect_sequences() {
+        let rect = Rect::new(3, 2, 10, 4);
+        assert_eq!(CopyRect(rect, 20, 8).to_string(), "\x1B[2;3;5;12;1;8;20;1$v");
+        assert_eq!(FillRect(rect, '#').to_string(), "\x1B[35;2;3;5;12$x");
+        assert_eq!(EraseRect(rect).to_string(), "\x1B[2;3;5;12$z");
+    }
+
+    #[test]
+    fn test_rect_geometry() {
+        let rect = Rect::new(3, 2, 10, 4);
+        assert!(rect.contains(3, 2) && rect.contains(12, 5));
+        assert!(!rect.contains(13, 5) && !rect.contains(3, 6) && !rect.contains(2, 2));
+        assert!(rect.intersects(Rect::new(12, 5, 3, 3)));
+        assert!(!rect.intersects(Rect::new(13, 2, 3, 3)));
+        assert!(!rect.intersects(Rect::new(5, 3, 0, 1)));
+        assert_eq!(rect.union(Rect::new(1, 4, 2, 5)), Rect::new(1, 2, 12, 7));
+        assert_eq!(rect.union(Rect::default()), rect);
+    }
+
+    #[test]
+    fn test_empty_rect() {
+        let rect = Rect::new(3, 2, 0, 4);
+        assert_eq!(CopyRect(rect, 20, 8).to_string(), "");
+        assert_eq!(FillRect(rect, '#').to_string(), "");
+        assert_eq!(EraseRect(rect).to_string(), "");
+    }
+}
@@ -0,0 +1,197 @@
+//! Terminals at the other end of an arbitrary transport.
+//!
+//! Most of termion only writes escape sequences to a `Write`r, and parses events from a `Read`er,
+//! so it works just as well over a TCP socket, an SSH channel or a serial port. What doesn't are
+//! the parts relying on the local TTY: raw mode, `terminal_size`, and the `Detect*` traits, which
+//! read the replies from `async_stdin`. `RemoteTerminal` provides these over the transport.
+//!
+//! There is no raw mode on the server's side: the client's terminal is switched to it by the
+//! client (e.g. SSH does when a PTY is requested).
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! extern crate termion;
+//!
+//! use std::io::Write;
+//! use std::net::TcpListener;
+//! use std::time::Duration;
+//! use termion::event::{Event, Key};
+//! use termion::remote::RemoteTerminal;
+//!
+//! fn main() {
+//!     let listener = TcpListener::bind("127.0.0.1:2323").unwrap();
+//!     let (stream, _) = listener.accept().unwrap();
+//!     // Let queries time out.
+//!     stream.set_read_timeout(Some(Duration::from_millis(10))).unwrap();
+//!
+//!     let mut term = RemoteTerminal::new(stream.try_clone().unwrap(), stream);
+//!     let (x, y) = term.cursor_pos().unwrap();
+//!     write!(term, "The cursor was at {}, {}.", x, y).unwrap();
+//!     term.flush().unwrap();
+//!
+//!     while let Some(event) = term.next_event() {
+//!         match event {
+//!             Ok(Event::Key(Key::Char('q'))) => break,
+//!             Err(ref e) if e.kind() == std::io::ErrorKind::WouldBlock => (),
+//!             Err(_) => break,
+//!             _ => (),
+//!         }
+//!     }
+//! }
+//! ```
+
+use std::io::{self, Read, Write};
+use std::ops;
+
+use color::{self, Rgb};
+use cursor;
+use event::Event;
+use input::{Events, TermRead};
+use terminal::{self, DeviceAttributes, ModeState};
+
+/// A terminal reached through `input` and `output` rather than the local TTY.
+///
+/// The input should not block forever: use a read timeout or a non-blocking transport, or
+/// queries waiting for a reply which never comes will hang.
+pub struct RemoteTerminal<R: Read, W: Write> {
+    events: Events<R>,
+    output: W,
+    size: Option<(u16, u16)>,
+}
+
+impl<R: Read, W: Write> RemoteTerminal<R, W> {
+    /// Create a terminal reading from `input` and writing to `output`.
+    pub fn new(input: R, output: W) -> RemoteTerminal<R, W> {
+        RemoteTerminal {
+            events: input.events(),
+            output,
+            size: None,
+        }
+    }
+
+    /// Read the next input event.
+    pub fn next_event(&mut self) -> Option<io::Result<Event>> {
+        self.events.next()
+    }
+
+    /// The input of the terminal.
+    pub fn input(&mut self) -> &mut R {
+        self.events.get_mut()
+    }
+
+    /// Report the size of the terminal, as received out of band (e.g. through an SSH
+    /// `window-change` request or telnet NAWS).
+    pub fn set_size(&mut self, width: u16, height: u16) {
+        self.size = Some((width, height));
+    }
+
+    /// The size of the terminal.
+    ///
+    /// This is the size given to `set_size`, if any. Otherwise it is detected by moving the
+    /// cursor to the bottom right corner and asking where it ended up.
+    pub fn size(&mut self) -> io::Result<(u16, u16)> {
+        if let Some(size) = self.size {
+            return Ok(size);
+        }
+
+        // Save the cursor, move it as far as possible and restore it afterwards.
+        write!(self.output, "\x1B7{}", cursor::Goto(9999, 9999))?;
+        let pos = cursor::query_cursor_pos(&mut self.output, self.events.get_mut());
+        write!(self.output, "\x1B8")?;
+        self.output.flush()?;
+        pos
+    }
+
+    /// Get the (1,1)-based cursor position from the terminal.
+    pub fn cursor_pos(&mut self) -> io::Result<(u16, u16)> {
+        cursor::query_cursor_pos(&mut self.output, self.events.get_mut())
+    }
+
+    /// Get the color of the cursor, using OSC 12.
+    pub fn cursor_color(&mut self) -> io::Result<Rgb> {
+        cursor::query_cursor_color(&mut self.output, self.events.get_mut())
+    }
+
+    /// Get the default background color of the terminal, using OSC 11.
+    pub fn background_color(&mut self) -> io::Result<Rgb> {
+        color::query_default_color(&mut self.output, self.events.get_mut(), 11)
+    }
+
+    /// Get the RGB value of the palette color `index`, using OSC 4.
+    pub fn palette_color(&mut self, index: u8) -> io::Result<Rgb> {
+        color::query_palette(&mut self.output, self.events.get_mut(), &[index])?[0]
+            .ok_or_else(|| io::Error::new(io::ErrorKind::TimedOut, "Palette query timed out."))
+    }
+
+    /// Get the Primary Device Attributes of the terminal.
+    pub fn device_attributes(&mut self) -> io::Result<DeviceAttributes> {
+        terminal::query_device_attributes(&mut self.output, self.events.get_mut())
+    }
+
+    /// Get the state of the DEC private mode `mode`, using DECRQM.
+    pub fn mode(&mut self, mode: u16) -> io::Result<ModeState> {
+        terminal::query_mode(&mut self.output, self.events.get_mut(), mode)
+    }
+
+    /// Split the terminal into its input and output.
+    pub fn into_inner(self) -> (R, W) {
+        let RemoteTerminal { events, output, .. } = self;
+        (events.into_inner(), output)
+    }
+}
+
+impl<R: Read, W: Write> ops::Deref for RemoteTerminal<R, W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+impl<R: Read, W: Write> ops::DerefMut for RemoteTerminal<R, W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+impl<R: Read, W: Write> Write for RemoteTerminal<R, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use event::Key;
+
+    #[test]
+    fn test_remote_queries() {
+        let input: &[u8] = b"\x1B[24;80R\x1B[3;7Rq";
+        let mut term = RemoteTerminal::new(input, Vec::new());
+
+        assert_eq!(term.size().unwrap(), (80, 24));
+        assert_eq!(term.cursor_pos().unwrap(), (7, 3));
+        assert_eq!(term.next_event().unwrap().unwrap(), Event::Key(Key::Char('q')));
+
+        let (_, output) = term.into_inner();
+        assert_eq!(output, b"\x1B7\x1B[9999;9999H\x1B[6n\x1B8\x1B[6n");
+    }
+
+    #[test]
+    fn test_reported_size() {
+        let mut term = RemoteTerminal::new(&b""[..], Vec::new());
+        term.set_size(100, 30);
+        assert_eq!(term.size().unwrap(), (100, 30));
+        assert!(term.into_inner().1.is_empty());
+    }
+}
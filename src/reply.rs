@@ -0,0 +1,133 @@
+//! Reading the replies of the terminal to queries.
+
+use std::io::{self, Read};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+use raw::CONTROL_SEQUENCE_TIMEOUT;
+
+/// How long to wait before reading again, when no data was available.
+const RETRY_INTERVAL: Duration = Duration::from_millis(1);
+
+/// Read from `input` until `is_complete` holds for the bytes read so far, or until the control
+/// sequence timeout runs out.
+///
+/// `input` is expected not to block, like `AsyncReader`, or to time out (`WouldBlock` and
+/// `TimedOut` errors are taken as no data yet, and so is the end of the input, which is read
+/// again after a while). It is read one byte at a time, so that the input following the reply is
+/// left alone.
+pub fn read_until<F>(input: &mut dyn Read, mut is_complete: F) -> io::Result<Vec<u8>>
+    where F: FnMut(&[u8]) -> bool
+{
+    let mut buf = [0u8; 1];
+    let mut read = Vec::new();
+
+    let timeout = Duration::from_millis(CONTROL_SEQUENCE_TIMEOUT);
+    let now = SystemTime::now();
+
+    while !is_complete(&read) && now.elapsed().unwrap_or(timeout) < timeout {
+        match input.read(&mut buf) {
+            Ok(0) => thread::sleep(RETRY_INTERVAL),
+            Ok(_) => read.push(buf[0]),
+            Err(ref e) if e.kind() == io::ErrorKind::WouldBlock ||
+                          e.kind() == io::ErrorKind::TimedOut => thread::sleep(RETRY_INTERVAL),
+            Err(ref e) if e.kind() == io::ErrorKind::Interrupted => (),
+            Err(e) => return Err(e),
+        }
+    }
+
+    Ok(read)
+}
+
+/// Count the string sequences (OSC, DCS, ...) terminated by BEL or ST in `buf`.
+pub fn count_strings(buf: &[u8]) -> usize {
+    split_strings(buf).count()
+}
+
+/// Split `buf` into the terminated string sequences it contains, without their terminators.
+pub fn split_strings<'a>(buf: &'a [u8]) -> Box<dyn Iterator<Item = &'a [u8]> + 'a> {
+    let mut rest = buf;
+
+    Box::new(::std::iter::from_fn(move || {
+        let end = rest.iter()
+            .enumerate()
+            .position(|(i, &c)| c == b'\x07' || (c == b'\\' && i > 0 && rest[i - 1] == b'\x1B'))?;
+        let (string, len) = if rest[end] == b'\x07' {
+            (&rest[..end], end + 1)
+        } else {
+            (&rest[..end - 1], end + 1)
+        };
+        rest = &rest[len..];
+        Some(string)
+    }))
+}
+
+/// Parse an X11 color specification (`rgb:RRRR/GGGG/BBBB`, with one to four hex digits per
+/// channel), as used in OSC color replies.
+pub fn parse_x11_color(spec: &[u8]) -> Option<(u8, u8, u8)> {
+    if !spec.starts_with(b"rgb:") {
+        return None;
+    }
+
+    let spec = ::std::str::from_utf8(&spec[4..]).ok()?;
+    let mut channels = spec.split('/').map(|hex| {
+        if hex.is_empty() || hex.len() > 4 {
+            return None;
+        }
+        let value = u32::from_str_radix(hex, 16).ok()?;
+        let max = (1u32 << (4 * hex.len())) - 1;
+        Some(((value * 255 + max / 2) / max) as u8)
+    });
+
+    let r = channels.next()??;
+    let g = channels.next()??;
+    let b = channels.next()??;
+    if channels.next().is_some() {
+        return None;
+    }
+
+    Some((r, g, b))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_read_until_eof() {
+        struct Empty(usize);
+        impl Read for Empty {
+            fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+                self.0 += 1;
+                Ok(0)
+            }
+        }
+
+        // The reads are spaced out rather than spinning until the timeout.
+        let mut input = Empty(0);
+        assert_eq!(read_until(&mut input, |read| !read.is_empty()).unwrap(), b"");
+        assert!(input.0 <= CONTROL_SEQUENCE_TIMEOUT as usize + 1);
+    }
+
+    #[test]
+    fn test_split_strings() {
+        let buf = b"\x1B]4;1;rgb:cd/00/00\x07\x1B]4;2;rgb:00/cd/00\x1B\\\x1B]4;3";
+        let strings: Vec<&[u8]> = split_strings(buf).collect();
+        assert_eq!(strings,
+                   vec![&b"\x1B]4;1;rgb:cd/00/00"[..], &b"\x1B]4;2;rgb:00/cd/00"[..]]);
+        assert_eq!(count_strings(buf), 2);
+        assert_eq!(count_strings(b""), 0);
+    }
+
+    #[test]
+    fn test_parse_x11_color() {
+        assert_eq!(parse_x11_color(b"rgb:ffff/8080/0000"), Some((255, 128, 0)));
+        assert_eq!(parse_x11_color(b"rgb:ff/80/00"), Some((255, 128, 0)));
+        assert_eq!(parse_x11_color(b"rgb:f/8/0"), Some((255, 136, 0)));
+        assert_eq!(parse_x11_color(b"rgb:fff/888/000"), Some((255, 136, 0)));
+        assert_eq!(parse_x11_color(b"rgb:ff/80"), None);
+        assert_eq!(parse_x11_color(b"rgb:ff/80/00/00"), None);
+        assert_eq!(parse_x11_color(b"rgb:fffff/0/0"), None);
+        assert_eq!(parse_x11_color(b"#ff8000"), None);
+    }
+}
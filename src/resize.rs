@@ -0,0 +1,46 @@
+use std::io;
+use std::sync::mpsc::{self, Receiver};
+
+use sys::signal::{self, Signal};
+use sys::size::terminal_size;
+
+/// Call `callback` with the new size of the terminal whenever it is resized.
+///
+/// This installs a `SIGWINCH` handler, which only performs async-signal-safe operations; the
+/// callback itself is called from a background thread. Handlers installed before are still
+/// called.
+///
+/// # Example
+///
+/// ```rust,no_run
+/// termion::on_resize(|width, height| {
+///     eprintln!("The terminal is now {}×{}.", width, height);
+/// }).unwrap();
+/// ```
+pub fn on_resize<F>(mut callback: F) -> io::Result<()>
+    where F: FnMut(u16, u16) + Send + 'static
+{
+    signal::subscribe(Signal::Resize,
+                      Box::new(move || {
+                          if let Ok((width, height)) = terminal_size() {
+                              callback(width, height);
+                          }
+                          true
+                      }))
+}
+
+/// Receive the new size of the terminal whenever it is resized.
+///
+/// See `on_resize`. Once the receiver is dropped, the handler is unsubscribed at the next resize,
+/// when it fails to send the size.
+pub fn resize_events() -> io::Result<Receiver<(u16, u16)>> {
+    let (send, recv) = mpsc::channel();
+
+    signal::subscribe(Signal::Resize,
+                      Box::new(move || match terminal_size() {
+                          Ok(size) => send.send(size).is_ok(),
+                          Err(_) => true,
+                      }))?;
+
+    Ok(recv)
+}
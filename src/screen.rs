@@ -5,7 +5,8 @@
 //!
 //! # Example
 //!
-//! ```rust
+#![cfg_attr(feature = "std", doc = "```rust")]
+#![cfg_attr(not(feature = "std"), doc = "```rust,ignore")]
 //! use termion::screen::AlternateScreen;
 //! use std::io::{Write, stdout};
 //!
@@ -18,17 +19,36 @@
 //!     println!("Writing to main screen.");
 //! }
 //! ```
+//!
+//! On the alternate screen, the mouse wheel can't scroll back through the terminal's history.
+//! With mouse reporting on (see `input::MouseTerminal`), the wheel is reported as mouse events;
+//! otherwise, alternate scroll mode makes it send arrow keys instead, which suits pagers reading
+//! only keys.
+//!
+//! Either screen can also be shown in reverse video, swapping the foreground and background
+//! colors of every cell at once (see `ReverseScreenTerminal`), for a flash or a high-contrast
+//! toggle that needs no redraw.
 
+#[cfg(feature = "std")]
 use std::io::{self, Write};
+#[cfg(feature = "std")]
 use std::ops;
 use std::fmt;
+#[cfg(feature = "std")]
+use raw::ExitGuard;
+
+/// The escape code to switch to the alternate screen buffer.
+pub(crate) const ENTER_ALTERNATE_SCREEN_SEQUENCE: &str = csi!("?1049h");
+
+/// The escape code to switch back to the main screen buffer.
+pub(crate) const EXIT_ALTERNATE_SCREEN_SEQUENCE: &str = csi!("?1049l");
 
 /// Switch to the main screen buffer of the terminal.
 pub struct ToMainScreen;
 
 impl fmt::Display for ToMainScreen {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, csi!("?1049l"))
+        f.write_str(EXIT_ALTERNATE_SCREEN_SEQUENCE)
     }
 }
 
@@ -37,35 +57,70 @@ pub struct ToAlternateScreen;
 
 impl fmt::Display for ToAlternateScreen {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, csi!("?1049h"))
+        f.write_str(ENTER_ALTERNATE_SCREEN_SEQUENCE)
     }
 }
 
+derive_csi_sequence!("Make the mouse wheel send up and down arrow keys on the alternate screen \
+                      (alternate scroll mode), unless mouse reporting is on.",
+                     EnableAlternateScroll,
+                     "?1007h");
+derive_csi_sequence!("Stop the mouse wheel from sending arrow keys on the alternate screen.",
+                     DisableAlternateScroll,
+                     "?1007l");
+
+derive_csi_sequence!("Reverse the colors of the whole screen (DECSCNM).", ReverseScreen, "?5h");
+derive_csi_sequence!("Show the screen in its normal colors again.", NormalScreen, "?5l");
+
 /// A terminal restorer, which wraps a type implementing Write, and causes all writes to be written
 /// to an alternate screen.
 ///
 /// This is achieved by switching the terminal to the alternate screen on creation and
 /// automatically switching it back to the original screen on drop.
+#[cfg(feature = "std")]
 pub struct AlternateScreen<W: Write> {
     /// The output target.
     output: W,
+    /// The switch back on exit, if `raw::restore_on_exit` was called.
+    on_exit: Option<ExitGuard>,
 }
 
+#[cfg(feature = "std")]
 impl<W: Write> AlternateScreen<W> {
     /// Create an alternate screen wrapper struct for the provided output and switch the terminal
     /// to the alternate screen.
     pub fn from(mut output: W) -> Self {
         write!(output, "{}", ToAlternateScreen).expect("switch to alternate screen");
-        AlternateScreen { output: output }
+        AlternateScreen {
+            output: output,
+            on_exit: ExitGuard::write(EXIT_ALTERNATE_SCREEN_SEQUENCE),
+        }
+    }
+
+    /// Choose whether the mouse wheel sends arrow keys on this screen when mouse reporting is
+    /// off, rather than nothing.
+    ///
+    /// Terminals differ on which is the default, so a pager relying on either should set it.
+    /// The mode only applies to the alternate screen, so it isn't restored on drop.
+    pub fn set_alternate_scroll(&mut self, enabled: bool) -> io::Result<()> {
+        if enabled {
+            write!(self.output, "{}", EnableAlternateScroll)?;
+        } else {
+            write!(self.output, "{}", DisableAlternateScroll)?;
+        }
+        self.output.flush()
     }
 }
 
+#[cfg(feature = "std")]
 impl<W: Write> Drop for AlternateScreen<W> {
     fn drop(&mut self) {
+        self.on_exit.take();
         write!(self, "{}", ToMainScreen).expect("switch to main screen");
     }
 }
 
+#[cfg(feature = "std")]
 impl<W: Write> ops::Deref for AlternateScreen<W> {
     type Target = W;
 
@@ -74,18 +129,123 @@ impl<W: Write> ops::Deref for AlternateScreen<W> {
     }
 }
 
+#[cfg(feature = "std")]
 impl<W: Write> ops::DerefMut for AlternateScreen<W> {
     fn deref_mut(&mut self) -> &mut W {
         &mut self.output
     }
 }
 
+#[cfg(feature = "std")]
 impl<W: Write> Write for AlternateScreen<W> {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
         self.output.write(buf)
     }
 
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
     fn flush(&mut self) -> io::Result<()> {
         self.output.flush()
     }
 }
+
+/// A terminal restorer, which shows the screen in its normal colors again when dropped.
+#[cfg(feature = "std")]
+pub struct ReverseScreenTerminal<W: Write> {
+    output: W,
+    reversed: bool,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ReverseScreenTerminal<W> {
+    /// Reverse the colors of the whole screen, or show it in its normal colors.
+    ///
+    /// Nothing is written if the screen is already shown this way.
+    pub fn set_reversed(&mut self, reversed: bool) -> io::Result<()> {
+        if reversed != self.reversed {
+            if reversed {
+                write!(self.output, "{}", ReverseScreen)?;
+            } else {
+                write!(self.output, "{}", NormalScreen)?;
+            }
+            self.reversed = reversed;
+        }
+        self.output.flush()
+    }
+
+    /// Is the screen shown in reverse video?
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> From<W> for ReverseScreenTerminal<W> {
+    fn from(output: W) -> ReverseScreenTerminal<W> {
+        ReverseScreenTerminal {
+            output,
+            reversed: false,
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Drop for ReverseScreenTerminal<W> {
+    fn drop(&mut self) {
+        self.set_reversed(false).expect("restore screen colors");
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::Deref for ReverseScreenTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::DerefMut for ReverseScreenTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for ReverseScreenTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_reverse_screen_terminal() {
+        let mut output = Vec::new();
+        {
+            let mut term = ReverseScreenTerminal::from(&mut output);
+            term.set_reversed(true).unwrap();
+            term.set_reversed(true).unwrap();
+            assert!(term.is_reversed());
+        }
+        assert_eq!(output, b"\x1B[?5h\x1B[?5l".to_vec());
+
+        let mut output = Vec::new();
+        drop(ReverseScreenTerminal::from(&mut output));
+        assert_eq!(output, b"");
+    }
+}
@@ -1,8 +1,24 @@
 //! Scrolling.
+//!
+//! Scrolling shifts the lines of the screen, or of the scroll region if one is set, in a single
+//! sequence: a log viewer can add a line at the bottom without rewriting the others.
+//!
+//! ```rust,no_run
+//! use termion::{cursor, scroll};
+//! use std::io::{Write, stdout};
+//!
+//! let mut stdout = stdout();
+//! // Keep a status line on row 1, and scroll the log below it.
+//! write!(stdout, "{}{}{}new log line", scroll::Region(2, 24), scroll::Up(1), cursor::Goto(1, 24))
+//!     .unwrap();
+//! write!(stdout, "{}", scroll::ResetRegion).unwrap();
+//! ```
 
 use std::fmt;
 
-/// Scroll up.
+/// Scroll up (SU): the lines move up, and blank ones appear at the bottom.
+///
+/// The cursor doesn't move. Scrolling up by 0 lines scrolls by 1.
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct Up(pub u16);
 
@@ -12,7 +28,9 @@ impl fmt::Display for Up {
     }
 }
 
-/// Scroll down.
+/// Scroll down (SD): the lines move down, and blank ones appear at the top.
+///
+/// The cursor doesn't move. Scrolling down by 0 lines scrolls by 1.
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct Down(pub u16);
 
@@ -21,3 +39,19 @@ impl fmt::Display for Down {
         write!(f, csi!("{}T"), self.0)
     }
 }
+
+/// Restrict scrolling to the rows from the first to the second, (1)-based and inclusive
+/// (DECSTBM).
+///
+/// The rows outside of the region are left alone by scrolling, and by line feeds at the bottom
+/// of the region. The cursor moves to the top left corner.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct Region(pub u16, pub u16);
+
+impl fmt::Display for Region {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, csi!("{};{}r"), self.0, self.1)
+    }
+}
+
+derive_csi_sequence!("Let scrolling shift the whole screen again.", ResetRegion, "r");
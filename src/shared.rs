@@ -0,0 +1,185 @@
+//! A terminal shared between threads.
+//!
+//! Writing to the same terminal from several threads can cut an escape sequence in two, so that
+//! the terminal gets e.g. half of a color change, then text of the other thread. A
+//! `SharedTerminal` holds its output behind a lock: `lock` gives a guard writing to it alone
+//! until dropped, and `write!` on a `&SharedTerminal` holds the lock for the whole formatted
+//! text.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::{clear, color, cursor};
+//! use termion::shared::SharedTerminal;
+//! use std::io::{stdout, Write};
+//! use std::sync::Arc;
+//! use std::thread;
+//!
+//! let term = Arc::new(SharedTerminal::new(stdout()));
+//!
+//! let log = term.clone();
+//! let logger = thread::spawn(move || {
+//!     write!(&*log, "{}{}started{}\r\n", cursor::Goto(1, 20), color::Fg(color::Green),
+//!            color::Fg(color::Reset)).unwrap();
+//! });
+//!
+//! let mut out = term.lock();
+//! write!(out, "{}{}", cursor::Goto(1, 1), clear::CurrentLine).unwrap();
+//! write!(out, "Rendering...").unwrap();
+//! out.flush().unwrap();
+//! drop(out);
+//!
+//! logger.join().unwrap();
+//! ```
+
+use std::fmt;
+use std::io::{self, Write};
+use std::ops;
+use std::sync::{Mutex, MutexGuard};
+
+/// An output which threads write to in turn.
+///
+/// Put it in an `Arc` to share it. A thread which panicked while writing doesn't keep the others
+/// from writing: they go on after what it wrote.
+pub struct SharedTerminal<W: Write> {
+    output: Mutex<W>,
+}
+
+impl<W: Write> SharedTerminal<W> {
+    /// Share `output`.
+    pub fn new(output: W) -> SharedTerminal<W> {
+        SharedTerminal { output: Mutex::new(output) }
+    }
+
+    /// Wait for the other threads to be done writing, and write alone until the guard is
+    /// dropped.
+    pub fn lock(&self) -> SharedTerminalGuard<'_, W> {
+        SharedTerminalGuard { output: self.output.lock().unwrap_or_else(|e| e.into_inner()) }
+    }
+
+    /// Stop sharing the output.
+    pub fn into_inner(self) -> W {
+        self.output.into_inner().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Writing to the shared output, locking it for every call.
+///
+/// `write_fmt`, which `write!` calls, locks it once for the whole text, so the sequences it
+/// writes are never cut.
+impl<W: Write> Write for &SharedTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.lock().write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.lock().write_vectored(bufs)
+    }
+
+    fn write_all(&mut self, buf: &[u8]) -> io::Result<()> {
+        self.lock().write_all(buf)
+    }
+
+    fn write_fmt(&mut self, args: fmt::Arguments) -> io::Result<()> {
+        self.lock().write_fmt(args)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.lock().flush()
+    }
+}
+
+/// The shared output, locked for a thread.
+pub struct SharedTerminalGuard<'a, W: Write + 'a> {
+    output: MutexGuard<'a, W>,
+}
+
+impl<'a, W: Write> ops::Deref for SharedTerminalGuard<'a, W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+impl<'a, W: Write> ops::DerefMut for SharedTerminalGuard<'a, W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+impl<'a, W: Write> Write for SharedTerminalGuard<'a, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::Arc;
+    use std::thread;
+    use color;
+
+    #[test]
+    fn test_threads() {
+        let term = Arc::new(SharedTerminal::new(Vec::new()));
+        let threads: Vec<_> = (0..4)
+            .map(|i| {
+                let term = term.clone();
+                thread::spawn(move || for _ in 0..100 {
+                    write!(&*term, "{}{}", color::Fg(color::AnsiValue(i)), i).unwrap();
+                    let mut out = term.lock();
+                    out.write_all(b"\x1B[").unwrap();
+                    out.write_all(b"m").unwrap();
+                })
+            })
+            .collect();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+
+        let out = Arc::try_unwrap(term).ok().unwrap().into_inner();
+        let out = String::from_utf8(out).unwrap();
+        let parts: Vec<&str> = out.split("\x1B[m").collect();
+        assert_eq!(parts.len(), 401);
+        for part in &parts[..400] {
+            let i = &part[part.len() - 1..];
+            assert_eq!(*part, format!("\x1B[38;5;{}m{}", i, i));
+        }
+    }
+    /// Keeps the slices of each vectored write.
+    struct Slices(Vec<Vec<u8>>);
+
+    impl Write for Slices {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.push(buf.to_vec());
+            Ok(buf.len())
+        }
+
+        fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+            self.0.extend(bufs.iter().map(|buf| buf.to_vec()));
+            Ok(bufs.iter().map(|buf| buf.len()).sum())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_vectored() {
+        let term = SharedTerminal::new(Slices(Vec::new()));
+        let bufs = [io::IoSlice::new(b"\x1B[1m"), io::IoSlice::new(b"a")];
+        assert_eq!((&term).write_vectored(&bufs).unwrap(), 5);
+        assert_eq!(term.into_inner().0, vec![b"\x1B[1m".to_vec(), b"a".to_vec()]);
+    }
+}
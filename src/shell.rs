@@ -0,0 +1,153 @@
+//! Shell integration markers (FinalTerm, OSC 133).
+//!
+//! A shell or REPL writing these around its prompts and commands lets the terminal tell them
+//! apart from the output: terminals supporting them (iTerm2, kitty, WezTerm, foot, VS Code, ...)
+//! can then jump between prompts, select the output of a command, or report how long it took and
+//! whether it failed. Other terminals ignore them.
+//!
+//! `report_cwd` tells the terminal the current directory of the shell (OSC 7), so that a new tab
+//! or window can open in the same directory.
+//!
+//! # Example
+//!
+#![cfg_attr(feature = "std", doc = "```rust,no_run")]
+#![cfg_attr(not(feature = "std"), doc = "```rust,ignore")]
+//! use termion::shell::{report_cwd, CommandExecuted, CommandFinished, CommandStart, PromptStart};
+//! use std::env;
+//! use std::io::{self, BufRead, Write};
+//!
+//! let stdin = io::stdin();
+//! let mut stdout = io::stdout();
+//!
+//! report_cwd(&mut stdout, &env::current_dir().unwrap()).unwrap();
+//! write!(stdout, "{}> {}", PromptStart, CommandStart).unwrap();
+//! stdout.flush().unwrap();
+//!
+//! let mut line = String::new();
+//! stdin.lock().read_line(&mut line).unwrap();
+//! write!(stdout, "{}", CommandExecuted).unwrap();
+//!
+//! let status = if line.trim() == "ok" { 0 } else { 1 };
+//! write!(stdout, "{}", CommandFinished(Some(status))).unwrap();
+//! ```
+
+#[cfg(feature = "std")]
+use std::borrow::Cow;
+use std::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::path::Path;
+
+#[cfg(feature = "std")]
+use sys::host::hostname;
+
+derive_esc_sequence!("Mark the start of a prompt.", PromptStart, "]133;A\x07");
+derive_esc_sequence!("Mark the end of a prompt, where the command typed starts.",
+                     CommandStart,
+                     "]133;B\x07");
+derive_esc_sequence!("Mark the end of the command typed, where its output starts.",
+                     CommandExecuted,
+                     "]133;C\x07");
+
+/// Mark the end of the output of a command, with its exit status if it has one.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CommandFinished(pub Option<i32>);
+
+impl fmt::Display for CommandFinished {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.0 {
+            Some(status) => write!(f, "\x1B]133;D;{}\x07", status),
+            None => f.write_str("\x1B]133;D\x07"),
+        }
+    }
+}
+
+/// Tell the terminal the current directory, as a `file://` URL (OSC 7).
+///
+/// The host name and the path are percent-encoded. The path should be absolute.
+#[cfg(feature = "std")]
+#[derive(Copy, Clone, Debug)]
+pub struct CurrentDir<'a> {
+    /// The name of the host the directory is on.
+    pub host: &'a str,
+    /// The directory.
+    pub path: &'a Path,
+}
+
+#[cfg(feature = "std")]
+impl<'a> fmt::Display for CurrentDir<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\x1B]7;file://")?;
+        percent_encode(f, self.host.as_bytes(), false)?;
+        percent_encode(f, &path_bytes(self.path), true)?;
+        f.write_str("\x07")
+    }
+}
+
+/// Tell the terminal `stdout` writes to that the current directory is `path`, on this host.
+///
+/// If the host name can't be found, the URL has none, which stands for the local host.
+#[cfg(feature = "std")]
+pub fn report_cwd<W: Write>(stdout: &mut W, path: &Path) -> io::Result<()> {
+    let host = hostname().unwrap_or_default();
+    write!(stdout, "{}", CurrentDir { host: &host, path })?;
+    stdout.flush()
+}
+
+/// Write `bytes`, escaping those which can't be in a URL path as is.
+#[cfg(feature = "std")]
+fn percent_encode(f: &mut fmt::Formatter, bytes: &[u8], keep_slash: bool) -> fmt::Result {
+    for &b in bytes {
+        match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => {
+                write!(f, "{}", b as char)?
+            }
+            b'/' if keep_slash => f.write_str("/")?,
+            _ => write!(f, "%{:02X}", b)?,
+        }
+    }
+    Ok(())
+}
+
+#[cfg(all(feature = "std", unix))]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    use std::os::unix::ffi::OsStrExt;
+    Cow::Borrowed(path.as_os_str().as_bytes())
+}
+
+#[cfg(all(feature = "std", not(unix)))]
+fn path_bytes(path: &Path) -> Cow<'_, [u8]> {
+    match path.to_string_lossy() {
+        Cow::Borrowed(s) => Cow::Borrowed(s.as_bytes()),
+        Cow::Owned(s) => Cow::Owned(s.into_bytes()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_markers() {
+        assert_eq!(PromptStart.to_string(), "\x1B]133;A\x07");
+        assert_eq!(CommandStart.to_string(), "\x1B]133;B\x07");
+        assert_eq!(CommandExecuted.to_string(), "\x1B]133;C\x07");
+        assert_eq!(CommandFinished(Some(127)).to_string(), "\x1B]133;D;127\x07");
+        assert_eq!(CommandFinished(None).to_string(), "\x1B]133;D\x07");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_current_dir() {
+        let dir = CurrentDir { host: "build-01", path: Path::new("/home/me/My Notes/100%/été") };
+        assert_eq!(dir.to_string(),
+                   "\x1B]7;file://build-01/home/me/My%20Notes/100%25/%C3%A9t%C3%A9\x07");
+
+        let mut out = Vec::new();
+        report_cwd(&mut out, Path::new("/tmp")).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\x1B]7;file://"));
+        assert!(out.ends_with("/tmp\x07"));
+    }
+}
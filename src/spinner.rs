@@ -0,0 +1,192 @@
+//! A spinner, for work whose progress is unknown.
+//!
+//! The spinner is drawn on the current line, and turns to its next frame when `tick` is called
+//! after its interval has passed, so it can be ticked from the loop doing the work. When the
+//! output is not a terminal, where an animation would only fill a log with carriage returns, the
+//! message is printed once as a line of its own instead.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::spinner::Spinner;
+//! use std::io::stdout;
+//!
+//! let mut spinner = Spinner::new(stdout(), "resolving");
+//! for _ in 0..1000 {
+//!     // Do a bit of the work...
+//!     spinner.tick().unwrap();
+//! }
+//! spinner.finish_with("resolved").unwrap();
+//! ```
+
+use std::io::{self, Write};
+use std::time::{Duration, Instant};
+
+use clear;
+use cursor;
+use is_tty;
+use sys::AsRawFd;
+
+/// A line turning around, which any terminal can draw.
+pub const LINE: &[&str] = &["-", "\\", "|", "/"];
+/// Braille dots running around a square.
+pub const DOTS: &[&str] = &["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+
+/// The time between two frames, unless another is set.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A spinner drawn on the current line of `W`, next to a message.
+///
+/// The cursor is hidden while the spinner is shown. Dropping the spinner erases it, as
+/// `finish` does.
+pub struct Spinner<W: Write> {
+    output: W,
+    message: String,
+    frames: Vec<String>,
+    frame: usize,
+    interval: Duration,
+    last_frame: Option<Instant>,
+    animated: bool,
+    shown: bool,
+}
+
+impl<W: Write + AsRawFd> Spinner<W> {
+    /// A spinner showing `message` on `output`, animated if `output` is a terminal.
+    pub fn new(output: W, message: &str) -> Spinner<W> {
+        let animated = is_tty(&output);
+        Spinner::with_animation(output, message, animated)
+    }
+}
+
+impl<W: Write> Spinner<W> {
+    /// A spinner showing `message` on `output`, animated or printed as static text.
+    pub fn with_animation(output: W, message: &str, animated: bool) -> Spinner<W> {
+        Spinner {
+            output,
+            message: message.to_string(),
+            frames: LINE.iter().map(|frame| frame.to_string()).collect(),
+            frame: 0,
+            interval: DEFAULT_INTERVAL,
+            last_frame: None,
+            animated,
+            shown: false,
+        }
+    }
+
+    /// Draw `frames` in turn, rather than `LINE`.
+    pub fn set_frames(&mut self, frames: &[&str]) {
+        self.frames = frames.iter().map(|frame| frame.to_string()).collect();
+        self.frame = 0;
+    }
+
+    /// Turn to the next frame every `interval`, rather than every `DEFAULT_INTERVAL`.
+    pub fn set_interval(&mut self, interval: Duration) {
+        self.interval = interval;
+    }
+
+    /// Is the spinner animated, rather than printed as static text?
+    pub fn is_animated(&self) -> bool {
+        self.animated
+    }
+
+    /// Show the spinner, or turn it to its next frame if its interval has passed since the
+    /// last one.
+    pub fn tick(&mut self) -> io::Result<()> {
+        match self.last_frame {
+            Some(last) if last.elapsed() < self.interval => Ok(()),
+            Some(_) if !self.animated => Ok(()),
+            Some(_) => {
+                self.frame = (self.frame + 1) % self.frames.len().max(1);
+                self.draw()
+            }
+            None => self.draw(),
+        }
+    }
+
+    /// Show `message` next to the spinner.
+    ///
+    /// When static, the message is printed on a line of its own.
+    pub fn set_message(&mut self, message: &str) -> io::Result<()> {
+        self.message = message.to_string();
+        self.draw()
+    }
+
+    /// Erase the spinner and show the cursor again.
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.shown && self.animated {
+            write!(self.output, "\r{}{}", clear::CurrentLine, cursor::Show)?;
+        }
+        self.shown = false;
+        self.output.flush()
+    }
+
+    /// Replace the spinner by the line `message`.
+    pub fn finish_with(&mut self, message: &str) -> io::Result<()> {
+        self.finish()?;
+        if self.animated {
+            write!(self.output, "{}\r\n", message)?;
+        } else {
+            writeln!(self.output, "{}", message)?;
+        }
+        self.output.flush()
+    }
+
+    fn draw(&mut self) -> io::Result<()> {
+        self.last_frame = Some(Instant::now());
+        if self.animated {
+            if !self.shown {
+                write!(self.output, "{}", cursor::Hide)?;
+            }
+            let frame = self.frames.get(self.frame).map_or("", |frame| &frame[..]);
+            write!(self.output, "\r{} {}{}", frame, self.message, clear::UntilNewline)?;
+        } else {
+            writeln!(self.output, "{}", self.message)?;
+        }
+        self.shown = true;
+        self.output.flush()
+    }
+}
+
+impl<W: Write> Drop for Spinner<W> {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn output(spinner: &mut Spinner<Vec<u8>>) -> String {
+        String::from_utf8(spinner.output.split_off(0)).unwrap()
+    }
+
+    #[test]
+    fn test_animated() {
+        let mut spinner = Spinner::with_animation(Vec::new(), "wait", true);
+        spinner.set_interval(Duration::from_secs(0));
+        spinner.tick().unwrap();
+        assert_eq!(output(&mut spinner), "\x1B[?25l\r- wait\x1B[K");
+        spinner.tick().unwrap();
+        spinner.set_message("more").unwrap();
+        assert_eq!(output(&mut spinner), "\r\\ wait\x1B[K\r\\ more\x1B[K");
+
+        spinner.set_interval(Duration::from_secs(60));
+        spinner.tick().unwrap();
+        assert_eq!(output(&mut spinner), "");
+
+        spinner.finish_with("done").unwrap();
+        assert_eq!(output(&mut spinner), "\r\x1B[2K\x1B[?25hdone\r\n");
+    }
+
+    #[test]
+    fn test_static() {
+        let mut spinner = Spinner::with_animation(Vec::new(), "wait", false);
+        spinner.set_interval(Duration::from_secs(0));
+        spinner.tick().unwrap();
+        spinner.tick().unwrap();
+        spinner.set_message("more").unwrap();
+        spinner.finish_with("done").unwrap();
+        assert_eq!(output(&mut spinner), "wait\nmore\ndone\n");
+    }
+}
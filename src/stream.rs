@@ -0,0 +1,426 @@
+//! Input events for async code, whatever the runtime.
+//!
+//! `EventStream` parses the input of an `AsyncSource` into events. The source is the only part
+//! which depends on the runtime: `ThreadSource` reads from a background thread and only relies
+//! on `std::task`, so it works under any executor, and a runtime's readiness API (such as tokio's
+//! `AsyncFd` or `async-io`'s `Async`, used by async-std and smol) can be plugged in by
+//! implementing `AsyncSource`.
+//!
+//! With the `tokio` feature, `TokioSource` reads the TTY device through tokio's `AsyncFd`, and
+//! with the `async-io` feature, `AsyncIoSource` reads it through `async-io`, the reactor of
+//! async-std and smol. Either avoids the thread.
+//!
+//! # Example
+//!
+//! ```rust,no_run,edition2018
+//! use termion::event::{Event, Key};
+//! use termion::stream::{EventStream, ThreadSource};
+//!
+//! async fn run() -> std::io::Result<()> {
+//!     let mut events = EventStream::new(ThreadSource::tty()?);
+//!     while let Some(event) = events.next_event().await {
+//!         if event? == Event::Key(Key::Char('q')) {
+//!             break;
+//!         }
+//!     }
+//!     Ok(())
+//! }
+//! ```
+
+#[cfg(all(feature = "async-io", unix))]
+extern crate async_io;
+#[cfg(all(feature = "tokio", unix))]
+extern crate tokio;
+
+#[cfg(all(any(feature = "tokio", feature = "async-io"), unix))]
+use std::fs::File;
+use std::future::Future;
+use std::io::{self, Read};
+#[cfg(all(feature = "async-io", unix))]
+use std::os::unix::io::AsFd;
+#[cfg(all(feature = "tokio", unix))]
+use std::os::unix::io::AsRawFd;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::thread;
+
+use event::Event;
+use input::{Events, InputBuffer};
+use sys::tty::get_tty;
+#[cfg(all(feature = "tokio", unix))]
+use sys::poll::set_nonblocking;
+#[cfg(all(feature = "async-io", unix))]
+use self::async_io::Async;
+#[cfg(all(feature = "tokio", unix))]
+use self::tokio::io::unix::AsyncFd;
+
+/// A source of input bytes for async code, which registers the task to wake up when it has
+/// no input yet.
+///
+/// This is the shape of `AsyncRead::poll_read` in tokio and futures.
+pub trait AsyncSource {
+    /// Read the input available into `buf`, returning the number of bytes read, or 0 once the
+    /// input has ended.
+    ///
+    /// When no input is available, the waker of `cx` is to be woken once some is, and
+    /// `Poll::Pending` returned.
+    fn poll_read(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>>;
+}
+
+impl<S: AsyncSource + ?Sized> AsyncSource for &mut S {
+    fn poll_read(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        (**self).poll_read(cx, buf)
+    }
+}
+
+/// What the reading thread of a `ThreadSource` shares with it.
+struct Shared {
+    /// The bytes read but not taken yet.
+    buf: Vec<u8>,
+    /// The error the thread stopped at, or `None` if it stopped at the end of the input.
+    error: Option<io::Error>,
+    ended: bool,
+    /// The task waiting for input.
+    waker: Option<Waker>,
+}
+
+/// A source reading its input from a background thread, which works under any executor.
+pub struct ThreadSource {
+    shared: Arc<Mutex<Shared>>,
+}
+
+impl ThreadSource {
+    /// Read the TTY device from a background thread.
+    pub fn tty() -> io::Result<ThreadSource> {
+        Ok(ThreadSource::new(get_tty()?))
+    }
+
+    /// Read `input` from a background thread.
+    ///
+    /// The thread stops at the end of the input, at the first error, or once a read returns
+    /// after this source was dropped.
+    pub fn new<R: Read + Send + 'static>(mut input: R) -> ThreadSource {
+        let shared = Arc::new(Mutex::new(Shared {
+            buf: Vec::new(),
+            error: None,
+            ended: false,
+            waker: None,
+        }));
+
+        let thread_shared = Arc::downgrade(&shared);
+        thread::spawn(move || {
+            let mut buf = [0u8; 1024];
+            loop {
+                let result = input.read(&mut buf);
+                let shared = match thread_shared.upgrade() {
+                    Some(shared) => shared,
+                    None => return,
+                };
+                let mut shared = shared.lock().unwrap_or_else(|e| e.into_inner());
+                let done = match result {
+                    Ok(0) => true,
+                    Ok(n) => {
+                        shared.buf.extend_from_slice(&buf[..n]);
+                        false
+                    }
+                    Err(ref e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                    Err(e) => {
+                        shared.error = Some(e);
+                        true
+                    }
+                };
+                shared.ended = done;
+                if let Some(waker) = shared.waker.take() {
+                    waker.wake();
+                }
+                if done {
+                    return;
+                }
+            }
+        });
+
+        ThreadSource { shared }
+    }
+}
+
+impl AsyncSource for ThreadSource {
+    fn poll_read(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let mut shared = self.shared.lock().unwrap_or_else(|e| e.into_inner());
+
+        if !shared.buf.is_empty() {
+            let n = buf.len().min(shared.buf.len());
+            buf[..n].copy_from_slice(&shared.buf[..n]);
+            shared.buf.drain(..n);
+            Poll::Ready(Ok(n))
+        } else if let Some(e) = shared.error.take() {
+            Poll::Ready(Err(e))
+        } else if shared.ended {
+            Poll::Ready(Ok(0))
+        } else {
+            shared.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// A source reading a file descriptor through tokio's `AsyncFd`, without a thread.
+///
+/// It has to be created within a tokio runtime which has its IO driver enabled.
+#[cfg(all(feature = "tokio", unix))]
+pub struct TokioSource<T: AsRawFd = File> {
+    input: AsyncFd<T>,
+}
+
+#[cfg(all(feature = "tokio", unix))]
+impl TokioSource {
+    /// Read the TTY device.
+    pub fn tty() -> io::Result<TokioSource> {
+        TokioSource::new(get_tty()?)
+    }
+}
+
+#[cfg(all(feature = "tokio", unix))]
+impl<T: AsRawFd> TokioSource<T> {
+    /// Read `input`, which is made non-blocking.
+    pub fn new(input: T) -> io::Result<TokioSource<T>> {
+        set_nonblocking(input.as_raw_fd())?;
+        Ok(TokioSource { input: AsyncFd::new(input)? })
+    }
+}
+
+#[cfg(all(feature = "tokio", unix))]
+impl<T: AsRawFd> AsyncSource for TokioSource<T>
+    where for<'a> &'a T: Read
+{
+    fn poll_read(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = match self.input.poll_read_ready(cx) {
+                Poll::Ready(Ok(guard)) => guard,
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            };
+            if let Ok(result) = guard.try_io(|input| input.get_ref().read(buf)) {
+                return Poll::Ready(result);
+            }
+        }
+    }
+}
+
+/// A source reading a file descriptor through `async-io`, the reactor of async-std and smol,
+/// without a thread.
+#[cfg(all(feature = "async-io", unix))]
+pub struct AsyncIoSource<T: AsFd = File> {
+    input: Async<T>,
+}
+
+#[cfg(all(feature = "async-io", unix))]
+impl AsyncIoSource {
+    /// Read the TTY device.
+    pub fn tty() -> io::Result<AsyncIoSource> {
+        AsyncIoSource::new(get_tty()?)
+    }
+}
+
+#[cfg(all(feature = "async-io", unix))]
+impl<T: AsFd> AsyncIoSource<T> {
+    /// Read `input`, which is made non-blocking.
+    pub fn new(input: T) -> io::Result<AsyncIoSource<T>> {
+        Ok(AsyncIoSource { input: Async::new(input)? })
+    }
+}
+
+#[cfg(all(feature = "async-io", unix))]
+impl<T: AsFd> AsyncSource for AsyncIoSource<T>
+    where for<'a> &'a T: Read
+{
+    fn poll_read(&mut self, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        loop {
+            match self.input.get_ref().read(buf) {
+                Err(ref e) if e.kind() == io::ErrorKind::WouldBlock => {}
+                result => return Poll::Ready(result),
+            }
+            match self.input.poll_readable(cx) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(e)) => return Poll::Ready(Err(e)),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// An asynchronous stream of input events, parsed from the input of an `AsyncSource`.
+///
+/// The stream ends once the input has ended and the events read before were yielded.
+pub struct EventStream<S> {
+    source: S,
+    events: Events<InputBuffer>,
+    ended: bool,
+}
+
+impl<S: AsyncSource> EventStream<S> {
+    /// Parse the input of `source` into events.
+    pub fn new(source: S) -> EventStream<S> {
+        EventStream {
+            source,
+            events: InputBuffer::new().events(),
+            ended: false,
+        }
+    }
+
+    /// The source of the input.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    /// Poll for the next event, like `Stream::poll_next` of the futures crate.
+    pub fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<io::Result<Event>>> {
+        let mut buf = [0u8; 1024];
+
+        loop {
+            if let Some(event) = self.events.next() {
+                return Poll::Ready(Some(event));
+            }
+            if self.ended {
+                return Poll::Ready(None);
+            }
+
+            match self.source.poll_read(cx, &mut buf) {
+                Poll::Ready(Ok(0)) => self.ended = true,
+                Poll::Ready(Ok(n)) => self.events.get_mut().buf.extend(&buf[..n]),
+                Poll::Ready(Err(e)) => return Poll::Ready(Some(Err(e))),
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    /// Wait for the next event, or `None` once the stream has ended.
+    pub fn next_event(&mut self) -> NextEvent<'_, S> {
+        NextEvent { stream: self }
+    }
+}
+
+/// The future returned by `EventStream::next_event`.
+pub struct NextEvent<'a, S: 'a> {
+    stream: &'a mut EventStream<S>,
+}
+
+impl<'a, S: AsyncSource> Future for NextEvent<'a, S> {
+    type Output = Option<io::Result<Event>>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<io::Result<Event>>> {
+        self.stream.poll_next(cx)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use event::Key;
+    #[cfg(all(any(feature = "tokio", feature = "async-io"), unix))]
+    use std::io::Write;
+    #[cfg(all(any(feature = "tokio", feature = "async-io"), unix))]
+    use std::os::unix::net::UnixStream;
+    use std::sync::mpsc;
+    use std::task::Wake;
+    #[cfg(all(feature = "tokio", unix))]
+    use super::tokio::runtime;
+
+    /// Wakes the test up by sending to a channel.
+    struct ChannelWaker(Mutex<mpsc::Sender<()>>);
+
+    impl Wake for ChannelWaker {
+        fn wake(self: Arc<Self>) {
+            let _ = self.0.lock().unwrap().send(());
+        }
+    }
+
+    fn block_on<F: Future>(future: F) -> F::Output {
+        let (send, recv) = mpsc::channel();
+        let waker = Waker::from(Arc::new(ChannelWaker(Mutex::new(send))));
+        let mut cx = Context::from_waker(&waker);
+        let mut future = Box::pin(future);
+        loop {
+            if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+                return output;
+            }
+            recv.recv().unwrap();
+        }
+    }
+
+    #[test]
+    fn test_event_stream() {
+        let (send, recv) = mpsc::channel::<&'static [u8]>();
+        struct Chunks(mpsc::Receiver<&'static [u8]>);
+        impl Read for Chunks {
+            fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+                match self.0.recv() {
+                    Ok(chunk) => {
+                        buf[..chunk.len()].copy_from_slice(chunk);
+                        Ok(chunk.len())
+                    }
+                    Err(_) => Ok(0),
+                }
+            }
+        }
+
+        let mut events = EventStream::new(ThreadSource::new(Chunks(recv)));
+        send.send(b"a\x1B[A").unwrap();
+        assert_eq!(block_on(events.next_event()).unwrap().unwrap(), Event::Key(Key::Char('a')));
+        assert_eq!(block_on(events.next_event()).unwrap().unwrap(), Event::Key(Key::Up));
+
+        thread::spawn(move || {
+            thread::sleep(::std::time::Duration::from_millis(10));
+            send.send(b"b").unwrap();
+        });
+        assert_eq!(block_on(events.next_event()).unwrap().unwrap(), Event::Key(Key::Char('b')));
+        assert!(block_on(events.next_event()).is_none());
+    }
+
+    /// Write `b` to `output` after a while, and close it.
+    #[cfg(all(any(feature = "tokio", feature = "async-io"), unix))]
+    fn write_later(mut output: UnixStream) {
+        thread::spawn(move || {
+            thread::sleep(::std::time::Duration::from_millis(10));
+            output.write_all(b"b").unwrap();
+        });
+    }
+
+    #[cfg(all(feature = "tokio", unix))]
+    #[test]
+    fn test_tokio_source() {
+        let runtime = runtime::Builder::new_current_thread().enable_io().build().unwrap();
+        let _runtime = runtime.enter();
+        let (input, mut output) = UnixStream::pair().unwrap();
+        let mut events = EventStream::new(TokioSource::new(input).unwrap());
+
+        output.write_all(b"a\x1B[A").unwrap();
+        let event = runtime.block_on(events.next_event()).unwrap().unwrap();
+        assert_eq!(event, Event::Key(Key::Char('a')));
+        let event = runtime.block_on(events.next_event()).unwrap().unwrap();
+        assert_eq!(event, Event::Key(Key::Up));
+
+        write_later(output);
+        let event = runtime.block_on(events.next_event()).unwrap().unwrap();
+        assert_eq!(event, Event::Key(Key::Char('b')));
+        assert!(runtime.block_on(events.next_event()).is_none());
+    }
+
+    #[cfg(all(feature = "async-io", unix))]
+    #[test]
+    fn test_async_io_source() {
+        let (input, mut output) = UnixStream::pair().unwrap();
+        let mut events = EventStream::new(AsyncIoSource::new(input).unwrap());
+
+        output.write_all(b"a\x1B[A").unwrap();
+        let event = async_io::block_on(events.next_event()).unwrap().unwrap();
+        assert_eq!(event, Event::Key(Key::Char('a')));
+        let event = async_io::block_on(events.next_event()).unwrap().unwrap();
+        assert_eq!(event, Event::Key(Key::Up));
+
+        write_later(output);
+        let event = async_io::block_on(events.next_event()).unwrap().unwrap();
+        assert_eq!(event, Event::Key(Key::Char('b')));
+        assert!(async_io::block_on(events.next_event()).is_none());
+    }
+}
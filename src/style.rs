@@ -1,6 +1,9 @@
 //! Text styling management.
 
-use std::fmt;
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::ops;
 
 derive_csi_sequence!("Reset SGR parameters.", Reset, "m");
 derive_csi_sequence!("Bold text.", Bold, "1m");
@@ -20,3 +23,149 @@ derive_csi_sequence!("Undo crossed out text (not widely supported).",
                      NoCrossedOut,
                      "29m");
 derive_csi_sequence!("Framed text (not widely supported).", Framed, "51m");
+
+/// A writer resetting the graphic rendition when dropped, if it was styled.
+///
+/// Write styles and colors through the guard: whichever way the function writing them returns,
+/// the guard then writes `Reset`, so that they don't leak into the output which follows. Nothing
+/// is written if no escape sequence went through the guard.
+///
+/// ```rust
+/// use termion::{color, style};
+/// use termion::style::StyleGuard;
+/// use std::io::{self, Write};
+///
+/// fn warn<W: Write>(out: W, message: &str) -> io::Result<()> {
+///     let mut out = StyleGuard::new(out);
+///     write!(out, "{}{}warning:{} ", style::Bold, color::Fg(color::Yellow), style::NoBold)?;
+///     // Even if this fails, the yellow doesn't stay on.
+///     writeln!(out, "{}", message)
+/// }
+///
+/// let mut stdout = io::stdout();
+/// warn(&mut stdout, "careful").unwrap();
+/// ```
+#[cfg(feature = "std")]
+pub struct StyleGuard<W: Write> {
+    output: W,
+    /// Was an escape sequence written since the last reset?
+    styled: bool,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> StyleGuard<W> {
+    /// Guard the styles written to `output`.
+    pub fn new(output: W) -> StyleGuard<W> {
+        StyleGuard {
+            output,
+            styled: false,
+        }
+    }
+
+    /// Was an escape sequence written through the guard since the last reset?
+    pub fn is_styled(&self) -> bool {
+        self.styled
+    }
+
+    /// Reset the graphic rendition now, if it was styled.
+    pub fn reset(&mut self) -> io::Result<()> {
+        if self.styled {
+            write!(self.output, "{}", Reset)?;
+            self.styled = false;
+        }
+        self.output.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> From<W> for StyleGuard<W> {
+    fn from(output: W) -> StyleGuard<W> {
+        StyleGuard::new(output)
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Drop for StyleGuard<W> {
+    fn drop(&mut self) {
+        // The guard is often dropped returning a write error, so a failing reset is ignored.
+        let _ = self.reset();
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::Deref for StyleGuard<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::DerefMut for StyleGuard<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for StyleGuard<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.output.write(buf)?;
+        if buf[..written].contains(&b'\x1B') {
+            self.styled = true;
+        }
+        Ok(written)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        let written = self.output.write_vectored(bufs)?;
+        let mut left = written;
+        for buf in bufs {
+            let n = left.min(buf.len());
+            if buf[..n].contains(&b'\x1B') {
+                self.styled = true;
+            }
+            left -= n;
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_style_guard() {
+        let mut out = Vec::new();
+        {
+            let mut guard = StyleGuard::new(&mut out);
+            write!(guard, "plain").unwrap();
+        }
+        assert_eq!(out, b"plain".to_vec());
+
+        let mut out = Vec::new();
+        {
+            let mut guard = StyleGuard::new(&mut out);
+            write!(guard, "{}bold", Bold).unwrap();
+            guard.reset().unwrap();
+            assert!(!guard.is_styled());
+            write!(guard, "{}italic", Italic).unwrap();
+        }
+        assert_eq!(out, b"\x1B[1mbold\x1B[m\x1B[3mitalic\x1B[m".to_vec());
+
+        let mut out = Vec::new();
+        {
+            let mut guard = StyleGuard::new(&mut out);
+            let bufs = [io::IoSlice::new(b"plain "), io::IoSlice::new(b"\x1B[1mbold")];
+            assert_eq!(guard.write_vectored(&bufs).unwrap(), 14);
+            assert!(guard.is_styled());
+        }
+        assert_eq!(out, b"plain \x1B[1mbold\x1B[m".to_vec());
+    }
+}
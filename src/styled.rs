@@ -0,0 +1,231 @@
+//! Text carrying its own style.
+//!
+//! A `StyledStr` is made of spans of text, each with its colors and attributes. Rather than
+//! strings with escapes baked in, which can't be measured, cut or restyled, applications can
+//! pass `StyledStr`s around and have the escapes written only when they are displayed.
+//!
+//! # Example
+//!
+//! ```rust
+//! use termion::buffer::{CellColor, CellStyle};
+//! use termion::styled::StyledStr;
+//!
+//! let red = CellStyle { fg: CellColor::Ansi(1), ..CellStyle::default() };
+//! let text = StyledStr::styled("error", red) + ": file not found";
+//!
+//! assert_eq!(text.width(), 21);
+//! assert_eq!(text.slice(0, 7).to_string(), "\x1B[38;5;1merror\x1B[0m: ");
+//! ```
+
+use std::fmt;
+use std::ops::{Add, AddAssign};
+use std::str;
+
+use buffer::CellStyle;
+use width::{grapheme_width, graphemes, str_width};
+
+/// A piece of text drawn in a single style.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Span {
+    text: String,
+    style: CellStyle,
+}
+
+impl Span {
+    /// Draw `text` with `style`.
+    pub fn new<T: Into<String>>(text: T, style: CellStyle) -> Span {
+        Span {
+            text: text.into(),
+            style,
+        }
+    }
+
+    /// The text of the span.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// The style of the span.
+    pub fn style(&self) -> CellStyle {
+        self.style
+    }
+
+    /// The number of columns the span takes.
+    pub fn width(&self) -> usize {
+        str_width(&self.text)
+    }
+}
+
+/// Text made of spans of different styles, whose escapes are written when it is displayed.
+///
+/// Displaying it assumes the text around it is drawn in the default style, and restores that
+/// style at the end.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StyledStr {
+    spans: Vec<Span>,
+}
+
+impl StyledStr {
+    /// An empty text.
+    pub fn new() -> StyledStr {
+        StyledStr::default()
+    }
+
+    /// `text` drawn with `style`.
+    pub fn styled<T: Into<String>>(text: T, style: CellStyle) -> StyledStr {
+        let mut styled = StyledStr::new();
+        styled.push_span(Span::new(text, style));
+        styled
+    }
+
+    /// The spans of the text, in order.
+    ///
+    /// No span is empty, and neighbouring spans have different styles.
+    pub fn spans(&self) -> &[Span] {
+        &self.spans
+    }
+
+    /// The text without its styles.
+    pub fn unstyled(&self) -> String {
+        self.spans.iter().map(Span::text).collect()
+    }
+
+    /// The number of columns the text takes.
+    pub fn width(&self) -> usize {
+        self.spans.iter().map(Span::width).sum()
+    }
+
+    /// Is the text empty?
+    pub fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Add `text` drawn with `style` at the end.
+    pub fn push_str(&mut self, text: &str, style: CellStyle) {
+        self.push_span(Span::new(text, style));
+    }
+
+    /// Add `span` at the end, merging it with the last span if they have the same style.
+    pub fn push_span(&mut self, span: Span) {
+        if span.text.is_empty() {
+            return;
+        }
+        match self.spans.last_mut() {
+            Some(last) if last.style == span.style => last.text.push_str(&span.text),
+            _ => self.spans.push(span),
+        }
+    }
+
+    /// The columns from `start` to `end`, excluded, of the text.
+    ///
+    /// A wide character cut by either bound is replaced by spaces, in its style, so that the
+    /// slice takes exactly the columns asked for (as far as the text goes).
+    pub fn slice(&self, start: usize, end: usize) -> StyledStr {
+        let mut slice = StyledStr::new();
+        let mut column = 0;
+        for span in &self.spans {
+            let mut text = String::new();
+            for grapheme in graphemes(&span.text) {
+                let (from, to) = (column, column + grapheme_width(grapheme));
+                column = to;
+                if from >= start && to <= end && (from < to || from < end) {
+                    text.push_str(grapheme);
+                } else if from < end && to > start {
+                    for _ in from.max(start)..to.min(end) {
+                        text.push(' ');
+                    }
+                }
+            }
+            slice.push_str(&text, span.style);
+            if column >= end {
+                break;
+            }
+        }
+        slice
+    }
+}
+
+impl fmt::Display for StyledStr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut current = CellStyle::default();
+        let mut sgr = Vec::new();
+        let end = Some((CellStyle::default(), ""));
+        for (style, text) in self.spans.iter().map(|span| (span.style, &span.text[..])).chain(end) {
+            sgr.clear();
+            style.write_sgr(Some(&current), &mut sgr);
+            f.write_str(str::from_utf8(&sgr).map_err(|_| fmt::Error)?)?;
+            f.write_str(text)?;
+            current = style;
+        }
+        Ok(())
+    }
+}
+
+impl From<&str> for StyledStr {
+    fn from(text: &str) -> StyledStr {
+        StyledStr::styled(text, CellStyle::default())
+    }
+}
+
+impl From<String> for StyledStr {
+    fn from(text: String) -> StyledStr {
+        StyledStr::styled(text, CellStyle::default())
+    }
+}
+
+impl From<Span> for StyledStr {
+    fn from(span: Span) -> StyledStr {
+        let mut styled = StyledStr::new();
+        styled.push_span(span);
+        styled
+    }
+}
+
+impl<T: Into<StyledStr>> AddAssign<T> for StyledStr {
+    fn add_assign(&mut self, other: T) {
+        for span in other.into().spans {
+            self.push_span(span);
+        }
+    }
+}
+
+impl<T: Into<StyledStr>> Add<T> for StyledStr {
+    type Output = StyledStr;
+
+    fn add(mut self, other: T) -> StyledStr {
+        self += other;
+        self
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use buffer::CellColor;
+
+    fn bold() -> CellStyle {
+        CellStyle { bold: true, ..CellStyle::default() }
+    }
+
+    #[test]
+    fn test_concat() {
+        let mut text = StyledStr::from("a") + "b" + Span::new("c", bold());
+        text += StyledStr::styled("d", bold());
+        assert_eq!(text.spans(), &[Span::new("ab", CellStyle::default()), Span::new("cd", bold())]);
+        assert_eq!(text.unstyled(), "abcd");
+        assert_eq!(text.to_string(), "ab\x1B[1mcd\x1B[0m");
+        assert_eq!(StyledStr::from("plain").to_string(), "plain");
+        assert!((StyledStr::new() + "").is_empty());
+    }
+
+    #[test]
+    fn test_slice() {
+        let red = CellStyle { fg: CellColor::Ansi(1), ..CellStyle::default() };
+        let text = StyledStr::from("ab日") + StyledStr::styled("e\u{301}f", red);
+        assert_eq!(text.width(), 6);
+        assert_eq!(text.slice(1, 5), StyledStr::from("b日") + StyledStr::styled("e\u{301}", red));
+        assert_eq!(text.slice(3, 10), StyledStr::from(" ") + StyledStr::styled("e\u{301}f", red));
+        assert_eq!(text.slice(0, 3).unstyled(), "ab ");
+        assert!(text.slice(6, 8).is_empty());
+    }
+}
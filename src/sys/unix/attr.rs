@@ -1,29 +1,86 @@
 use std::{io, mem};
+use std::os::unix::io::RawFd;
 
 use super::{cvt, Termios};
-use super::libc::c_int;
+use super::libc::{self, c_int};
 
-pub fn get_terminal_attr() -> io::Result<Termios> {
+pub fn get_terminal_attr_fd(fd: RawFd) -> io::Result<Termios> {
     extern "C" {
         pub fn tcgetattr(fd: c_int, termptr: *mut Termios) -> c_int;
     }
     unsafe {
         let mut termios = mem::zeroed();
-        cvt(tcgetattr(0, &mut termios))?;
+        cvt(tcgetattr(fd, &mut termios))?;
         Ok(termios)
     }
 }
 
-pub fn set_terminal_attr(termios: &Termios) -> io::Result<()> {
+pub fn set_terminal_attr_fd(fd: RawFd, termios: &Termios) -> io::Result<()> {
     extern "C" {
         pub fn tcsetattr(fd: c_int, opt: c_int, termptr: *const Termios) -> c_int;
     }
-    cvt(unsafe { tcsetattr(0, 0, termios) }).and(Ok(()))
+    // TCSANOW is not 0 everywhere: Solaris and illumos number it from their ioctls.
+    cvt(unsafe { tcsetattr(fd, libc::TCSANOW, termios) }).and(Ok(()))
 }
 
 pub fn raw_terminal_attr(termios: &mut Termios) {
-    extern "C" {
-        pub fn cfmakeraw(termptr: *mut Termios);
+    // Solaris and illumos lack `cfmakeraw`, which libc then implements itself.
+    unsafe {
+        libc::cfmakeraw(termios);
+    }
+}
+
+/// Are the input flags of `termios` those of raw mode, which making it raw would leave as they
+/// are? The output processing is left out, as `keep_output_processing` turns it back on.
+pub fn is_raw(termios: &Termios) -> bool {
+    let mut raw = *termios;
+    raw_terminal_attr(&mut raw);
+    raw.c_iflag == termios.c_iflag && raw.c_lflag == termios.c_lflag
+}
+
+pub fn keep_output_processing(termios: &mut Termios) {
+    termios.c_oflag |= libc::OPOST | libc::ONLCR;
+}
+
+/// Duplicate `fd`, so the terminal can still be reached after `fd` is closed or replaced.
+pub fn dup_fd(fd: RawFd) -> io::Result<RawFd> {
+    cvt(unsafe { libc::fcntl(fd, libc::F_DUPFD_CLOEXEC, 0) })
+}
+
+pub fn close_fd(fd: RawFd) {
+    unsafe {
+        libc::close(fd);
+    }
+}
+
+/// Call `hook` when the process exits through `exit`, as `std::process::exit` does.
+pub fn at_exit(hook: extern "C" fn()) -> io::Result<()> {
+    if unsafe { libc::atexit(hook) } == 0 {
+        Ok(())
+    } else {
+        Err(io::Error::other("atexit failed"))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_is_raw() {
+        let mut termios: Termios = unsafe { mem::zeroed() };
+        termios.c_iflag = libc::ICRNL | libc::IXON;
+        termios.c_oflag = libc::OPOST | libc::ONLCR;
+        termios.c_lflag = libc::ICANON | libc::ECHO | libc::ISIG | libc::IEXTEN;
+        assert!(!is_raw(&termios));
+
+        // Neither canonical nor echoed, but still sending signals.
+        termios.c_lflag &= !(libc::ICANON | libc::ECHO);
+        assert!(!is_raw(&termios));
+
+        raw_terminal_attr(&mut termios);
+        assert!(is_raw(&termios));
+        keep_output_processing(&mut termios);
+        assert!(is_raw(&termios));
     }
-    unsafe { cfmakeraw(termios) }
 }
@@ -0,0 +1,14 @@
+use std::ffi::CStr;
+use std::io;
+
+use super::cvt;
+use super::libc;
+
+/// Get the name of this host.
+pub fn hostname() -> io::Result<String> {
+    // Host names are at most 255 bytes, and one more holds the terminating null.
+    let mut buf = [0 as libc::c_char; 256];
+    cvt(unsafe { libc::gethostname(buf.as_mut_ptr(), buf.len() - 1) })?;
+    let name = unsafe { CStr::from_ptr(buf.as_ptr()) };
+    Ok(name.to_string_lossy().into_owned())
+}
@@ -2,9 +2,15 @@ extern crate libc;
 
 use std::io;
 
+pub use std::os::unix::io::{AsRawFd, RawFd};
+
 pub use self::libc::termios as Termios;
 
 pub mod attr;
+pub mod host;
+pub mod poll;
+pub mod pty;
+pub mod signal;
 pub mod size;
 pub mod tty;
 
@@ -0,0 +1,59 @@
+use std::fs::File;
+use std::io;
+use std::os::unix::io::FromRawFd;
+use std::time::Duration;
+
+use super::{cvt, RawFd};
+use super::libc::{self, c_int};
+
+/// Wait until one of `fds` is readable, or until `timeout` has passed (forever if `None`),
+/// returning which ones are readable.
+///
+/// A file descriptor which was closed on the other end or failed counts as readable, so that
+/// the read reports it.
+pub fn poll_readable(fds: &[RawFd], timeout: Option<Duration>) -> io::Result<Vec<bool>> {
+    let mut pollfds: Vec<libc::pollfd> = fds.iter()
+        .map(|&fd| {
+            libc::pollfd {
+                fd,
+                events: libc::POLLIN,
+                revents: 0,
+            }
+        })
+        .collect();
+
+    // Round up, so that the deadline has passed when poll returns.
+    let timeout = match timeout {
+        Some(timeout) => timeout.as_nanos().div_ceil(1_000_000).min(c_int::MAX as u128) as c_int,
+        None => -1,
+    };
+
+    cvt(unsafe { libc::poll(pollfds.as_mut_ptr(), pollfds.len() as libc::nfds_t, timeout) })?;
+
+    Ok(pollfds.iter()
+        .map(|pollfd| pollfd.revents & (libc::POLLIN | libc::POLLHUP | libc::POLLERR) != 0)
+        .collect())
+}
+
+/// Create a pipe, returning its read and write ends, both non-blocking and closed on exec.
+pub fn pipe() -> io::Result<(File, File)> {
+    let mut fds = [0; 2];
+    unsafe {
+        cvt(libc::pipe(fds.as_mut_ptr()))?;
+        let ends = (File::from_raw_fd(fds[0]), File::from_raw_fd(fds[1]));
+        for &fd in &fds {
+            cvt(libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+            set_nonblocking(fd)?;
+        }
+        Ok(ends)
+    }
+}
+
+/// Make the reads and writes of `fd` fail with `WouldBlock` rather than wait.
+pub fn set_nonblocking(fd: RawFd) -> io::Result<()> {
+    unsafe {
+        let flags = cvt(libc::fcntl(fd, libc::F_GETFL))?;
+        cvt(libc::fcntl(fd, libc::F_SETFL, flags | libc::O_NONBLOCK))?;
+    }
+    Ok(())
+}
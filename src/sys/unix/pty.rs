@@ -0,0 +1,58 @@
+use std::fs::File;
+use std::io;
+use std::os::unix::io::{AsRawFd, FromRawFd};
+use std::os::unix::process::CommandExt;
+use std::process::Command;
+use std::ptr;
+
+use super::cvt;
+use super::libc;
+use super::libc::c_int;
+
+fn winsize(width: u16, height: u16) -> libc::winsize {
+    libc::winsize {
+        ws_row: height,
+        ws_col: width,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    }
+}
+
+/// Open a pseudo-terminal of the given size, returning its master and slave sides.
+pub fn openpty(width: u16, height: u16) -> io::Result<(File, File)> {
+    let mut master: c_int = -1;
+    let mut slave: c_int = -1;
+    let mut size = winsize(width, height);
+
+    unsafe {
+        // Some platforms take the size as mutable.
+        let size = &mut size as *mut _;
+        cvt(libc::openpty(&mut master, &mut slave, ptr::null_mut(), ptr::null_mut(), size))?;
+        let (master, slave) = (File::from_raw_fd(master), File::from_raw_fd(slave));
+
+        // Don't leak the pseudo-terminal into other children.
+        cvt(libc::fcntl(master.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC))?;
+        cvt(libc::fcntl(slave.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC))?;
+
+        Ok((master, slave))
+    }
+}
+
+/// Set the size of the pseudo-terminal `master`, which sends `SIGWINCH` to its foreground process
+/// group.
+pub fn set_size(master: &File, width: u16, height: u16) -> io::Result<()> {
+    let size = winsize(width, height);
+    cvt(unsafe { libc::ioctl(master.as_raw_fd(), libc::TIOCSWINSZ, &size) }).map(|_| ())
+}
+
+/// Make the standard input of the child spawned by `command` its controlling terminal, in a new
+/// session.
+pub fn set_controlling_terminal(command: &mut Command) {
+    unsafe {
+        command.pre_exec(|| {
+            cvt(libc::setsid())?;
+            cvt(libc::ioctl(0, libc::TIOCSCTTY as _, 0))?;
+            Ok(())
+        });
+    }
+}
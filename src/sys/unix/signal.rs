@@ -0,0 +1,255 @@
+use std::{io, mem, ptr, thread};
+use std::sync::{Mutex, Once};
+use std::sync::atomic::{AtomicBool, AtomicI32, AtomicUsize, Ordering};
+
+use super::cvt;
+use super::libc::{self, c_int, c_void};
+
+/// A signal handled through the self-pipe.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Signal {
+    /// The terminal was resized (`SIGWINCH`).
+    Resize = 0,
+    /// The process was continued after being stopped (`SIGCONT`).
+    Continue = 1,
+}
+
+impl Signal {
+    fn number(self) -> c_int {
+        match self {
+            Signal::Resize => libc::SIGWINCH,
+            Signal::Continue => libc::SIGCONT,
+        }
+    }
+
+    fn from_number(number: c_int) -> Option<Signal> {
+        match number {
+            libc::SIGWINCH => Some(Signal::Resize),
+            libc::SIGCONT => Some(Signal::Continue),
+            _ => None,
+        }
+    }
+}
+
+/// A callback subscribed to a signal, which is unsubscribed once it returns `false`.
+pub type Callback = Box<dyn FnMut() -> bool + Send>;
+
+/// The write end of the self-pipe, or -1 before it is created.
+static PIPE: AtomicI32 = AtomicI32::new(-1);
+/// The handlers installed before ours, which are chained.
+static PREVIOUS: [AtomicUsize; 2] = [AtomicUsize::new(0), AtomicUsize::new(0)];
+/// Do the handlers installed before ours take a `siginfo_t`?
+static PREVIOUS_SIGINFO: [AtomicBool; 2] = [AtomicBool::new(false), AtomicBool::new(false)];
+/// The subscribed callbacks.
+static CALLBACKS: Mutex<Vec<(Signal, Callback)>> = Mutex::new(Vec::new());
+
+static PIPE_INIT: Once = Once::new();
+static HANDLER_INIT: [Once; 2] = [Once::new(), Once::new()];
+
+/// Call `callback` from a background thread whenever `signal` is received.
+///
+/// The signal handler itself only writes to a pipe, which is async-signal-safe, and then calls
+/// the previously installed handler, if any.
+pub fn subscribe(signal: Signal, callback: Callback) -> io::Result<()> {
+    let mut res = Ok(());
+    PIPE_INIT.call_once(|| res = create_pipe());
+    res?;
+    if PIPE.load(Ordering::SeqCst) < 0 {
+        return Err(io::Error::new(io::ErrorKind::BrokenPipe, "Signal pipe is unavailable."));
+    }
+
+    CALLBACKS.lock().unwrap_or_else(|e| e.into_inner()).push((signal, callback));
+
+    let mut res = Ok(());
+    HANDLER_INIT[signal as usize].call_once(|| res = install_handler(signal));
+    res
+}
+
+/// Create the self-pipe and spawn the thread dispatching the signals written to it.
+fn create_pipe() -> io::Result<()> {
+    let mut fds = [0; 2];
+    unsafe {
+        cvt(libc::pipe(fds.as_mut_ptr()))?;
+        for &fd in &fds {
+            cvt(libc::fcntl(fd, libc::F_SETFD, libc::FD_CLOEXEC))?;
+        }
+        // The handler must never block.
+        let flags = cvt(libc::fcntl(fds[1], libc::F_GETFL))?;
+        cvt(libc::fcntl(fds[1], libc::F_SETFL, flags | libc::O_NONBLOCK))?;
+    }
+
+    let read_fd = fds[0];
+    thread::Builder::new()
+        .name("termion-signals".into())
+        .spawn(move || dispatch(read_fd))?;
+
+    PIPE.store(fds[1], Ordering::SeqCst);
+    Ok(())
+}
+
+/// Read signals from the self-pipe and call the callbacks subscribed to them.
+fn dispatch(read_fd: c_int) {
+    let mut buf = [0u8; 64];
+
+    loop {
+        let n = unsafe { libc::read(read_fd, buf.as_mut_ptr() as *mut c_void, buf.len()) };
+        if n < 0 {
+            if io::Error::last_os_error().kind() == io::ErrorKind::Interrupted {
+                continue;
+            }
+            return;
+        } else if n == 0 {
+            return;
+        }
+
+        for &byte in &buf[..n as usize] {
+            if let Some(signal) = Signal::from_number(byte as c_int) {
+                call(signal);
+            }
+        }
+    }
+}
+
+/// Call the callbacks subscribed to `signal`.
+///
+/// They are called without holding the lock, so that they can subscribe other callbacks.
+fn call(signal: Signal) {
+    let mut called: Vec<(Signal, Callback)> = {
+        let mut callbacks = CALLBACKS.lock().unwrap_or_else(|e| e.into_inner());
+        let (called, others) = callbacks.drain(..).partition(|&(s, _)| s == signal);
+        *callbacks = others;
+        called
+    };
+    called.retain_mut(|&mut (_, ref mut callback)| callback());
+    CALLBACKS.lock().unwrap_or_else(|e| e.into_inner()).extend(called);
+}
+
+/// Install our handler for `signal`, keeping the previous one for chaining.
+fn install_handler(signal: Signal) -> io::Result<()> {
+    unsafe {
+        let mut action: libc::sigaction = mem::zeroed();
+        action.sa_sigaction = handler as *const () as usize;
+        action.sa_flags = libc::SA_RESTART | libc::SA_SIGINFO;
+        libc::sigemptyset(&mut action.sa_mask);
+
+        let mut previous: libc::sigaction = mem::zeroed();
+        cvt(libc::sigaction(signal.number(), &action, &mut previous))?;
+
+        PREVIOUS_SIGINFO[signal as usize].store(previous.sa_flags & libc::SA_SIGINFO != 0,
+                                                Ordering::SeqCst);
+        PREVIOUS[signal as usize].store(previous.sa_sigaction, Ordering::SeqCst);
+    }
+
+    Ok(())
+}
+
+/// The signal handler, which must only perform async-signal-safe operations.
+extern "C" fn handler(number: c_int, info: *mut libc::siginfo_t, context: *mut c_void) {
+    let errno = errno();
+
+    let fd = PIPE.load(Ordering::Relaxed);
+    if fd >= 0 {
+        let byte = number as u8;
+        unsafe {
+            libc::write(fd, &byte as *const u8 as *const c_void, 1);
+        }
+    }
+
+    if let Some(signal) = Signal::from_number(number) {
+        let previous = PREVIOUS[signal as usize].load(Ordering::Relaxed);
+        if previous != libc::SIG_DFL && previous != libc::SIG_IGN {
+            unsafe {
+                if PREVIOUS_SIGINFO[signal as usize].load(Ordering::Relaxed) {
+                    let previous: extern "C" fn(c_int, *mut libc::siginfo_t, *mut c_void) =
+                        mem::transmute(previous);
+                    previous(number, info, context);
+                } else {
+                    let previous: extern "C" fn(c_int) = mem::transmute(previous);
+                    previous(number);
+                }
+            }
+        }
+    }
+
+    set_errno(errno);
+}
+
+#[cfg(any(target_os = "linux", target_os = "emscripten", target_os = "dragonfly",
+          target_os = "redox"))]
+unsafe fn errno_location() -> *mut c_int {
+    libc::__errno_location()
+}
+
+#[cfg(any(target_os = "macos", target_os = "ios", target_os = "freebsd"))]
+unsafe fn errno_location() -> *mut c_int {
+    libc::__error()
+}
+
+#[cfg(any(target_os = "android", target_os = "netbsd", target_os = "openbsd"))]
+unsafe fn errno_location() -> *mut c_int {
+    libc::__errno()
+}
+
+#[cfg(any(target_os = "solaris", target_os = "illumos"))]
+unsafe fn errno_location() -> *mut c_int {
+    libc::___errno()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "emscripten", target_os = "dragonfly",
+              target_os = "redox", target_os = "macos", target_os = "ios", target_os = "freebsd",
+              target_os = "android", target_os = "netbsd", target_os = "openbsd",
+              target_os = "solaris", target_os = "illumos")))]
+unsafe fn errno_location() -> *mut c_int {
+    // Unknown platform: errno is not preserved across the handler.
+    static mut ERRNO: c_int = 0;
+    ptr::addr_of_mut!(ERRNO)
+}
+
+fn errno() -> c_int {
+    unsafe { *errno_location() }
+}
+
+fn set_errno(errno: c_int) {
+    unsafe { ptr::write(errno_location(), errno) }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_subscribe() {
+        let (send, recv) = mpsc::channel();
+        subscribe(Signal::Continue, Box::new(move || send.send(()).is_ok())).unwrap();
+
+        unsafe {
+            libc::raise(libc::SIGCONT);
+        }
+        recv.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+
+    #[test]
+    fn test_subscribe_from_callback() {
+        let (send, recv) = mpsc::channel();
+        subscribe(Signal::Continue,
+                  Box::new(move || {
+                      let send = send.clone();
+                      subscribe(Signal::Continue, Box::new(move || send.send(()).is_ok()))
+                          .unwrap();
+                      false
+                  }))
+            .unwrap();
+
+        // The first signal subscribes the inner callback, which the second one calls.
+        unsafe {
+            libc::raise(libc::SIGCONT);
+        }
+        thread::sleep(Duration::from_millis(50));
+        unsafe {
+            libc::raise(libc::SIGCONT);
+        }
+        recv.recv_timeout(Duration::from_secs(5)).unwrap();
+    }
+}
@@ -1,4 +1,6 @@
 use std::{fs, io};
+#[cfg(target_os = "redox")]
+use std::env;
 use std::os::unix::io::AsRawFd;
 
 use super::libc;
@@ -12,6 +14,16 @@ pub fn is_tty<T: AsRawFd>(stream: &T) -> bool {
 /// Get the TTY device.
 ///
 /// This allows for getting stdio representing _only_ the TTY, and not other streams.
+#[cfg(not(target_os = "redox"))]
 pub fn get_tty() -> io::Result<fs::File> {
     fs::OpenOptions::new().read(true).write(true).open("/dev/tty")
 }
+
+/// Get the TTY device, as named by the `TTY` environment variable.
+///
+/// This allows for getting stdio representing _only_ the TTY, and not other streams.
+#[cfg(target_os = "redox")]
+pub fn get_tty() -> io::Result<fs::File> {
+    let tty = env::var("TTY").map_err(|x| io::Error::new(io::ErrorKind::NotFound, x))?;
+    fs::OpenOptions::new().read(true).write(true).open(tty)
+}
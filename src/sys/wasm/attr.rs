@@ -0,0 +1,35 @@
+use std::io;
+use std::sync::Mutex;
+
+use super::{RawFd, Termios};
+
+static TERMIOS: Mutex<Termios> = Mutex::new(Termios { raw: false });
+
+pub fn get_terminal_attr_fd(_tty: RawFd) -> io::Result<Termios> {
+    Ok(*TERMIOS.lock().unwrap_or_else(|e| e.into_inner()))
+}
+
+pub fn set_terminal_attr_fd(_tty: RawFd, termios: &Termios) -> io::Result<()> {
+    *TERMIOS.lock().unwrap_or_else(|e| e.into_inner()) = *termios;
+    Ok(())
+}
+
+pub fn raw_terminal_attr(termios: &mut Termios) {
+    termios.raw = true;
+}
+
+pub fn is_raw(termios: &Termios) -> bool {
+    termios.raw
+}
+
+pub fn keep_output_processing(_termios: &mut Termios) {}
+
+pub fn dup_fd(tty: RawFd) -> io::Result<RawFd> {
+    Ok(tty)
+}
+
+pub fn close_fd(_tty: RawFd) {}
+
+pub fn at_exit(_hook: extern "C" fn()) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "There is no process to exit."))
+}
@@ -0,0 +1,6 @@
+use std::io;
+
+/// Get the name of this host, which the browser doesn't tell.
+pub fn hostname() -> io::Result<String> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "There is no host name in the browser."))
+}
@@ -0,0 +1,112 @@
+use std::io;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use self::signal::Signal;
+
+pub mod attr;
+pub mod host;
+pub mod pty;
+pub mod signal;
+pub mod size;
+pub mod tty;
+
+/// The terminal attributes, which xterm.js doesn't have: it always hands over the keys as typed.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct Termios {
+    raw: bool,
+}
+
+/// A file descriptor, which only distinguishes the standard streams in the browser.
+pub type RawFd = i32;
+
+/// A stream which has a file descriptor.
+pub trait AsRawFd {
+    /// Extract the file descriptor.
+    fn as_raw_fd(&self) -> RawFd;
+}
+
+impl AsRawFd for io::Stdin {
+    fn as_raw_fd(&self) -> RawFd {
+        0
+    }
+}
+
+impl AsRawFd for io::Stdout {
+    fn as_raw_fd(&self) -> RawFd {
+        1
+    }
+}
+
+impl AsRawFd for io::Stderr {
+    fn as_raw_fd(&self) -> RawFd {
+        2
+    }
+}
+
+extern "C" {
+    /// Provided by the JavaScript side, which usually passes the bytes on to `Terminal.write`.
+    fn termion_write(data: *const u8, len: usize);
+}
+
+/// The size of the terminal, as `width << 16 | height`.
+static SIZE: AtomicU32 = AtomicU32::new(80 << 16 | 24);
+/// The readers of the input fed from the JavaScript side.
+static READERS: Mutex<Vec<Sender<io::Result<u8>>>> = Mutex::new(Vec::new());
+/// The buffer the JavaScript side copies input to, before calling `termion_input`.
+static mut INPUT_BUFFER: [u8; INPUT_BUFFER_SIZE] = [0; INPUT_BUFFER_SIZE];
+
+/// The size of the buffer returned by `termion_input_buffer`.
+pub const INPUT_BUFFER_SIZE: usize = 4096;
+
+/// Write `buf` to the terminal.
+pub fn write_output(buf: &[u8]) {
+    unsafe { termion_write(buf.as_ptr(), buf.len()) }
+}
+
+/// Receive the input fed with `feed_input` from now on.
+pub fn subscribe_input() -> Receiver<io::Result<u8>> {
+    let (send, recv) = mpsc::channel();
+    READERS.lock().unwrap_or_else(|e| e.into_inner()).push(send);
+    recv
+}
+
+/// Feed `input` to the readers of the terminal, e.g. the data of xterm.js' `onData` event.
+pub fn feed_input(input: &[u8]) {
+    READERS.lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|send| input.iter().all(|&b| send.send(Ok(b)).is_ok()));
+}
+
+/// Report that the terminal was resized to `width` columns and `height` rows, e.g. from xterm.js'
+/// `onResize` event.
+pub fn set_size(width: u16, height: u16) {
+    SIZE.store((width as u32) << 16 | height as u32, Ordering::SeqCst);
+    signal::raise(Signal::Resize);
+}
+
+fn size() -> (u16, u16) {
+    let size = SIZE.load(Ordering::SeqCst);
+    ((size >> 16) as u16, size as u16)
+}
+
+/// The buffer to copy up to `INPUT_BUFFER_SIZE` bytes of input to, before calling
+/// `termion_input`.
+#[no_mangle]
+pub extern "C" fn termion_input_buffer() -> *mut u8 {
+    std::ptr::addr_of_mut!(INPUT_BUFFER) as *mut u8
+}
+
+/// Feed the first `len` bytes of the input buffer to the terminal.
+#[no_mangle]
+pub extern "C" fn termion_input(len: usize) {
+    let input = unsafe { &*std::ptr::addr_of!(INPUT_BUFFER) };
+    feed_input(&input[..len.min(INPUT_BUFFER_SIZE)]);
+}
+
+/// Report that the terminal was resized.
+#[no_mangle]
+pub extern "C" fn termion_resize(width: u16, height: u16) {
+    set_size(width, height);
+}
@@ -0,0 +1,20 @@
+use std::fs::File;
+use std::io;
+use std::process::Command;
+
+fn unsupported() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "Pseudo-terminals are not supported in the browser.")
+}
+
+/// Open a pseudo-terminal of the given size, returning its master and slave sides.
+pub fn openpty(_width: u16, _height: u16) -> io::Result<(File, File)> {
+    Err(unsupported())
+}
+
+/// Set the size of the pseudo-terminal `master`.
+pub fn set_size(_master: &File, _width: u16, _height: u16) -> io::Result<()> {
+    Err(unsupported())
+}
+
+/// Make the standard input of the child spawned by `command` its controlling terminal.
+pub fn set_controlling_terminal(_command: &mut Command) {}
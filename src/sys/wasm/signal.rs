@@ -0,0 +1,37 @@
+use std::io;
+use std::sync::Mutex;
+
+/// A signal raised by the JavaScript side.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Signal {
+    /// The terminal was resized.
+    Resize = 0,
+}
+
+/// A callback subscribed to a signal, which is unsubscribed once it returns `false`.
+pub type Callback = Box<dyn FnMut() -> bool + Send>;
+
+/// The subscribed callbacks.
+static CALLBACKS: Mutex<Vec<(Signal, Callback)>> = Mutex::new(Vec::new());
+
+/// Call `callback` whenever `signal` is raised.
+///
+/// There are no threads in the browser: the callback is called from `raise`.
+pub fn subscribe(signal: Signal, callback: Callback) -> io::Result<()> {
+    CALLBACKS.lock().unwrap_or_else(|e| e.into_inner()).push((signal, callback));
+    Ok(())
+}
+
+/// Call the callbacks subscribed to `signal`.
+///
+/// They are called without holding the lock, so that they can subscribe other callbacks.
+pub fn raise(signal: Signal) {
+    let mut called: Vec<(Signal, Callback)> = {
+        let mut callbacks = CALLBACKS.lock().unwrap_or_else(|e| e.into_inner());
+        let (called, others) = callbacks.drain(..).partition(|&(s, _)| s == signal);
+        *callbacks = others;
+        called
+    };
+    called.retain_mut(|&mut (_, ref mut callback)| callback());
+    CALLBACKS.lock().unwrap_or_else(|e| e.into_inner()).extend(called);
+}
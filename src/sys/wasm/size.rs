@@ -0,0 +1,6 @@
+use std::io;
+
+/// Get the size of the terminal, as last reported by the JavaScript side.
+pub fn terminal_size() -> io::Result<(u16, u16)> {
+    Ok(super::size())
+}
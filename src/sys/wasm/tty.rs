@@ -0,0 +1,66 @@
+use std::io::{self, Read, Write};
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use super::{subscribe_input, write_output, AsRawFd, RawFd};
+
+/// Is this stream a TTY?
+///
+/// In the browser, every stream is considered to be the terminal.
+pub fn is_tty<T: AsRawFd>(_stream: &T) -> bool {
+    true
+}
+
+/// Get the TTY device.
+///
+/// This is the xterm.js terminal: writing calls `termion_write`, and reading returns the input fed
+/// through `termion_input`.
+pub fn get_tty() -> io::Result<Tty> {
+    Ok(Tty { input: subscribe_input() })
+}
+
+/// The terminal in the browser.
+///
+/// Reading never blocks, as the input only arrives once control returns to the browser; it fails
+/// with `WouldBlock` instead.
+pub struct Tty {
+    input: Receiver<io::Result<u8>>,
+}
+
+impl AsRawFd for Tty {
+    fn as_raw_fd(&self) -> RawFd {
+        0
+    }
+}
+
+impl Read for Tty {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut total = 0;
+
+        while total < buf.len() {
+            match self.input.try_recv() {
+                Ok(Ok(b)) => {
+                    buf[total] = b;
+                    total += 1;
+                }
+                Ok(Err(e)) => return Err(e),
+                Err(TryRecvError::Empty) if total == 0 && !buf.is_empty() => {
+                    return Err(io::ErrorKind::WouldBlock.into());
+                }
+                Err(_) => break,
+            }
+        }
+
+        Ok(total)
+    }
+}
+
+impl Write for Tty {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        write_output(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,128 @@
+//! Tab stop control.
+//!
+//! # Example
+//!
+#![cfg_attr(feature = "std", doc = "```rust")]
+#![cfg_attr(not(feature = "std"), doc = "```rust,ignore")]
+//! extern crate termion;
+//!
+//! use termion::tab::{self, TabStops};
+//!
+//! fn main() {
+//!     // Replace the default stops with a single stop in column 5.
+//!     print!("{}{}{}", tab::ClearAll, termion::cursor::Goto(5, 1), tab::Set);
+//!
+//!     let mut stops = TabStops::new(80);
+//!     stops.clear_all();
+//!     stops.set(5);
+//!     assert_eq!(stops.next_stop(1), 5);
+//! }
+//! ```
+
+derive_esc_sequence!("Set a tab stop at the cursor column (HTS).", Set, "H");
+derive_csi_sequence!("Clear the tab stop at the cursor column (TBC).", Clear, "0g");
+derive_csi_sequence!("Clear all tab stops (TBC).", ClearAll, "3g");
+
+/// The interval between the tab stops a terminal starts out with.
+pub const DEFAULT_TAB_WIDTH: u16 = 8;
+
+/// A model of the tab stops of a terminal, used to compute where a TAB moves the cursor.
+///
+/// Columns are (1)-based, just like `cursor::Goto`. Keep this in sync with the `Set`, `Clear` and
+/// `ClearAll` escapes written to the terminal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg(feature = "std")]
+pub struct TabStops {
+    /// The columns holding a tab stop, sorted in ascending order.
+    stops: Vec<u16>,
+    /// The width of the terminal.
+    width: u16,
+}
+
+#[cfg(feature = "std")]
+impl TabStops {
+    /// The default tab stops (every eight columns) of a terminal `width` columns wide.
+    pub fn new(width: u16) -> TabStops {
+        TabStops {
+            stops: (DEFAULT_TAB_WIDTH + 1..width).step_by(DEFAULT_TAB_WIDTH as usize).collect(),
+            width,
+        }
+    }
+
+    /// Set a tab stop in column `x`.
+    pub fn set(&mut self, x: u16) {
+        if let Err(i) = self.stops.binary_search(&x) {
+            self.stops.insert(i, x);
+        }
+    }
+
+    /// Clear the tab stop in column `x`, if any.
+    pub fn clear(&mut self, x: u16) {
+        if let Ok(i) = self.stops.binary_search(&x) {
+            self.stops.remove(i);
+        }
+    }
+
+    /// Clear all tab stops.
+    pub fn clear_all(&mut self) {
+        self.stops.clear();
+    }
+
+    /// The columns holding a tab stop, in ascending order.
+    pub fn stops(&self) -> &[u16] {
+        &self.stops
+    }
+
+    /// The width of the terminal these tab stops belong to.
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// The column the cursor ends up in after a TAB is written in column `x`.
+    ///
+    /// Without any further tab stop on the line, the cursor moves to the last column.
+    pub fn next_stop(&self, x: u16) -> u16 {
+        match self.stops.iter().find(|&&stop| stop > x) {
+            Some(&stop) if stop <= self.width => stop,
+            _ => self.width.max(x),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_default_stops() {
+        let stops = TabStops::new(30);
+        assert_eq!(stops.stops(), &[9, 17, 25]);
+        assert_eq!(stops.next_stop(1), 9);
+        assert_eq!(stops.next_stop(8), 9);
+        assert_eq!(stops.next_stop(9), 17);
+        assert_eq!(stops.next_stop(25), 30);
+        assert_eq!(stops.next_stop(30), 30);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_set_and_clear() {
+        let mut stops = TabStops::new(40);
+        stops.set(4);
+        stops.set(4);
+        stops.clear(17);
+        assert_eq!(stops.stops(), &[4, 9, 25, 33]);
+        assert_eq!(stops.next_stop(1), 4);
+        assert_eq!(stops.next_stop(9), 25);
+
+        stops.clear_all();
+        assert!(stops.stops().is_empty());
+        assert_eq!(stops.next_stop(3), 40);
+    }
+
+    #[test]
+    fn test_escapes() {
+        assert_eq!(format!("{}{}{}", Set, Clear, ClearAll), "\x1BH\x1B[0g\x1B[3g");
+    }
+}
@@ -0,0 +1,317 @@
+//! Tables sized to their contents.
+//!
+//! A `Table` is filled with rows of cells, then laid out: every column takes the width of its
+//! widest cell, and if the table is wider than its maximum width, the widest columns are shrunk
+//! and the cells cut with an ellipsis. Columns are separated by spaces, or by lines drawn with
+//! the box-drawing characters.
+//!
+//! # Example
+//!
+//! ```rust
+//! use termion::box_drawing::{BoxChars, Encoding, Style};
+//! use termion::table::{Align, Table};
+//!
+//! let mut table = Table::new();
+//! table.set_header(&["name", "size"]);
+//! table.add_row(&["Cargo.toml", "1 KiB"]);
+//! table.add_row(&["termion.rlib", "2 MiB"]);
+//! table.set_align(1, Align::Right);
+//! table.set_borders(BoxChars::new(Style::Single, Encoding::Ascii));
+//! table.set_max_width(20);
+//!
+//! assert_eq!(table.to_string(),
+//!            "+----------+-------+\n\
+//!             | name     |  size |\n\
+//!             +----------+-------+\n\
+//!             | Cargo.t… | 1 KiB |\n\
+//!             | termion… | 2 MiB |\n\
+//!             +----------+-------+\n");
+//! ```
+
+use std::fmt;
+use std::io::{self, Write};
+
+use box_drawing::{BoxChars, Part};
+use width::{grapheme_width, graphemes, str_width};
+
+/// How the text of a cell is placed, when it is narrower than its column.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum Align {
+    /// Against the left edge.
+    #[default]
+    Left,
+    /// Against the right edge.
+    Right,
+    /// In the middle, nearer the left edge if it can't be exactly.
+    Center,
+}
+
+/// A table of text cells.
+///
+/// Displaying a table writes its lines, each ending with `\n`. In raw mode, where that doesn't
+/// return the cursor to the left edge, write the `lines` one by one instead.
+#[derive(Clone, Debug)]
+pub struct Table {
+    header: Option<Vec<String>>,
+    rows: Vec<Vec<String>>,
+    align: Vec<Align>,
+    borders: Option<BoxChars>,
+    max_width: Option<u16>,
+    padding: u16,
+    ellipsis: String,
+}
+
+impl Default for Table {
+    fn default() -> Table {
+        Table::new()
+    }
+}
+
+impl Table {
+    /// An empty table without borders, as wide as its contents.
+    pub fn new() -> Table {
+        Table {
+            header: None,
+            rows: Vec::new(),
+            align: Vec::new(),
+            borders: None,
+            max_width: None,
+            padding: 1,
+            ellipsis: "…".to_string(),
+        }
+    }
+
+    /// Make `cells` the header, the first row, separated from the others by a line if the table
+    /// has borders.
+    pub fn set_header<T: fmt::Display>(&mut self, cells: &[T]) {
+        self.header = Some(cells.iter().map(cell).collect());
+    }
+
+    /// Add a row of `cells` below the others.
+    ///
+    /// Rows may have different lengths: the missing cells are left empty. Control characters
+    /// are replaced by spaces.
+    pub fn add_row<T: fmt::Display>(&mut self, cells: &[T]) {
+        self.rows.push(cells.iter().map(cell).collect());
+    }
+
+    /// Place the text of the cells of `column`, counted from 0, with `align`.
+    pub fn set_align(&mut self, column: usize, align: Align) {
+        if self.align.len() <= column {
+            self.align.resize(column + 1, Align::Left);
+        }
+        self.align[column] = align;
+    }
+
+    /// Draw lines around and between the cells with `chars`.
+    pub fn set_borders(&mut self, chars: BoxChars) {
+        self.borders = Some(chars);
+    }
+
+    /// Make the table at most `width` columns wide, cutting cells if needed.
+    ///
+    /// The columns won't shrink to less than one column each, so a table with many columns may
+    /// still be wider.
+    pub fn set_max_width(&mut self, width: u16) {
+        self.max_width = Some(width);
+    }
+
+    /// Put `padding` spaces on each side of the cells, rather than 1.
+    ///
+    /// Without borders, the space between two columns is twice the padding, and there is none
+    /// at the edges.
+    pub fn set_padding(&mut self, padding: u16) {
+        self.padding = padding;
+    }
+
+    /// End the cells which are cut with `ellipsis`, rather than `…`.
+    pub fn set_ellipsis(&mut self, ellipsis: &str) {
+        self.ellipsis = ellipsis.to_string();
+    }
+
+    /// The width of each column, without padding.
+    pub fn column_widths(&self) -> Vec<usize> {
+        let mut widths = Vec::new();
+        for row in self.header.iter().chain(&self.rows) {
+            if widths.len() < row.len() {
+                widths.resize(row.len(), 0);
+            }
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(str_width(cell));
+            }
+        }
+
+        if let Some(max) = self.max_width {
+            let columns = widths.len();
+            let padding = 2 * self.padding as usize * columns;
+            let overhead = match self.borders {
+                Some(_) => padding + columns + 1,
+                None => padding.saturating_sub(2 * self.padding as usize),
+            };
+            let mut total = overhead + widths.iter().sum::<usize>();
+            while total > max as usize {
+                match widths.iter_mut().filter(|width| **width > 1).max() {
+                    Some(widest) => *widest -= 1,
+                    None => break,
+                }
+                total -= 1;
+            }
+        }
+
+        widths
+    }
+
+    /// The lines of the table, without line endings.
+    pub fn lines(&self) -> Vec<String> {
+        let widths = self.column_widths();
+        let mut lines = Vec::new();
+
+        if let Some(chars) = self.borders {
+            let rule = |left, middle, right| {
+                let mut line = chars.get(left).to_string();
+                for (i, &width) in widths.iter().enumerate() {
+                    if i > 0 {
+                        line += &chars.get(middle).to_string();
+                    }
+                    let count = width + 2 * self.padding as usize;
+                    line += &chars.repeat(Part::Horizontal, count as u16).to_string();
+                }
+                line + &chars.get(right).to_string()
+            };
+
+            lines.push(rule(Part::TopLeft, Part::TopTee, Part::TopRight));
+            if let Some(ref header) = self.header {
+                lines.push(self.row_line(header, &widths));
+                lines.push(rule(Part::LeftTee, Part::Cross, Part::RightTee));
+            }
+            for row in &self.rows {
+                lines.push(self.row_line(row, &widths));
+            }
+            lines.push(rule(Part::BottomLeft, Part::BottomTee, Part::BottomRight));
+        } else {
+            for row in self.header.iter().chain(&self.rows) {
+                lines.push(self.row_line(row, &widths));
+            }
+        }
+
+        lines
+    }
+
+    /// Write the lines of the table to `out`, each ending with `\n`.
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        write!(out, "{}", self)
+    }
+
+    fn row_line(&self, row: &[String], widths: &[usize]) -> String {
+        let padding = " ".repeat(self.padding as usize);
+        let vertical = self.borders.map(|chars| chars.get(Part::Vertical).to_string());
+        let mut line = String::new();
+        for (i, &width) in widths.iter().enumerate() {
+            match vertical {
+                Some(ref vertical) => {
+                    line += vertical;
+                    line += &padding;
+                }
+                None if i > 0 => {
+                    line += &padding;
+                    line += &padding;
+                }
+                None => {}
+            }
+            let text = row.get(i).map_or("", |cell| &cell[..]);
+            let align = self.align.get(i).cloned().unwrap_or_default();
+            line += &fit(text, width, align, &self.ellipsis);
+            if vertical.is_some() {
+                line += &padding;
+            }
+        }
+        if let Some(ref vertical) = vertical {
+            line += vertical;
+        } else {
+            // Don't leave the padding of the last column at the end of the line.
+            line.truncate(line.trim_end_matches(' ').len());
+        }
+        line
+    }
+}
+
+impl fmt::Display for Table {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for line in self.lines() {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
+
+/// The text of a cell, with the control characters replaced by spaces.
+fn cell<T: fmt::Display>(content: &T) -> String {
+    content.to_string().chars().map(|c| if c.is_control() { ' ' } else { c }).collect()
+}
+
+/// `text` made exactly `width` columns wide, cut with `ellipsis` or padded as `align` says.
+fn fit(text: &str, width: usize, align: Align, ellipsis: &str) -> String {
+    let text_width = str_width(text);
+    if text_width <= width {
+        let space = width - text_width;
+        let left = match align {
+            Align::Left => 0,
+            Align::Right => space,
+            Align::Center => space / 2,
+        };
+        return format!("{}{}{}", " ".repeat(left), text, " ".repeat(space - left));
+    }
+
+    let (ellipsis, room) = match width.checked_sub(str_width(ellipsis)) {
+        Some(room) => (ellipsis, room),
+        None => ("", width),
+    };
+    let mut fitted = String::new();
+    let mut column = 0;
+    for grapheme in graphemes(text) {
+        let grapheme_width = grapheme_width(grapheme);
+        if column + grapheme_width > room {
+            break;
+        }
+        fitted.push_str(grapheme);
+        column += grapheme_width;
+    }
+    // A wide character may not fit exactly.
+    fitted + &" ".repeat(room - column) + ellipsis
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use box_drawing::{Encoding, Style};
+
+    #[test]
+    fn test_plain() {
+        let mut table = Table::new();
+        table.add_row(&["a", "bb", "c"]);
+        table.add_row(&[1, 22222]);
+        table.set_align(1, Align::Center);
+        assert_eq!(table.column_widths(), vec![1, 5, 1]);
+        assert_eq!(table.lines(), vec!["a   bb    c", "1  22222"]);
+
+        table.set_max_width(6);
+        table.set_padding(0);
+        assert_eq!(table.lines(), vec!["a bb c", "1222…"]);
+    }
+
+    #[test]
+    fn test_fit() {
+        assert_eq!(fit("日本語", 4, Align::Left, "…"), "日 …");
+        assert_eq!(fit("abc", 2, Align::Left, "..."), "ab");
+        assert_eq!(fit("ab", 5, Align::Center, "…"), " ab  ");
+        assert_eq!(fit("a\u{301}b", 2, Align::Right, "…"), "a\u{301}b");
+    }
+
+    #[test]
+    fn test_borders() {
+        let mut table = Table::new();
+        table.add_row(&["x\ny"]);
+        table.set_borders(BoxChars::new(Style::Double, Encoding::Unicode));
+        assert_eq!(table.to_string(), "╔═════╗\n║ x y ║\n╚═════╝\n");
+    }
+}
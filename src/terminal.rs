@@ -0,0 +1,660 @@
+//! Identifying the terminal and what it supports.
+
+use std::env;
+use std::fmt;
+use std::io::{self, Read, Write};
+use std::str;
+
+use async::async_stdin;
+use reply;
+
+/// The reply to a Primary Device Attributes query (DA1).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DeviceAttributes {
+    /// The conformance level: 1 for VT100-like terminals, 62 for VT220, 63 for VT320, 64 for
+    /// VT420, and 65 for VT510 and later.
+    pub conformance: u16,
+    /// The extensions supported, such as 4 (sixel graphics), 22 (ANSI colors) or 28 (rectangular
+    /// editing).
+    pub attributes: Vec<u16>,
+}
+
+impl DeviceAttributes {
+    /// Is the extension `attribute` supported?
+    pub fn has(&self, attribute: u16) -> bool {
+        self.attributes.contains(&attribute)
+    }
+
+    /// Are the rectangular area operations of the `rect` module supported?
+    pub fn rectangular_editing(&self) -> bool {
+        self.conformance >= 64 || self.has(28)
+    }
+
+    /// Are sixel graphics supported?
+    pub fn sixel(&self) -> bool {
+        self.has(4)
+    }
+}
+
+/// Types that allow querying the attributes of the terminal.
+pub trait DetectDeviceAttributes {
+    /// Get the Primary Device Attributes of the terminal, using `ESC [ c`.
+    fn device_attributes(&mut self) -> io::Result<DeviceAttributes>;
+}
+
+impl<W: Write> DetectDeviceAttributes for W {
+    fn device_attributes(&mut self) -> io::Result<DeviceAttributes> {
+        let mut stdin = async_stdin();
+        query_device_attributes(self, &mut stdin)
+    }
+}
+
+/// Query the Primary Device Attributes, reading the reply from `stdin`.
+pub(crate) fn query_device_attributes(stdout: &mut dyn Write,
+                                     stdin: &mut dyn Read)
+                                     -> io::Result<DeviceAttributes> {
+    write!(stdout, csi!("c"))?;
+    stdout.flush()?;
+
+    // The answer will look like `ESC [ ? conformance ; attributes... c`.
+    let reply = reply::read_until(stdin, |buf| parse_device_attributes(buf).is_some())?;
+    parse_device_attributes(&reply).ok_or_else(|| {
+        io::Error::new(io::ErrorKind::TimedOut, "Device attributes query timed out.")
+    })
+}
+
+fn parse_device_attributes(reply: &[u8]) -> Option<DeviceAttributes> {
+    let start = reply.windows(3).rposition(|w| w == b"\x1B[?")?;
+    let params = str::from_utf8(&reply[start + 3..]).ok()?.strip_suffix('c')?;
+    let mut params = params.split(';').map(|n| n.parse().ok());
+
+    Some(DeviceAttributes {
+        conformance: params.next()??,
+        attributes: params.collect::<Option<_>>()?,
+    })
+}
+
+/// The name and version of the terminal emulator, as reported to XTVERSION.
+///
+/// Emulators format the report in their own way, such as `XTerm(367)`, `kitty(0.26.5)`,
+/// `iTerm2 3.4.19`, `WezTerm 20220408-101518-b908e2dd` or `foot(1.13.1)`; both the
+/// `name(version)` and the `name version` forms are split. A terminal multiplexer, such as tmux,
+/// reports itself rather than the terminal it runs in.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct TerminalId {
+    /// The name of the emulator, as reported (e.g. `XTerm` or `kitty`).
+    pub name: String,
+    /// The version of the emulator, if reported.
+    pub version: Option<String>,
+}
+
+impl TerminalId {
+    /// Is this the emulator `name`, ignoring the case?
+    pub fn is(&self, name: &str) -> bool {
+        self.name.eq_ignore_ascii_case(name)
+    }
+}
+
+impl fmt::Display for TerminalId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.version {
+            Some(ref version) => write!(f, "{} {}", self.name, version),
+            None => write!(f, "{}", self.name),
+        }
+    }
+}
+
+/// Types that allow querying the name and version of the terminal emulator.
+pub trait DetectTerminalId {
+    /// Get the name and version of the terminal emulator, using XTVERSION (`ESC [ > 0 q`).
+    ///
+    /// Primary Device Attributes are queried right after, which every terminal answers: a
+    /// terminal answering them but not XTVERSION gives an `Unsupported` error, without waiting
+    /// for the timeout.
+    fn terminal_id(&mut self) -> io::Result<TerminalId>;
+}
+
+impl<W: Write> DetectTerminalId for W {
+    fn terminal_id(&mut self) -> io::Result<TerminalId> {
+        let mut stdin = async_stdin();
+        query_terminal_id(self, &mut stdin)
+    }
+}
+
+/// Query the name and version of the terminal emulator, reading the reply from `stdin`.
+pub(crate) fn query_terminal_id(stdout: &mut dyn Write,
+                               stdin: &mut dyn Read)
+                               -> io::Result<TerminalId> {
+    write!(stdout, csi!(">0q"))?;
+    write!(stdout, csi!("c"))?;
+    stdout.flush()?;
+
+    // The answer will look like `ESC P > | name(version) ESC \`, followed by the device
+    // attributes.
+    let reply = reply::read_until(stdin, |buf| parse_device_attributes(buf).is_some())?;
+    if let Some(id) = parse_terminal_id(&reply) {
+        Ok(id)
+    } else if parse_device_attributes(&reply).is_some() {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "XTVERSION is not supported."))
+    } else {
+        Err(io::Error::new(io::ErrorKind::TimedOut, "Terminal version query timed out."))
+    }
+}
+
+fn parse_terminal_id(reply: &[u8]) -> Option<TerminalId> {
+    let start = reply.windows(4).position(|w| w == b"\x1BP>|")?;
+    let text = reply::split_strings(&reply[start + 4..]).next()?;
+    let text = String::from_utf8_lossy(text);
+    let text = text.trim();
+
+    let (name, version) = match text.strip_suffix(')').and_then(|t| t.split_once('(')) {
+        Some((name, version)) => (name, version),
+        None => text.split_once(' ').unwrap_or((text, "")),
+    };
+    let (name, version) = (name.trim(), version.trim());
+    if name.is_empty() {
+        return None;
+    }
+
+    Some(TerminalId {
+        name: name.to_string(),
+        version: if version.is_empty() {
+            None
+        } else {
+            Some(version.to_string())
+        },
+    })
+}
+
+/// The known shortcomings of a terminal emulator, which termion works around.
+///
+/// They are looked up in a small database of emulators, by the `TERM`, `TERM_PROGRAM` and
+/// `VTE_VERSION` environment variables, and by what the terminal reports to XTVERSION. An
+/// unknown terminal has no quirks.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Quirks {
+    /// 24-bit colors are not drawn, or not correctly (e.g. Terminal.app or the Linux console).
+    ///
+    /// `ColorDepth::detect` gives at most the 256-color palette then.
+    pub no_truecolor: bool,
+    /// Mouse events can't be reported in the SGR encoding (`?1006`), only in the urxvt one
+    /// (`?1015`), as with urxvt.
+    ///
+    /// `MouseTerminal` leaves the SGR encoding alone then.
+    pub no_sgr_mouse: bool,
+    /// Bracketed paste is broken, garbling the pasted text (e.g. VTE before 0.40).
+    ///
+    /// `BracketedPasteTerminal` doesn't enable it then.
+    pub broken_bracketed_paste: bool,
+}
+
+/// How an entry of the quirk database recognizes a terminal.
+enum Match {
+    /// `TERM` is this, or starts with this and a dash.
+    Term(&'static str),
+    /// `TERM_PROGRAM` is this.
+    Program(&'static str),
+    /// This is VTE, older than this `VTE_VERSION` (e.g. 4000 for 0.40), as told by the
+    /// environment or XTVERSION.
+    VteBefore(u32),
+}
+
+const NO_QUIRKS: Quirks = Quirks {
+    no_truecolor: false,
+    no_sgr_mouse: false,
+    broken_bracketed_paste: false,
+};
+
+/// The quirk database.
+const DATABASE: &[(Match, Quirks)] = &[
+    (Match::Program("Apple_Terminal"), Quirks { no_truecolor: true, ..NO_QUIRKS }),
+    (Match::Term("linux"), Quirks { no_truecolor: true, ..NO_QUIRKS }),
+    (Match::Term("rxvt-unicode"), Quirks { no_sgr_mouse: true, ..NO_QUIRKS }),
+    (Match::VteBefore(4000), Quirks { broken_bracketed_paste: true, ..NO_QUIRKS }),
+];
+
+/// What the environment tells about the terminal.
+#[derive(Clone, Debug, Default)]
+struct Environment {
+    term: String,
+    term_program: String,
+    vte_version: Option<u32>,
+}
+
+impl Environment {
+    fn current() -> Environment {
+        Environment {
+            term: env::var("TERM").unwrap_or_default(),
+            term_program: env::var("TERM_PROGRAM").unwrap_or_default(),
+            vte_version: env::var("VTE_VERSION").ok().and_then(|v| v.trim().parse().ok()),
+        }
+    }
+}
+
+impl Quirks {
+    /// The quirks of the terminal, going by the environment variables only.
+    pub fn from_env() -> Quirks {
+        Quirks::lookup(None, &Environment::current())
+    }
+
+    /// The quirks of the terminal `id`, as reported to XTVERSION, and of the terminal the
+    /// environment variables tell about.
+    pub fn of(id: &TerminalId) -> Quirks {
+        Quirks::lookup(Some(id), &Environment::current())
+    }
+
+    /// The quirks of the terminal, asking its name to XTVERSION through `stdout`.
+    ///
+    /// When the terminal doesn't answer, this goes by the environment variables only. Beware:
+    /// the terminal must be in raw mode, or the reply is only read once the user presses enter.
+    pub fn detect<W: Write>(stdout: &mut W) -> Quirks {
+        match stdout.terminal_id() {
+            Ok(id) => Quirks::of(&id),
+            Err(_) => Quirks::from_env(),
+        }
+    }
+
+    fn lookup(id: Option<&TerminalId>, env: &Environment) -> Quirks {
+        // Newer VTE report their version as `VTE(7600)`, in the format of `VTE_VERSION`.
+        let vte_version = match id {
+            Some(id) if id.is("VTE") => id.version.as_ref().and_then(|v| v.parse().ok()),
+            _ => env.vte_version,
+        };
+
+        let mut quirks = NO_QUIRKS;
+        for (pattern, entry) in DATABASE {
+            let matched = match *pattern {
+                Match::Term(term) => {
+                    env.term == term ||
+                    env.term.strip_prefix(term).is_some_and(|rest| rest.starts_with('-'))
+                }
+                Match::Program(program) => env.term_program == program,
+                Match::VteBefore(version) => vte_version.is_some_and(|v| v < version),
+            };
+            if matched {
+                quirks.no_truecolor |= entry.no_truecolor;
+                quirks.no_sgr_mouse |= entry.no_sgr_mouse;
+                quirks.broken_bracketed_paste |= entry.broken_bracketed_paste;
+            }
+        }
+        quirks
+    }
+}
+
+/// A setting of the terminal which can be requested with DECRQSS.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Setting {
+    /// The current graphic rendition (SGR).
+    GraphicRendition,
+    /// The top and bottom margins of the scroll region (DECSTBM).
+    ScrollMargins,
+    /// The left and right margins (DECSLRM).
+    LeftRightMargins,
+    /// The shape of the cursor (DECSCUSR).
+    CursorStyle,
+}
+
+impl Setting {
+    /// The final bytes of the control sequence setting it.
+    fn selector(&self) -> &'static str {
+        match *self {
+            Setting::GraphicRendition => "m",
+            Setting::ScrollMargins => "r",
+            Setting::LeftRightMargins => "s",
+            Setting::CursorStyle => " q",
+        }
+    }
+}
+
+/// The value of a setting, as reported to DECRQSS.
+///
+/// Writing it (through `Display`) sets the setting to this value again, so that a setting
+/// changed by the application can be restored.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct SettingValue {
+    /// The setting.
+    pub setting: Setting,
+    /// The parameters of the control sequence setting it, e.g. `0;1;31` for bold red text.
+    pub params: String,
+}
+
+impl SettingValue {
+    /// The numeric parameters, with omitted ones given as 0, or `None` if some aren't numbers
+    /// (such as the `:`-separated extended colors of SGR).
+    pub fn numbers(&self) -> Option<Vec<u16>> {
+        if self.params.is_empty() {
+            return Some(Vec::new());
+        }
+        self.params
+            .split(';')
+            .map(|n| if n.is_empty() { Some(0) } else { n.parse().ok() })
+            .collect()
+    }
+
+    /// The first and last rows of the scroll region, or the first and last columns between the
+    /// left and right margins.
+    pub fn margins(&self) -> Option<(u16, u16)> {
+        match self.numbers()?[..] {
+            [first, last] => Some((first, last)),
+            _ => None,
+        }
+    }
+
+    /// The cursor style: 0 or 1 for a blinking block, 2 for a steady block, 3 and 4 for a
+    /// blinking and a steady underline, 5 and 6 for a blinking and a steady bar.
+    pub fn cursor_style(&self) -> Option<u16> {
+        match self.numbers()?[..] {
+            [] => Some(0),
+            [style] => Some(style),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for SettingValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, csi!("{}{}"), self.params, self.setting.selector())
+    }
+}
+
+/// Request the value of a setting (DECRQSS).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RequestSetting(pub Setting);
+
+impl fmt::Display for RequestSetting {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\x1BP$q{}\x1B\\", self.0.selector())
+    }
+}
+
+/// Types that allow querying the current settings of the terminal.
+pub trait DetectSetting {
+    /// Get the current value of `setting`, using DECRQSS (`ESC P $ q`).
+    ///
+    /// As for `terminal_id`, Primary Device Attributes are queried right after: a terminal not
+    /// supporting DECRQSS, or not this setting, gives an `Unsupported` error.
+    fn setting(&mut self, setting: Setting) -> io::Result<SettingValue>;
+}
+
+impl<W: Write> DetectSetting for W {
+    fn setting(&mut self, setting: Setting) -> io::Result<SettingValue> {
+        let mut stdin = async_stdin();
+        query_setting(self, &mut stdin, setting)
+    }
+}
+
+/// Query the value of `setting`, reading the reply from `stdin`.
+pub(crate) fn query_setting(stdout: &mut dyn Write,
+                           stdin: &mut dyn Read,
+                           setting: Setting)
+                           -> io::Result<SettingValue> {
+    write!(stdout, "{}", RequestSetting(setting))?;
+    write!(stdout, csi!("c"))?;
+    stdout.flush()?;
+
+    // The answer will look like `ESC P 1 $ r value ESC \`, followed by the device attributes.
+    let reply = reply::read_until(stdin, |buf| parse_device_attributes(buf).is_some())?;
+    if let Some(value) = parse_setting(&reply, setting) {
+        Ok(value)
+    } else if parse_device_attributes(&reply).is_some() {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "The setting can't be requested."))
+    } else {
+        Err(io::Error::new(io::ErrorKind::TimedOut, "Setting request timed out."))
+    }
+}
+
+fn parse_setting(reply: &[u8], setting: Setting) -> Option<SettingValue> {
+    let start = reply.windows(2).position(|w| w == b"\x1BP")?;
+    let text = reply::split_strings(&reply[start + 2..]).next()?;
+    let text = str::from_utf8(text).ok()?;
+
+    // Old xterm versions swapped the meaning of 0 and 1, so the status is told by the value:
+    // an invalid request is answered with an empty one.
+    let (_status, value) = text.split_once("$r")?;
+    let params = value.strip_suffix(setting.selector())?;
+
+    Some(SettingValue {
+        setting,
+        params: params.to_string(),
+    })
+}
+
+/// The state of a mode, as reported to DECRQM.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ModeState {
+    /// The terminal doesn't know the mode.
+    NotRecognized,
+    /// The mode is set.
+    Set,
+    /// The mode is reset.
+    Reset,
+    /// The mode is set, and can't be reset.
+    PermanentlySet,
+    /// The mode is reset, and can't be set.
+    PermanentlyReset,
+}
+
+impl ModeState {
+    /// Can the mode be changed?
+    pub fn is_supported(&self) -> bool {
+        matches!(*self, ModeState::Set | ModeState::Reset)
+    }
+
+    /// Is the mode set, be it for good or not?
+    pub fn is_set(&self) -> bool {
+        matches!(*self, ModeState::Set | ModeState::PermanentlySet)
+    }
+
+    fn from_code(code: u16) -> Option<ModeState> {
+        match code {
+            0 => Some(ModeState::NotRecognized),
+            1 => Some(ModeState::Set),
+            2 => Some(ModeState::Reset),
+            3 => Some(ModeState::PermanentlySet),
+            4 => Some(ModeState::PermanentlyReset),
+            _ => None,
+        }
+    }
+}
+
+/// Request the state of the DEC private mode with this number (DECRQM), such as 1006 for SGR
+/// mouse reports, 2004 for bracketed paste or 2026 for synchronized output.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RequestMode(pub u16);
+
+impl fmt::Display for RequestMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, csi!("?{}$p"), self.0)
+    }
+}
+
+/// Types that allow querying the state of the modes of the terminal.
+pub trait DetectMode {
+    /// Get the state of the DEC private mode `mode`, using DECRQM (`ESC [ ? mode $ p`).
+    ///
+    /// As for `terminal_id`, Primary Device Attributes are queried right after: a terminal not
+    /// supporting DECRQM gives an `Unsupported` error, while one supporting it reports the modes
+    /// it doesn't know as `NotRecognized`.
+    fn mode(&mut self, mode: u16) -> io::Result<ModeState>;
+}
+
+impl<W: Write> DetectMode for W {
+    fn mode(&mut self, mode: u16) -> io::Result<ModeState> {
+        let mut stdin = async_stdin();
+        query_mode(self, &mut stdin, mode)
+    }
+}
+
+/// Query the state of `mode`, reading the reply from `stdin`.
+pub(crate) fn query_mode(stdout: &mut dyn Write,
+                        stdin: &mut dyn Read,
+                        mode: u16)
+                        -> io::Result<ModeState> {
+    write!(stdout, "{}", RequestMode(mode))?;
+    write!(stdout, csi!("c"))?;
+    stdout.flush()?;
+
+    // The answer will look like `ESC [ ? mode ; state $ y`, followed by the device attributes.
+    let reply = reply::read_until(stdin, |buf| parse_device_attributes(buf).is_some())?;
+    if let Some(state) = parse_mode(&reply, mode) {
+        Ok(state)
+    } else if parse_device_attributes(&reply).is_some() {
+        Err(io::Error::new(io::ErrorKind::Unsupported, "DECRQM is not supported."))
+    } else {
+        Err(io::Error::new(io::ErrorKind::TimedOut, "Mode request timed out."))
+    }
+}
+
+fn parse_mode(reply: &[u8], mode: u16) -> Option<ModeState> {
+    let end = reply.windows(2).position(|w| w == b"$y")?;
+    let start = reply[..end].windows(3).rposition(|w| w == b"\x1B[?")?;
+    let params = str::from_utf8(&reply[start + 3..end]).ok()?;
+    let (number, state) = params.split_once(';')?;
+    if number.parse() != Ok(mode) {
+        return None;
+    }
+    ModeState::from_code(state.parse().ok()?)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_attributes() {
+        let attrs = parse_device_attributes(b"\x1B[?64;1;2;6;9;15;18;21;22;28c").unwrap();
+        assert_eq!(attrs.conformance, 64);
+        assert!(attrs.has(22));
+        assert!(attrs.rectangular_editing());
+        assert!(!attrs.sixel());
+
+        let attrs = parse_device_attributes(b"\x1B[?1;2c").unwrap();
+        assert_eq!(attrs, DeviceAttributes { conformance: 1, attributes: vec![2] });
+        assert!(!attrs.rectangular_editing());
+
+        assert_eq!(parse_device_attributes(b"\x1B[?64;1"), None);
+    }
+
+    #[test]
+    fn test_query_device_attributes() {
+        let mut stdout = Vec::new();
+        let mut stdin: &[u8] = b"\x1B[?62;4c";
+        let attrs = query_device_attributes(&mut stdout, &mut stdin).unwrap();
+        assert_eq!(stdout, b"\x1B[c");
+        assert!(attrs.sixel());
+    }
+
+    fn id(name: &str, version: Option<&str>) -> TerminalId {
+        TerminalId {
+            name: name.to_string(),
+            version: version.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_parse_terminal_id() {
+        assert_eq!(parse_terminal_id(b"\x1BP>|XTerm(367)\x1B\\"), Some(id("XTerm", Some("367"))));
+        assert_eq!(parse_terminal_id(b"\x1BP>|kitty(0.26.5)\x1B\\"),
+                   Some(id("kitty", Some("0.26.5"))));
+        assert_eq!(parse_terminal_id(b"\x1BP>|iTerm2 3.4.19\x1B\\"),
+                   Some(id("iTerm2", Some("3.4.19"))));
+        assert_eq!(parse_terminal_id(b"\x1BP>|WezTerm 20220408-101518-b908e2dd\x1B\\"),
+                   Some(id("WezTerm", Some("20220408-101518-b908e2dd"))));
+        assert_eq!(parse_terminal_id(b"\x1BP>|foot(1.13.1)\x07"), Some(id("foot", Some("1.13.1"))));
+        assert_eq!(parse_terminal_id(b"\x1BP>|tmux\x1B\\"), Some(id("tmux", None)));
+        assert_eq!(parse_terminal_id(b"\x1BP>|\x1B\\"), None);
+        assert_eq!(parse_terminal_id(b"\x1BP>|XTerm(367)"), None);
+        assert!(id("XTerm", None).is("xterm"));
+        assert_eq!(id("kitty", Some("0.26.5")).to_string(), "kitty 0.26.5");
+    }
+
+    fn env(term: &str, term_program: &str, vte_version: Option<u32>) -> Environment {
+        Environment {
+            term: term.to_string(),
+            term_program: term_program.to_string(),
+            vte_version,
+        }
+    }
+
+    #[test]
+    fn test_quirks() {
+        let quirks = Quirks::lookup(None, &env("xterm-256color", "Apple_Terminal", None));
+        assert_eq!(quirks, Quirks { no_truecolor: true, ..Quirks::default() });
+        let quirks = Quirks::lookup(None, &env("rxvt-unicode-256color", "", Some(3800)));
+        assert!(quirks.no_sgr_mouse && quirks.broken_bracketed_paste && !quirks.no_truecolor);
+        assert_eq!(Quirks::lookup(None, &env("linuxish", "", Some(6003))), Quirks::default());
+
+        // What XTVERSION reports takes precedence over the environment.
+        let vte = id("VTE", Some("7600"));
+        assert_eq!(Quirks::lookup(Some(&vte), &env("xterm", "", Some(3800))), Quirks::default());
+        let quirks = Quirks::lookup(Some(&id("VTE", Some("3405"))), &env("xterm", "", None));
+        assert!(quirks.broken_bracketed_paste);
+    }
+
+    #[test]
+    fn test_parse_setting() {
+        let sgr = parse_setting(b"\x1BP1$r0;1;31m\x1B\\", Setting::GraphicRendition).unwrap();
+        assert_eq!(sgr.params, "0;1;31");
+        assert_eq!(sgr.numbers(), Some(vec![0, 1, 31]));
+        assert_eq!(sgr.to_string(), "\x1B[0;1;31m");
+
+        let margins = parse_setting(b"\x1BP1$r2;24r\x1B\\", Setting::ScrollMargins).unwrap();
+        assert_eq!(margins.margins(), Some((2, 24)));
+
+        let style = parse_setting(b"\x1BP1$r4 q\x1B\\", Setting::CursorStyle).unwrap();
+        assert_eq!(style.cursor_style(), Some(4));
+        assert_eq!(style.to_string(), "\x1B[4 q");
+
+        assert_eq!(parse_setting(b"\x1BP0$r\x1B\\", Setting::ScrollMargins), None);
+        assert_eq!(parse_setting(b"\x1BP1$r2;24r", Setting::ScrollMargins), None);
+    }
+
+    #[test]
+    fn test_query_setting() {
+        let mut stdout = Vec::new();
+        let mut stdin: &[u8] = b"\x1BP1$r1;40r\x1B\\\x1B[?62;4c";
+        let value = query_setting(&mut stdout, &mut stdin, Setting::ScrollMargins).unwrap();
+        assert_eq!(stdout, b"\x1BP$qr\x1B\\\x1B[c");
+        assert_eq!(value.margins(), Some((1, 40)));
+
+        let mut stdin: &[u8] = b"\x1BP0$r\x1B\\\x1B[?62;4c";
+        let err = query_setting(&mut Vec::new(), &mut stdin, Setting::CursorStyle).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn test_query_terminal_id() {
+        let mut stdout = Vec::new();
+        let mut stdin: &[u8] = b"\x1BP>|foot(1.13.1)\x1B\\\x1B[?62;4c";
+        let id = query_terminal_id(&mut stdout, &mut stdin).unwrap();
+        assert_eq!(stdout, b"\x1B[>0q\x1B[c");
+        assert!(id.is("foot"));
+
+        let mut stdin: &[u8] = b"\x1B[?62;4c";
+        let err = query_terminal_id(&mut Vec::new(), &mut stdin).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+
+    #[test]
+    fn test_parse_mode() {
+        assert_eq!(parse_mode(b"\x1B[?2004;2$y", 2004), Some(ModeState::Reset));
+        assert_eq!(parse_mode(b"\x1B[?2026;0$y", 2026), Some(ModeState::NotRecognized));
+        assert_eq!(parse_mode(b"\x1B[?1006;1$y", 2004), None);
+        assert_eq!(parse_mode(b"\x1B[?1006;9$y", 1006), None);
+        assert!(ModeState::Set.is_supported() && ModeState::Set.is_set());
+        assert!(!ModeState::PermanentlySet.is_supported() && ModeState::PermanentlySet.is_set());
+        assert!(!ModeState::NotRecognized.is_supported());
+    }
+
+    #[test]
+    fn test_query_mode() {
+        let mut stdout = Vec::new();
+        let mut stdin: &[u8] = b"\x1B[?1006;1$y\x1B[?62;4c";
+        assert_eq!(query_mode(&mut stdout, &mut stdin, 1006).unwrap(), ModeState::Set);
+        assert_eq!(stdout, b"\x1B[?1006$p\x1B[c");
+
+        let mut stdin: &[u8] = b"\x1B[?62;4c";
+        let err = query_mode(&mut Vec::new(), &mut stdin, 2004).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::Unsupported);
+    }
+}
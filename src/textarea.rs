@@ -0,0 +1,522 @@
+//! A multi-line text editing area.
+//!
+//! `TextArea` holds lines of text and a cursor, edits them as keys are handed to it, and draws
+//! them into an area of a `Buffer`. Long lines wrap to the width of the area, and the cursor
+//! moves across the wrapped rows as it is displayed. Shift with the arrows, Home or End selects
+//! text, which is replaced by what is typed next.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::buffer::Buffer;
+//! use termion::cursor;
+//! use termion::event::Key;
+//! use termion::input::TermRead;
+//! use termion::raw::IntoRawMode;
+//! use termion::rect::Rect;
+//! use termion::textarea::TextArea;
+//! use std::io::{stdin, stdout, Write};
+//!
+//! let mut stdout = stdout().into_raw_mode().unwrap();
+//! let mut buffer = Buffer::new(40, 10);
+//! let mut area = TextArea::new();
+//!
+//! for key in stdin().keys() {
+//!     match key.unwrap() {
+//!         Key::Esc => break,
+//!         key => area.handle_key(key),
+//!     };
+//!     let (x, y) = area.render(&mut buffer, Rect::new(1, 1, 40, 10));
+//!     buffer.flush(&mut stdout).unwrap();
+//!     write!(stdout, "{}", cursor::Goto(x, y)).unwrap();
+//!     stdout.flush().unwrap();
+//! }
+//! println!("{}", area.text());
+//! ```
+
+use buffer::{Buffer, CellStyle};
+use event::{Key, Modifiers};
+use rect::Rect;
+use width::{grapheme_width, graphemes, str_width};
+
+/// A position in the text: a line, counted from 0, and a byte offset in that line.
+///
+/// The offset is always at the start of a grapheme cluster, or at the end of the line.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Position {
+    /// The line.
+    pub line: usize,
+    /// The byte offset in the line.
+    pub offset: usize,
+}
+
+/// A row of the wrapped text.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Row {
+    line: usize,
+    start: usize,
+    end: usize,
+}
+
+/// An area of editable text.
+#[derive(Clone, Debug)]
+pub struct TextArea {
+    lines: Vec<String>,
+    cursor: Position,
+    anchor: Option<Position>,
+    /// The column the cursor goes back to when moving up and down through shorter rows.
+    goal: Option<usize>,
+    width: u16,
+    height: u16,
+    scroll: usize,
+}
+
+impl Default for TextArea {
+    fn default() -> TextArea {
+        TextArea::new()
+    }
+}
+
+impl TextArea {
+    /// An empty text area.
+    pub fn new() -> TextArea {
+        TextArea {
+            lines: vec![String::new()],
+            cursor: Position::default(),
+            anchor: None,
+            goal: None,
+            width: 80,
+            height: 24,
+            scroll: 0,
+        }
+    }
+
+    /// A text area holding `text`, with the cursor at its start.
+    pub fn from_text(text: &str) -> TextArea {
+        let mut area = TextArea::new();
+        area.insert_str(text);
+        area.cursor = Position::default();
+        area
+    }
+
+    /// The text, with its lines joined by `\n`.
+    pub fn text(&self) -> String {
+        self.lines.join("\n")
+    }
+
+    /// The lines of the text.
+    pub fn lines(&self) -> &[String] {
+        &self.lines
+    }
+
+    /// The position of the cursor.
+    pub fn cursor(&self) -> Position {
+        self.cursor
+    }
+
+    /// Move the cursor to `pos`, clearing the selection.
+    ///
+    /// A position past the end of its line, or not at the start of a grapheme cluster, is moved
+    /// back to one.
+    pub fn set_cursor(&mut self, pos: Position) {
+        let line = pos.line.min(self.lines.len() - 1);
+        let text = &self.lines[line];
+        let mut offset = 0;
+        for grapheme in graphemes(text) {
+            if offset + grapheme.len() > pos.offset {
+                break;
+            }
+            offset += grapheme.len();
+        }
+        self.cursor = Position { line, offset };
+        self.anchor = None;
+        self.goal = None;
+    }
+
+    /// The start and end of the selection, if there is one.
+    pub fn selection(&self) -> Option<(Position, Position)> {
+        match self.anchor {
+            Some(anchor) if anchor != self.cursor => {
+                Some((anchor.min(self.cursor), anchor.max(self.cursor)))
+            }
+            _ => None,
+        }
+    }
+
+    /// The selected text, with its lines joined by `\n`.
+    pub fn selected_text(&self) -> Option<String> {
+        let (start, end) = self.selection()?;
+        if start.line == end.line {
+            return Some(self.lines[start.line][start.offset..end.offset].to_string());
+        }
+
+        let mut text = self.lines[start.line][start.offset..].to_string();
+        for line in &self.lines[start.line + 1..end.line] {
+            text.push('\n');
+            text.push_str(line);
+        }
+        text.push('\n');
+        text.push_str(&self.lines[end.line][..end.offset]);
+        Some(text)
+    }
+
+    /// Delete the selected text, returning whether there was any.
+    pub fn delete_selection(&mut self) -> bool {
+        let (start, end) = match self.selection() {
+            Some(selection) => selection,
+            None => return false,
+        };
+
+        let rest = self.lines[end.line][end.offset..].to_string();
+        self.lines.drain(start.line + 1..=end.line);
+        let line = &mut self.lines[start.line];
+        line.truncate(start.offset);
+        line.push_str(&rest);
+        self.cursor = start;
+        self.anchor = None;
+        self.goal = None;
+        true
+    }
+
+    /// Insert `text` at the cursor, replacing the selection.
+    ///
+    /// Control characters other than newlines are dropped.
+    pub fn insert_str(&mut self, text: &str) {
+        self.delete_selection();
+        self.goal = None;
+        for (i, part) in text.split('\n').enumerate() {
+            if i > 0 {
+                self.split_line();
+            }
+            let part: String = part.chars().filter(|c| !c.is_control()).collect();
+            let Position { line, offset } = self.cursor;
+            self.lines[line].insert_str(offset, &part);
+            self.cursor.offset += part.len();
+        }
+    }
+
+    /// Handle `key`, returning whether it is one the text area uses.
+    ///
+    /// Besides typing and `Enter`, `Backspace` and `Delete`:
+    ///
+    /// - The arrow keys, `Home` and `End`, and `PageUp` and `PageDown` move the cursor.
+    /// - Ctrl with the left and right arrows moves by word.
+    /// - Shift with the arrows, `Home` and `End` extends the selection, also when moving by word.
+    pub fn handle_key(&mut self, key: Key) -> bool {
+        let (key, select, word) = match key {
+            Key::ModifiedCursor(Modifiers { alt: false, meta: false, shift, ctrl }, key) => {
+                (key.into(), shift, ctrl)
+            }
+            Key::ModifiedCursor(..) => return false,
+            key => (key, false, false),
+        };
+        let goal = self.goal;
+        let page = self.height.max(1) as isize;
+        match key {
+            Key::Left if word => self.with_selection(select, Self::move_word_left),
+            Key::Right if word => self.with_selection(select, Self::move_word_right),
+            _ if word => return false,
+            Key::Char('\n') => self.insert_str("\n"),
+            Key::Char(c) if !c.is_control() => self.insert_str(c.encode_utf8(&mut [0; 4])),
+            Key::Backspace => {
+                if !self.delete_selection() {
+                    self.anchor = Some(self.cursor);
+                    self.move_left();
+                    self.delete_selection();
+                }
+            }
+            Key::Delete => {
+                if !self.delete_selection() {
+                    self.anchor = Some(self.cursor);
+                    self.move_right();
+                    self.delete_selection();
+                }
+            }
+            Key::Left => self.with_selection(select, Self::move_left),
+            Key::Right => self.with_selection(select, Self::move_right),
+            Key::Up => self.with_selection(select, |area| area.move_rows(-1, goal)),
+            Key::Down => self.with_selection(select, |area| area.move_rows(1, goal)),
+            Key::Home => self.with_selection(select, |area| area.cursor.offset = 0),
+            Key::End => {
+                self.with_selection(select, |area| {
+                    area.cursor.offset = area.lines[area.cursor.line].len()
+                })
+            }
+            Key::PageUp => self.with_selection(false, |area| area.move_rows(-page, goal)),
+            Key::PageDown => self.with_selection(false, |area| area.move_rows(page, goal)),
+            _ => return false,
+        }
+        true
+    }
+
+    /// Draw the text into `area` of `buffer`, scrolling it so that the cursor is visible.
+    ///
+    /// The selection is drawn in inverted colors. This returns the (1,1)-based position of the
+    /// cursor on the screen, for `cursor::Goto`, which is the corner of `area` if it is empty.
+    pub fn render(&mut self, buffer: &mut Buffer, area: Rect) -> (u16, u16) {
+        if area.is_empty() {
+            return (area.x, area.y);
+        }
+        self.width = area.width;
+        self.height = area.height;
+        let rows = self.rows();
+        let (cursor_row, cursor_column) = self.visual(&rows, self.cursor);
+        if cursor_row < self.scroll {
+            self.scroll = cursor_row;
+        } else if cursor_row >= self.scroll + area.height as usize {
+            self.scroll = cursor_row + 1 - area.height as usize;
+        }
+
+        let blank = " ".repeat(area.width as usize);
+        let normal = CellStyle::default();
+        let inverted = CellStyle { invert: true, ..normal };
+        let selection = self.selection();
+        for (i, y) in (area.y..area.y + area.height).enumerate() {
+            buffer.set_str(area.x, y, &blank, normal);
+            let row = match rows.get(self.scroll + i) {
+                Some(row) => row,
+                None => continue,
+            };
+
+            let mut x = area.x;
+            let mut offset = row.start;
+            for grapheme in graphemes(&self.lines[row.line][row.start..row.end]) {
+                let pos = Position { line: row.line, offset };
+                let selected = selection.is_some_and(|(start, end)| start <= pos && pos < end);
+                let style = if selected { inverted } else { normal };
+                x += buffer.set_str(x, y, grapheme, style);
+                offset += grapheme.len();
+            }
+        }
+
+        let x = area.x + (cursor_column as u16).min(area.width.saturating_sub(1));
+        let y = area.y + (cursor_row - self.scroll) as u16;
+        (x, y)
+    }
+
+    /// Run `motion`, extending the selection if `select` is set and clearing it otherwise.
+    ///
+    /// The goal column is forgotten, unless `motion` sets it again.
+    fn with_selection<F: FnOnce(&mut TextArea)>(&mut self, select: bool, motion: F) {
+        if select {
+            self.anchor.get_or_insert(self.cursor);
+        } else {
+            self.anchor = None;
+        }
+        self.goal = None;
+        motion(self);
+        if select && self.anchor == Some(self.cursor) {
+            self.anchor = None;
+        }
+    }
+
+    fn split_line(&mut self) {
+        let Position { line, offset } = self.cursor;
+        let rest = self.lines[line].split_off(offset);
+        self.lines.insert(line + 1, rest);
+        self.cursor = Position { line: line + 1, offset: 0 };
+    }
+
+    fn move_left(&mut self) {
+        let Position { line, offset } = self.cursor;
+        if offset > 0 {
+            let before = &self.lines[line][..offset];
+            let last = graphemes(before).last().map_or(0, str::len);
+            self.cursor.offset -= last;
+        } else if line > 0 {
+            self.cursor = Position { line: line - 1, offset: self.lines[line - 1].len() };
+        }
+    }
+
+    fn move_right(&mut self) {
+        let Position { line, offset } = self.cursor;
+        match graphemes(&self.lines[line][offset..]).next() {
+            Some(next) => self.cursor.offset += next.len(),
+            None if line + 1 < self.lines.len() => {
+                self.cursor = Position { line: line + 1, offset: 0 };
+            }
+            None => {}
+        }
+    }
+
+    fn move_word_left(&mut self) {
+        if self.cursor.offset == 0 {
+            return self.move_left();
+        }
+        let text = &self.lines[self.cursor.line][..self.cursor.offset];
+        let trimmed = text.trim_end();
+        let start = trimmed.rfind(char::is_whitespace).map_or(0, |i| {
+            i + trimmed[i..].chars().next().map_or(0, char::len_utf8)
+        });
+        self.cursor.offset = start;
+    }
+
+    fn move_word_right(&mut self) {
+        let line = &self.lines[self.cursor.line];
+        if self.cursor.offset == line.len() {
+            return self.move_right();
+        }
+        let rest = &line[self.cursor.offset..];
+        let word = rest.len() - rest.trim_start().len();
+        let end = rest[word..].find(char::is_whitespace).map_or(rest.len(), |i| word + i);
+        self.cursor.offset += end;
+    }
+
+    /// Move the cursor by `delta` rows of the wrapped text, keeping to the column `goal`, or to
+    /// its current column.
+    fn move_rows(&mut self, delta: isize, goal: Option<usize>) {
+        let rows = self.rows();
+        let (row, column) = self.visual(&rows, self.cursor);
+        let goal = goal.unwrap_or(column);
+        let target = (row as isize + delta).clamp(0, rows.len() as isize - 1) as usize;
+        let row = rows[target];
+
+        // The end of a row which isn't the end of its line is the start of the next row.
+        let last = rows.get(target + 1).is_none_or(|next| next.line != row.line);
+        let mut offset = row.start;
+        let mut width = 0;
+        for grapheme in graphemes(&self.lines[row.line][row.start..row.end]) {
+            width += grapheme_width(grapheme);
+            if width > goal || (!last && offset + grapheme.len() == row.end) {
+                break;
+            }
+            offset += grapheme.len();
+        }
+        self.cursor = Position { line: row.line, offset };
+        self.goal = Some(goal);
+    }
+
+    /// The rows of the text, wrapped to the width of the area.
+    ///
+    /// A line as wide as the area is followed by an empty row, for the cursor at its end.
+    fn rows(&self) -> Vec<Row> {
+        let width = self.width.max(1) as usize;
+        let mut rows = Vec::new();
+        for (i, line) in self.lines.iter().enumerate() {
+            let mut start = 0;
+            let mut offset = 0;
+            let mut column = 0;
+            for grapheme in graphemes(line) {
+                let grapheme_width = grapheme_width(grapheme);
+                if column + grapheme_width > width && column > 0 {
+                    rows.push(Row { line: i, start, end: offset });
+                    start = offset;
+                    column = 0;
+                }
+                column += grapheme_width;
+                offset += grapheme.len();
+            }
+            rows.push(Row { line: i, start, end: line.len() });
+            if column >= width {
+                rows.push(Row { line: i, start: line.len(), end: line.len() });
+            }
+        }
+        rows
+    }
+
+    /// The row of `rows` and the column of `pos`.
+    fn visual(&self, rows: &[Row], pos: Position) -> (usize, usize) {
+        let row = rows.iter()
+            .rposition(|row| row.line == pos.line && row.start <= pos.offset)
+            .unwrap_or(0);
+        let start = rows[row].start;
+        (row, str_width(&self.lines[pos.line][start..pos.offset]))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use event::CursorKey;
+
+    fn typed(keys: &[Key]) -> TextArea {
+        let mut area = TextArea::new();
+        for &key in keys {
+            area.handle_key(key);
+        }
+        area
+    }
+
+    #[test]
+    fn test_editing() {
+        let mut area = TextArea::from_text("hello\nworld");
+        area.handle_key(Key::End);
+        area.handle_key(Key::Char('!'));
+        area.handle_key(Key::Delete);
+        assert_eq!(area.text(), "hello!world");
+        area.handle_key(Key::Char('\n'));
+        area.handle_key(Key::Backspace);
+        area.handle_key(Key::Backspace);
+        assert_eq!(area.text(), "helloworld");
+        assert_eq!(area.cursor(), Position { line: 0, offset: 5 });
+
+        let area = typed(&[Key::Char('e'), Key::Char('\u{301}'), Key::Left, Key::Char('a')]);
+        assert_eq!(area.text(), "ae\u{301}");
+    }
+
+    #[test]
+    fn test_selection() {
+        let shift = Modifiers::from_xterm(2);
+        let ctrl = Modifiers::from_xterm(5);
+        let mut area = TextArea::from_text("one two\nthree");
+        area.handle_key(Key::ModifiedCursor(ctrl, CursorKey::Right));
+        area.handle_key(Key::ModifiedCursor(shift, CursorKey::Right));
+        area.handle_key(Key::ModifiedCursor(shift, CursorKey::Down));
+        assert_eq!(area.selected_text().unwrap(), " two\nthre");
+        area.handle_key(Key::Char('-'));
+        assert_eq!(area.text(), "one-e");
+        assert!(area.selection().is_none());
+
+        area.handle_key(Key::ModifiedCursor(shift, CursorKey::Left));
+        area.handle_key(Key::ModifiedCursor(shift, CursorKey::Right));
+        assert!(area.selection().is_none());
+        area.handle_key(Key::ModifiedCursor(ctrl, CursorKey::Left));
+        assert_eq!(area.cursor().offset, 0);
+
+        area.handle_key(Key::ModifiedCursor(shift, CursorKey::End));
+        assert_eq!(area.selected_text().unwrap(), "one-e");
+        area.handle_key(Key::End);
+        area.handle_key(Key::Char(' '));
+        area.handle_key(Key::Char('x'));
+        area.handle_key(Key::ModifiedCursor(Modifiers::from_xterm(6), CursorKey::Left));
+        assert_eq!(area.selected_text().unwrap(), "x");
+    }
+
+    #[test]
+    fn test_wrapped_motion() {
+        let mut area = TextArea::from_text("abcdefgh\nxy");
+        let mut buffer = Buffer::new(4, 4);
+        let area_rect = Rect::new(1, 1, 4, 4);
+        assert_eq!(area.render(&mut buffer, area_rect), (1, 1));
+        area.handle_key(Key::Right);
+        area.handle_key(Key::Down);
+        assert_eq!(area.render(&mut buffer, area_rect), (2, 2));
+        area.handle_key(Key::Down);
+        assert_eq!(area.render(&mut buffer, area_rect), (1, 3));
+        area.handle_key(Key::Down);
+        assert_eq!(area.render(&mut buffer, area_rect), (2, 4));
+        area.handle_key(Key::Up);
+        area.handle_key(Key::Up);
+        assert_eq!(area.cursor(), Position { line: 0, offset: 5 });
+        assert_eq!(buffer.get(1, 2).unwrap().symbol, "e");
+    }
+
+    #[test]
+    fn test_scroll() {
+        let mut area = TextArea::from_text("1\n2\n3\n4");
+        area.handle_key(Key::ModifiedCursor(Modifiers::from_xterm(2), CursorKey::Down));
+        let mut buffer = Buffer::new(3, 2);
+        area.handle_key(Key::PageDown);
+        area.handle_key(Key::PageDown);
+        assert_eq!(area.render(&mut buffer, Rect::new(1, 1, 3, 2)), (1, 2));
+        assert_eq!(buffer.get(1, 1).unwrap().symbol, "3");
+        area.handle_key(Key::Up);
+        area.handle_key(Key::Up);
+        area.handle_key(Key::ModifiedCursor(Modifiers::from_xterm(2), CursorKey::Right));
+        assert_eq!(area.render(&mut buffer, Rect::new(1, 1, 3, 2)), (2, 1));
+        assert!(buffer.get(1, 1).unwrap().style.invert);
+        assert_eq!(buffer.get(1, 1).unwrap().symbol, "2");
+
+        assert_eq!(area.render(&mut buffer, Rect::new(1, 1, 10, 0)), (1, 1));
+    }
+}
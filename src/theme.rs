@@ -0,0 +1,171 @@
+//! Colors chosen by their role rather than by their value.
+//!
+//! A `Theme` gives the color of each role text can have, such as an error or a hint, so that
+//! an application can ask for "the error color" and look right on both dark and light
+//! terminals. The built-in themes use colors of the palette, which the user's own terminal
+//! theme usually adjusts, and `Theme::detect` picks the one fitting the background color of the
+//! terminal.
+//!
+//! # Example
+//!
+//! ```rust,no_run
+//! use termion::color::Fg;
+//! use termion::style;
+//! use termion::theme::{Role, Theme};
+//! use std::io::stdout;
+//!
+//! let theme = Theme::detect(&mut stdout());
+//! println!("{}error:{} file not found", Fg(theme.color(Role::Error)), style::Reset);
+//! ```
+
+use std::io::Write;
+
+use buffer::{CellColor, CellStyle};
+use color::{DetectDefaultColors, Rgb};
+
+/// The role of some text on the screen.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// What the user should look at first, such as titles or the focused item.
+    Primary,
+    /// Errors.
+    Error,
+    /// Warnings.
+    Warning,
+    /// Secondary text, such as help or placeholders.
+    Hint,
+    /// The background of selected text.
+    Selection,
+    /// Lines around and between parts of the screen.
+    Border,
+}
+
+/// The color of each role.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Theme {
+    /// The color of `Role::Primary`.
+    pub primary: CellColor,
+    /// The color of `Role::Error`.
+    pub error: CellColor,
+    /// The color of `Role::Warning`.
+    pub warning: CellColor,
+    /// The color of `Role::Hint`.
+    pub hint: CellColor,
+    /// The color of `Role::Selection`.
+    pub selection: CellColor,
+    /// The color of `Role::Border`.
+    pub border: CellColor,
+}
+
+impl Default for Theme {
+    fn default() -> Theme {
+        Theme::dark()
+    }
+}
+
+impl Theme {
+    /// The theme for a dark background.
+    pub fn dark() -> Theme {
+        Theme {
+            primary: CellColor::Ansi(12),
+            error: CellColor::Ansi(9),
+            warning: CellColor::Ansi(11),
+            hint: CellColor::Ansi(245),
+            selection: CellColor::Ansi(238),
+            border: CellColor::Ansi(240),
+        }
+    }
+
+    /// The theme for a light background.
+    pub fn light() -> Theme {
+        Theme {
+            primary: CellColor::Ansi(4),
+            error: CellColor::Ansi(1),
+            warning: CellColor::Ansi(3),
+            hint: CellColor::Ansi(242),
+            selection: CellColor::Ansi(252),
+            border: CellColor::Ansi(250),
+        }
+    }
+
+    /// The built-in theme for the background color `background`.
+    pub fn for_background(background: Rgb) -> Theme {
+        let Rgb(r, g, b) = background;
+        let luma = 0.299 * r as f32 + 0.587 * g as f32 + 0.114 * b as f32;
+        if luma < 128.0 {
+            Theme::dark()
+        } else {
+            Theme::light()
+        }
+    }
+
+    /// The built-in theme for the background color of the terminal `stdout` writes to.
+    ///
+    /// The color is queried with OSC 11. If the terminal doesn't report it, the dark theme is
+    /// used. Beware: the terminal must be in raw mode, or the reply is only read once the user
+    /// presses enter.
+    pub fn detect<W: Write>(stdout: &mut W) -> Theme {
+        stdout.background_color().map(Theme::for_background).unwrap_or_default()
+    }
+
+    /// The color of `role`.
+    pub fn color(&self, role: Role) -> CellColor {
+        match role {
+            Role::Primary => self.primary,
+            Role::Error => self.error,
+            Role::Warning => self.warning,
+            Role::Hint => self.hint,
+            Role::Selection => self.selection,
+            Role::Border => self.border,
+        }
+    }
+
+    /// Set the color of `role`.
+    pub fn set_color(&mut self, role: Role, color: CellColor) {
+        match role {
+            Role::Primary => self.primary = color,
+            Role::Error => self.error = color,
+            Role::Warning => self.warning = color,
+            Role::Hint => self.hint = color,
+            Role::Selection => self.selection = color,
+            Role::Border => self.border = color,
+        }
+    }
+
+    /// The style of text with `role`, for a `Buffer` or a `StyledStr`.
+    ///
+    /// The selection color is the background, and the others are the foreground.
+    pub fn style(&self, role: Role) -> CellStyle {
+        let color = self.color(role);
+        match role {
+            Role::Selection => CellStyle { bg: color, ..CellStyle::default() },
+            _ => CellStyle { fg: color, ..CellStyle::default() },
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use color::{Bg, Fg};
+
+    #[test]
+    fn test_for_background() {
+        assert_eq!(Theme::for_background(Rgb(0x1d, 0x20, 0x21)), Theme::dark());
+        assert_eq!(Theme::for_background(Rgb(0xfd, 0xf6, 0xe3)), Theme::light());
+        assert_eq!(Theme::for_background(Rgb(0, 0, 255)), Theme::dark());
+    }
+
+    #[test]
+    fn test_roles() {
+        let mut theme = Theme::light();
+        theme.set_color(Role::Error, CellColor::Rgb(Rgb(200, 0, 0)));
+        assert_eq!(theme.error, CellColor::Rgb(Rgb(200, 0, 0)));
+        assert_eq!(theme.style(Role::Selection).bg, CellColor::Ansi(252));
+        assert_eq!(theme.style(Role::Hint).fg, CellColor::Ansi(242));
+
+        assert_eq!(Fg(theme.color(Role::Error)).to_string(), "\x1B[38;2;200;0;0m");
+        assert_eq!(Bg(theme.color(Role::Primary)).to_string(), "\x1B[48;5;4m");
+        assert_eq!(Fg(CellColor::Reset).to_string(), "\x1B[39m");
+    }
+}
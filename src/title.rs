@@ -0,0 +1,126 @@
+//! Setting the window title.
+//!
+//! The previous title is lost once a new one is set, unless it was saved on the terminal's title
+//! stack first (XTWINOPS). `TitleTerminal` does so, and restores it when dropped.
+//!
+//! # Example
+//!
+#![cfg_attr(feature = "std", doc = "```rust")]
+#![cfg_attr(not(feature = "std"), doc = "```rust,ignore")]
+//! use termion::title::TitleTerminal;
+//! use std::io::stdout;
+//!
+//! fn main() {
+//!     let mut stdout = TitleTerminal::from(stdout());
+//!     stdout.set_title("Editing notes.txt").unwrap();
+//!     // The shell's title is back once `stdout` is dropped.
+//! }
+//! ```
+
+#[cfg(feature = "std")]
+use std::io::{self, Write};
+#[cfg(feature = "std")]
+use std::ops;
+use std::fmt;
+
+derive_csi_sequence!("Save the window and icon titles on the title stack.", PushTitle, "22;0t");
+derive_csi_sequence!("Restore the window and icon titles from the title stack.", PopTitle, "23;0t");
+
+/// Set the window title, using OSC 2.
+///
+/// Control characters are left out, so that the title can't end the sequence early.
+#[derive(Copy, Clone, Debug)]
+pub struct SetTitle<'a>(pub &'a str);
+
+impl<'a> fmt::Display for SetTitle<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("\x1B]2;")?;
+        for c in self.0.chars().filter(|c| !c.is_control()) {
+            write!(f, "{}", c)?;
+        }
+        f.write_str("\x07")
+    }
+}
+
+/// A terminal restorer, which saves the window title when created and restores it when dropped.
+#[cfg(feature = "std")]
+pub struct TitleTerminal<W: Write> {
+    output: W,
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> TitleTerminal<W> {
+    /// Set the window title until this terminal is dropped.
+    pub fn set_title(&mut self, title: &str) -> io::Result<()> {
+        write!(self.output, "{}", SetTitle(title))?;
+        self.output.flush()
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> From<W> for TitleTerminal<W> {
+    fn from(mut output: W) -> TitleTerminal<W> {
+        write!(output, "{}", PushTitle).expect("save window title");
+        TitleTerminal { output }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Drop for TitleTerminal<W> {
+    fn drop(&mut self) {
+        write!(self.output, "{}", PopTitle).expect("restore window title");
+        self.output.flush().expect("restore window title");
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::Deref for TitleTerminal<W> {
+    type Target = W;
+
+    fn deref(&self) -> &W {
+        &self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> ops::DerefMut for TitleTerminal<W> {
+    fn deref_mut(&mut self) -> &mut W {
+        &mut self.output
+    }
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> Write for TitleTerminal<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn write_vectored(&mut self, bufs: &[io::IoSlice]) -> io::Result<usize> {
+        self.output.write_vectored(bufs)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_set_title() {
+        assert_eq!(SetTitle("notes\x07\x1B]0;évil").to_string(), "\x1B]2;notes]0;évil\x07");
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_title_terminal() {
+        let mut output = Vec::new();
+        {
+            let mut term = TitleTerminal::from(&mut output);
+            term.set_title("x").unwrap();
+        }
+        assert_eq!(output, b"\x1B[22;0t\x1B]2;x\x07\x1B[23;0t");
+    }
+}
@@ -0,0 +1,40 @@
+//! Running in the browser, on top of [xterm.js](https://xtermjs.org).
+//!
+//! On `wasm32-unknown-unknown`, the terminal is a JavaScript callback: the module imports
+//! `termion_write(data, len)` from `env`, which receives the output as a pointer into the
+//! module's memory and a length. Input and size changes are fed back through the exports
+//! `termion_input_buffer()`, `termion_input(len)` and `termion_resize(width, height)`, or through
+//! `feed_input` and `set_size` from Rust (e.g. with `wasm-bindgen`).
+//!
+//! Write to `get_tty()` rather than `stdout()`, which goes nowhere in the browser. Reading never
+//! blocks, so queries waiting for a reply from the terminal (such as `DetectCursorPos`) don't
+//! work: input only arrives once control returns to the browser. The `prompt` and `event_loop`
+//! modules, which wait for input, are left out.
+//!
+//! # Example
+//!
+//! ```js
+//! const term = new Terminal();
+//! let memory;
+//! const { instance } = await WebAssembly.instantiateStreaming(fetch("app.wasm"), {
+//!     env: {
+//!         termion_write: (data, len) => term.write(new Uint8Array(memory.buffer, data, len)),
+//!     },
+//! });
+//! memory = instance.exports.memory;
+//!
+//! const encoder = new TextEncoder();
+//! term.onData(data => {
+//!     for (let input = encoder.encode(data); input.length > 0; input = input.subarray(4096)) {
+//!         const chunk = input.subarray(0, 4096);
+//!         new Uint8Array(memory.buffer, instance.exports.termion_input_buffer(), chunk.length)
+//!             .set(chunk);
+//!         instance.exports.termion_input(chunk.length);
+//!     }
+//! });
+//! term.onResize(({ cols, rows }) => instance.exports.termion_resize(cols, rows));
+//! ```
+
+pub use sys::{AsRawFd, RawFd, INPUT_BUFFER_SIZE, feed_input, set_size, write_output};
+pub use sys::{termion_input, termion_input_buffer, termion_resize};
+pub use sys::tty::Tty;
@@ -0,0 +1,589 @@
+//! The number of columns text takes on a terminal.
+//!
+//! Most characters take one column, East Asian wide characters and most emoji take two, and
+//! combining marks take none, being drawn over the character before them. The tables cover the
+//! common ranges rather than the whole of Unicode's East Asian Width property, so terminals may
+//! disagree on rare characters.
+//!
+//! Text is measured by grapheme cluster, so that an emoji made of several characters (joined by
+//! ZWJ, with a skin tone modifier, or a flag made of two regional indicators) takes the columns
+//! of a single emoji. The segmentation follows the rules of UAX #29 which matter for terminals,
+//! leaving out the rare ones such as Hangul syllables spelled in conjoining jamo.
+//!
+//! The characters of East Asian ambiguous width, such as Greek and Cyrillic letters, box drawing
+//! characters or `§`, take one column on most terminals, but two on terminals set up for CJK
+//! text. They take one column unless `set_ambiguous_width` says otherwise, for every piece of
+//! the crate measuring text, and `DetectAmbiguousWidth` asks the terminal which it uses.
+//!
+//! ```rust
+//! use termion::width;
+//!
+//! assert_eq!(width::str_width("abc"), 3);
+//! assert_eq!(width::str_width("日本"), 4);
+//! assert_eq!(width::str_width("e\u{301}"), 1);
+//! assert_eq!(width::str_width("\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}"), 2);
+//! ```
+
+use std::cmp::Ordering;
+#[cfg(feature = "std")]
+use std::io::{self, Error, ErrorKind, Read, Write};
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+
+#[cfg(feature = "std")]
+use async::async_stdin;
+#[cfg(feature = "std")]
+use cursor::query_cursor_pos;
+
+/// Ranges of characters taking no column.
+static ZERO_WIDTH: &[(u32, u32)] = &[(0x0300, 0x036F),
+                                     (0x0483, 0x0489),
+                                     (0x0591, 0x05BD),
+                                     (0x05BF, 0x05BF),
+                                     (0x05C1, 0x05C2),
+                                     (0x05C4, 0x05C5),
+                                     (0x05C7, 0x05C7),
+                                     (0x0610, 0x061A),
+                                     (0x064B, 0x065F),
+                                     (0x0670, 0x0670),
+                                     (0x06D6, 0x06DC),
+                                     (0x06DF, 0x06E4),
+                                     (0x06E7, 0x06E8),
+                                     (0x06EA, 0x06ED),
+                                     (0x0900, 0x0902),
+                                     (0x093A, 0x093A),
+                                     (0x093C, 0x093C),
+                                     (0x0941, 0x0948),
+                                     (0x094D, 0x094D),
+                                     (0x0951, 0x0957),
+                                     (0x0E31, 0x0E31),
+                                     (0x0E34, 0x0E3A),
+                                     (0x0E47, 0x0E4E),
+                                     (0x1160, 0x11FF),
+                                     (0x1AB0, 0x1AFF),
+                                     (0x1DC0, 0x1DFF),
+                                     (0x200B, 0x200F),
+                                     (0x202A, 0x202E),
+                                     (0x2060, 0x2064),
+                                     (0x20D0, 0x20FF),
+                                     (0xFE00, 0xFE0F),
+                                     (0xFE20, 0xFE2F),
+                                     (0xFEFF, 0xFEFF),
+                                     (0x1F3FB, 0x1F3FF),
+                                     (0xE0000, 0xE007F),
+                                     (0xE0100, 0xE01EF)];
+
+/// Ranges of characters taking two columns.
+static WIDE: &[(u32, u32)] = &[(0x1100, 0x115F),
+                               (0x231A, 0x231B),
+                               (0x2329, 0x232A),
+                               (0x23E9, 0x23EC),
+                               (0x23F0, 0x23F0),
+                               (0x23F3, 0x23F3),
+                               (0x25FD, 0x25FE),
+                               (0x2614, 0x2615),
+                               (0x2648, 0x2653),
+                               (0x267F, 0x267F),
+                               (0x2693, 0x2693),
+                               (0x26A1, 0x26A1),
+                               (0x26AA, 0x26AB),
+                               (0x26BD, 0x26BE),
+                               (0x26C4, 0x26C5),
+                               (0x26CE, 0x26CE),
+                               (0x26D4, 0x26D4),
+                               (0x26EA, 0x26EA),
+                               (0x26F2, 0x26F3),
+                               (0x26F5, 0x26F5),
+                               (0x26FA, 0x26FA),
+                               (0x26FD, 0x26FD),
+                               (0x2705, 0x2705),
+                               (0x270A, 0x270B),
+                               (0x2728, 0x2728),
+                               (0x274C, 0x274C),
+                               (0x274E, 0x274E),
+                               (0x2753, 0x2755),
+                               (0x2757, 0x2757),
+                               (0x2795, 0x2797),
+                               (0x27B0, 0x27B0),
+                               (0x27BF, 0x27BF),
+                               (0x2B1B, 0x2B1C),
+                               (0x2B50, 0x2B50),
+                               (0x2B55, 0x2B55),
+                               (0x2E80, 0x303E),
+                               (0x3041, 0x33FF),
+                               (0x3400, 0x4DBF),
+                               (0x4E00, 0x9FFF),
+                               (0xA000, 0xA4CF),
+                               (0xA960, 0xA97F),
+                               (0xAC00, 0xD7A3),
+                               (0xF900, 0xFAFF),
+                               (0xFE10, 0xFE19),
+                               (0xFE30, 0xFE6F),
+                               (0xFF00, 0xFF60),
+                               (0xFFE0, 0xFFE6),
+                               (0x16FE0, 0x16FE4),
+                               (0x17000, 0x18CFF),
+                               (0x1B000, 0x1B2FF),
+                               (0x1F004, 0x1F004),
+                               (0x1F0CF, 0x1F0CF),
+                               (0x1F18E, 0x1F18E),
+                               (0x1F191, 0x1F19A),
+                               (0x1F200, 0x1F251),
+                               (0x1F300, 0x1F320),
+                               (0x1F32D, 0x1F335),
+                               (0x1F337, 0x1F37C),
+                               (0x1F37E, 0x1F393),
+                               (0x1F3A0, 0x1F3CA),
+                               (0x1F3CF, 0x1F3D3),
+                               (0x1F3E0, 0x1F3F0),
+                               (0x1F3F4, 0x1F3F4),
+                               (0x1F3F8, 0x1F3FA),
+                               (0x1F400, 0x1F43E),
+                               (0x1F440, 0x1F440),
+                               (0x1F442, 0x1F4FC),
+                               (0x1F4FF, 0x1F53D),
+                               (0x1F54B, 0x1F54E),
+                               (0x1F550, 0x1F567),
+                               (0x1F57A, 0x1F57A),
+                               (0x1F595, 0x1F596),
+                               (0x1F5A4, 0x1F5A4),
+                               (0x1F5FB, 0x1F64F),
+                               (0x1F680, 0x1F6C5),
+                               (0x1F6CC, 0x1F6CC),
+                               (0x1F6D0, 0x1F6D2),
+                               (0x1F6D5, 0x1F6D7),
+                               (0x1F6EB, 0x1F6EC),
+                               (0x1F6F4, 0x1F6FC),
+                               (0x1F7E0, 0x1F7EB),
+                               (0x1F90C, 0x1F93A),
+                               (0x1F93C, 0x1F945),
+                               (0x1F947, 0x1F9FF),
+                               (0x1FA70, 0x1FAFF),
+                               (0x20000, 0x2FFFD),
+                               (0x30000, 0x3FFFD)];
+
+/// Ranges of characters of East Asian ambiguous width, leaving out those taking no column.
+static AMBIGUOUS: &[(u32, u32)] = &[(0x00A1, 0x00A1),
+                                    (0x00A4, 0x00A4),
+                                    (0x00A7, 0x00A8),
+                                    (0x00AA, 0x00AA),
+                                    (0x00AE, 0x00AE),
+                                    (0x00B0, 0x00B4),
+                                    (0x00B6, 0x00BA),
+                                    (0x00BC, 0x00BF),
+                                    (0x00C6, 0x00C6),
+                                    (0x00D0, 0x00D0),
+                                    (0x00D7, 0x00D8),
+                                    (0x00DE, 0x00E1),
+                                    (0x00E6, 0x00E6),
+                                    (0x00E8, 0x00EA),
+                                    (0x00EC, 0x00ED),
+                                    (0x00F0, 0x00F0),
+                                    (0x00F2, 0x00F3),
+                                    (0x00F7, 0x00FA),
+                                    (0x00FC, 0x00FC),
+                                    (0x00FE, 0x00FE),
+                                    (0x0101, 0x0101),
+                                    (0x0111, 0x0111),
+                                    (0x0113, 0x0113),
+                                    (0x011B, 0x011B),
+                                    (0x0126, 0x0127),
+                                    (0x012B, 0x012B),
+                                    (0x0131, 0x0133),
+                                    (0x0138, 0x0138),
+                                    (0x013F, 0x0142),
+                                    (0x0144, 0x0144),
+                                    (0x0148, 0x014B),
+                                    (0x014D, 0x014D),
+                                    (0x0152, 0x0153),
+                                    (0x0166, 0x0167),
+                                    (0x016B, 0x016B),
+                                    (0x01CE, 0x01CE),
+                                    (0x01D0, 0x01D0),
+                                    (0x01D2, 0x01D2),
+                                    (0x01D4, 0x01D4),
+                                    (0x01D6, 0x01D6),
+                                    (0x01D8, 0x01D8),
+                                    (0x01DA, 0x01DA),
+                                    (0x01DC, 0x01DC),
+                                    (0x0251, 0x0251),
+                                    (0x0261, 0x0261),
+                                    (0x02C4, 0x02C4),
+                                    (0x02C7, 0x02C7),
+                                    (0x02C9, 0x02CB),
+                                    (0x02CD, 0x02CD),
+                                    (0x02D0, 0x02D0),
+                                    (0x02D8, 0x02DB),
+                                    (0x02DD, 0x02DD),
+                                    (0x02DF, 0x02DF),
+                                    (0x0391, 0x03A9),
+                                    (0x03B1, 0x03C1),
+                                    (0x03C3, 0x03C9),
+                                    (0x0401, 0x0401),
+                                    (0x0410, 0x044F),
+                                    (0x0451, 0x0451),
+                                    (0x2010, 0x2010),
+                                    (0x2013, 0x2016),
+                                    (0x2018, 0x2019),
+                                    (0x201C, 0x201D),
+                                    (0x2020, 0x2022),
+                                    (0x2024, 0x2027),
+                                    (0x2030, 0x2030),
+                                    (0x2032, 0x2033),
+                                    (0x2035, 0x2035),
+                                    (0x203B, 0x203B),
+                                    (0x203E, 0x203E),
+                                    (0x2074, 0x2074),
+                                    (0x207F, 0x207F),
+                                    (0x2081, 0x2084),
+                                    (0x20AC, 0x20AC),
+                                    (0x2103, 0x2103),
+                                    (0x2105, 0x2105),
+                                    (0x2109, 0x2109),
+                                    (0x2113, 0x2113),
+                                    (0x2116, 0x2116),
+                                    (0x2121, 0x2122),
+                                    (0x2126, 0x2126),
+                                    (0x212B, 0x212B),
+                                    (0x2153, 0x2154),
+                                    (0x215B, 0x215E),
+                                    (0x2160, 0x216B),
+                                    (0x2170, 0x2179),
+                                    (0x2189, 0x2189),
+                                    (0x2190, 0x2199),
+                                    (0x21B8, 0x21B9),
+                                    (0x21D2, 0x21D2),
+                                    (0x21D4, 0x21D4),
+                                    (0x21E7, 0x21E7),
+                                    (0x2200, 0x2200),
+                                    (0x2202, 0x2203),
+                                    (0x2207, 0x2208),
+                                    (0x220B, 0x220B),
+                                    (0x220F, 0x220F),
+                                    (0x2211, 0x2211),
+                                    (0x2215, 0x2215),
+                                    (0x221A, 0x221A),
+                                    (0x221D, 0x2220),
+                                    (0x2223, 0x2223),
+                                    (0x2225, 0x2225),
+                                    (0x2227, 0x222C),
+                                    (0x222E, 0x222E),
+                                    (0x2234, 0x2237),
+                                    (0x223C, 0x223D),
+                                    (0x2248, 0x2248),
+                                    (0x224C, 0x224C),
+                                    (0x2252, 0x2252),
+                                    (0x2260, 0x2261),
+                                    (0x2264, 0x2267),
+                                    (0x226A, 0x226B),
+                                    (0x226E, 0x226F),
+                                    (0x2282, 0x2283),
+                                    (0x2286, 0x2287),
+                                    (0x2295, 0x2295),
+                                    (0x2299, 0x2299),
+                                    (0x22A5, 0x22A5),
+                                    (0x22BF, 0x22BF),
+                                    (0x2312, 0x2312),
+                                    (0x2460, 0x24E9),
+                                    (0x24EB, 0x254B),
+                                    (0x2550, 0x2573),
+                                    (0x2580, 0x258F),
+                                    (0x2592, 0x2595),
+                                    (0x25A0, 0x25A1),
+                                    (0x25A3, 0x25A9),
+                                    (0x25B2, 0x25B3),
+                                    (0x25B6, 0x25B7),
+                                    (0x25BC, 0x25BD),
+                                    (0x25C0, 0x25C1),
+                                    (0x25C6, 0x25C8),
+                                    (0x25CB, 0x25CB),
+                                    (0x25CE, 0x25D1),
+                                    (0x25E2, 0x25E5),
+                                    (0x25EF, 0x25EF),
+                                    (0x2605, 0x2606),
+                                    (0x2609, 0x2609),
+                                    (0x260E, 0x260F),
+                                    (0x261C, 0x261C),
+                                    (0x261E, 0x261E),
+                                    (0x2640, 0x2640),
+                                    (0x2642, 0x2642),
+                                    (0x2660, 0x2661),
+                                    (0x2663, 0x2665),
+                                    (0x2667, 0x266A),
+                                    (0x266C, 0x266D),
+                                    (0x266F, 0x266F),
+                                    (0x269E, 0x269F),
+                                    (0x26BF, 0x26BF),
+                                    (0x26C6, 0x26CD),
+                                    (0x26CF, 0x26D3),
+                                    (0x26D5, 0x26E1),
+                                    (0x26E3, 0x26E3),
+                                    (0x26E8, 0x26E9),
+                                    (0x26EB, 0x26F1),
+                                    (0x26F4, 0x26F4),
+                                    (0x26F6, 0x26F9),
+                                    (0x26FB, 0x26FC),
+                                    (0x26FE, 0x26FF),
+                                    (0x273D, 0x273D),
+                                    (0x2776, 0x277F),
+                                    (0x2B56, 0x2B59),
+                                    (0x3248, 0x324F),
+                                    (0xE000, 0xF8FF),
+                                    (0xFFFD, 0xFFFD),
+                                    (0x1F100, 0x1F10A),
+                                    (0x1F110, 0x1F12D),
+                                    (0x1F130, 0x1F169),
+                                    (0x1F170, 0x1F18D),
+                                    (0x1F18F, 0x1F190),
+                                    (0x1F19B, 0x1F1AC),
+                                    (0xF0000, 0x10FFFD)];
+
+/// Do the characters of ambiguous width take two columns?
+static AMBIGUOUS_WIDE: AtomicBool = AtomicBool::new(false);
+
+/// The number of columns the characters of ambiguous width take: 1, unless set otherwise.
+pub fn ambiguous_width() -> usize {
+    if AMBIGUOUS_WIDE.load(AtomicOrdering::Relaxed) { 2 } else { 1 }
+}
+
+/// Set the number of columns the characters of ambiguous width take, which is 1 or 2.
+///
+/// This is the width used by the whole crate, so it should match the terminal being written to.
+pub fn set_ambiguous_width(width: usize) {
+    AMBIGUOUS_WIDE.store(width >= 2, AtomicOrdering::Relaxed);
+}
+
+/// Is `c` in one of the sorted `ranges`?
+fn in_ranges(c: u32, ranges: &[(u32, u32)]) -> bool {
+    ranges.binary_search_by(|&(start, end)| if end < c {
+                                  Ordering::Less
+                              } else if start > c {
+                                  Ordering::Greater
+                              } else {
+                                  Ordering::Equal
+                              })
+        .is_ok()
+}
+
+/// The number of columns `c` takes.
+///
+/// Control characters take no column, as they are not drawn.
+pub fn char_width(c: char) -> usize {
+    char_width_with(c, ambiguous_width())
+}
+
+/// The number of columns `c` takes, if the characters of ambiguous width take `ambiguous`.
+fn char_width_with(c: char, ambiguous: usize) -> usize {
+    let c = c as u32;
+    if c < 0x20 || (0x7F..0xA0).contains(&c) {
+        0
+    } else if c < 0xA1 {
+        1
+    } else if in_ranges(c, ZERO_WIDTH) {
+        0
+    } else if in_ranges(c, WIDE) {
+        2
+    } else if in_ranges(c, AMBIGUOUS) {
+        ambiguous
+    } else {
+        1
+    }
+}
+
+/// Detecting the width of the characters of ambiguous width.
+#[cfg(feature = "std")]
+pub trait DetectAmbiguousWidth {
+    /// Ask the terminal how many columns the characters of ambiguous width take, and make the
+    /// crate use it with `set_ambiguous_width`.
+    ///
+    /// A character is written at the start of the cursor's line, and erased once the terminal
+    /// reported where it left the cursor, which is then moved back. This blanks the first two
+    /// columns of that line, so it is best done before drawing, or on an empty line.
+    ///
+    /// Beware: the terminal must be in raw mode, or the reply is only read once the user presses
+    /// enter.
+    fn detect_ambiguous_width(&mut self) -> io::Result<usize>;
+}
+
+#[cfg(feature = "std")]
+impl<W: Write> DetectAmbiguousWidth for W {
+    fn detect_ambiguous_width(&mut self) -> io::Result<usize> {
+        let mut stdin = async_stdin();
+        let width = query_ambiguous_width(self, &mut stdin)?;
+        set_ambiguous_width(width);
+        Ok(width)
+    }
+}
+
+/// Measure the width of a character of ambiguous width, reading the reply from `stdin`.
+#[cfg(feature = "std")]
+pub(crate) fn query_ambiguous_width(stdout: &mut dyn Write,
+                                    stdin: &mut dyn Read)
+                                    -> io::Result<usize> {
+    // U+25BD WHITE DOWN-POINTING TRIANGLE, which no terminal draws as an emoji. The cursor is
+    // saved, and the two columns the triangle takes at most are erased afterwards, whatever
+    // they held before.
+    write!(stdout, "\x1B7\r\u{25BD}")?;
+    let pos = query_cursor_pos(stdout, stdin);
+    write!(stdout, "\r\x1B[2X\x1B8")?;
+    stdout.flush()?;
+
+    match pos?.0 {
+        2 => Ok(1),
+        3 => Ok(2),
+        _ => Err(Error::new(ErrorKind::InvalidData, "Unexpected cursor position.")),
+    }
+}
+
+/// The number of columns `s` takes.
+pub fn str_width(s: &str) -> usize {
+    graphemes(s).map(grapheme_width).sum()
+}
+
+fn is_control(c: char) -> bool {
+    c < ' ' || ('\x7F'..'\u{A0}').contains(&c)
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1F1E6}'..='\u{1F1FF}').contains(&c)
+}
+
+/// Does `c` continue the grapheme cluster `cluster`, rather than start a new one?
+///
+/// Combining marks, variation selectors and emoji modifiers continue a cluster, and so does
+/// anything following a ZWJ. Regional indicators pair up into flags.
+pub fn extends_grapheme(cluster: &str, c: char) -> bool {
+    let prev = match cluster.chars().next_back() {
+        Some(prev) => prev,
+        None => return false,
+    };
+
+    if prev == '\r' {
+        c == '\n'
+    } else if is_control(prev) || is_control(c) {
+        false
+    } else if is_regional_indicator(c) {
+        is_regional_indicator(prev) &&
+        cluster.chars().filter(|&c| is_regional_indicator(c)).count() % 2 == 1
+    } else {
+        char_width(c) == 0 || prev == '\u{200D}'
+    }
+}
+
+/// The number of columns the grapheme cluster `grapheme` takes.
+///
+/// This is the width of its first character, except for flags and for emoji turned to their
+/// emoji presentation by U+FE0F, which take two columns.
+pub fn grapheme_width(grapheme: &str) -> usize {
+    let mut chars = grapheme.chars();
+    let first = match chars.next() {
+        Some(first) => first,
+        None => return 0,
+    };
+
+    let width = char_width(first);
+    if width == 1 && chars.any(|c| c == '\u{FE0F}' || is_regional_indicator(c)) {
+        2
+    } else {
+        width
+    }
+}
+
+/// Split `s` into grapheme clusters.
+pub fn graphemes(s: &str) -> Graphemes<'_> {
+    Graphemes { rest: s }
+}
+
+/// An iterator over the grapheme clusters of a string, returned by `graphemes`.
+#[derive(Clone, Debug)]
+pub struct Graphemes<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Graphemes<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let mut chars = self.rest.char_indices();
+        chars.next()?;
+        let end = chars.find(|&(i, c)| !extends_grapheme(&self.rest[..i], c))
+            .map_or(self.rest.len(), |(i, _)| i);
+
+        let (grapheme, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        Some(grapheme)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_tables_sorted() {
+        for table in &[ZERO_WIDTH, WIDE, AMBIGUOUS] {
+            for pair in table.windows(2) {
+                assert!(pair[0].0 <= pair[0].1 && pair[0].1 < pair[1].0);
+            }
+        }
+    }
+
+    #[test]
+    fn test_char_width() {
+        assert_eq!(char_width('a'), 1);
+        assert_eq!(char_width('é'), 1);
+        assert_eq!(char_width('\n'), 0);
+        assert_eq!(char_width('\u{301}'), 0);
+        assert_eq!(char_width('\u{200D}'), 0);
+        assert_eq!(char_width('日'), 2);
+        assert_eq!(char_width('한'), 2);
+        assert_eq!(char_width('Ａ'), 2);
+        assert_eq!(char_width('\u{1F600}'), 2);
+        assert_eq!(char_width('─'), 1);
+    }
+
+    #[test]
+    fn test_ambiguous_width() {
+        for &c in &['§', 'α', 'Ж', '─', '\u{E000}'] {
+            assert_eq!(char_width_with(c, 1), 1);
+            assert_eq!(char_width_with(c, 2), 2);
+        }
+        assert_eq!(char_width_with('a', 2), 1);
+        assert_eq!(char_width_with('ç', 2), 1);
+        assert_eq!(char_width_with('日', 2), 2);
+        assert_eq!(char_width_with('\u{301}', 2), 0);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn test_query_ambiguous_width() {
+        let mut out = Vec::new();
+        assert_eq!(query_ambiguous_width(&mut out, &mut &b"\x1B[4;3R"[..]).unwrap(), 2);
+        assert_eq!(out, "\x1B7\r\u{25BD}\x1B[6n\r\x1B[2X\x1B8".as_bytes());
+        assert_eq!(query_ambiguous_width(&mut Vec::new(), &mut &b"\x1B[4;2R"[..]).unwrap(), 1);
+        let err = query_ambiguous_width(&mut Vec::new(), &mut &b"\x1B[4;9R"[..]).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_graphemes() {
+        let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+        let thumbs = "\u{1F44D}\u{1F3FD}";
+        let flags = "\u{1F1EB}\u{1F1F7}\u{1F1EF}\u{1F1F5}";
+        let s = format!("ae\u{301}\r\n{}{}{}\u{2764}\u{FE0F}", family, thumbs, flags);
+
+        let clusters: Vec<&str> = graphemes(&s).collect();
+        assert_eq!(clusters,
+                   vec!["a",
+                        "e\u{301}",
+                        "\r\n",
+                        family,
+                        thumbs,
+                        &flags[..8],
+                        &flags[8..],
+                        "\u{2764}\u{FE0F}"]);
+
+        let widths: Vec<usize> = clusters.iter().map(|g| grapheme_width(g)).collect();
+        assert_eq!(widths, vec![1, 1, 0, 2, 2, 2, 2, 2]);
+        assert_eq!(str_width(&s), 12);
+    }
+}
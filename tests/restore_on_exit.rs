@@ -0,0 +1,61 @@
+//! `restore_on_exit` installs a hook for the whole process, so it is tested in a child process,
+//! running in a pseudo-terminal of its own.
+#![cfg(all(unix, feature = "std"))]
+
+extern crate termion;
+
+use std::env;
+use std::io::{self, Read, Write};
+use std::process;
+
+use termion::pty::Command;
+use termion::raw::{self, IntoRawMode};
+use termion::screen::AlternateScreen;
+
+/// Set in the environment of the child.
+const CHILD: &str = "TERMION_TEST_RESTORE_ON_EXIT";
+
+/// In the child, enter raw mode and the alternate screen, and exit without leaving them.
+#[test]
+fn exit_in_child() {
+    if env::var_os(CHILD).is_none() {
+        return;
+    }
+
+    raw::restore_on_exit().unwrap();
+    let mut stdout = AlternateScreen::from(io::stdout().into_raw_mode().unwrap());
+    write!(stdout, "ready").unwrap();
+    stdout.flush().unwrap();
+
+    // Exit once told to.
+    io::stdin().read_exact(&mut [0]).unwrap();
+    process::exit(0);
+}
+
+#[test]
+fn test_restore_on_exit() {
+    let mut child = Command::new(env::current_exe().unwrap())
+        .args(["--exact", "exit_in_child", "--nocapture"])
+        .env(CHILD, "1")
+        .spawn()
+        .unwrap();
+
+    let mut output = Vec::new();
+    let mut buf = [0; 256];
+    while !String::from_utf8_lossy(&output).contains("ready") {
+        let n = child.read(&mut buf).unwrap();
+        assert!(n > 0, "the child exited early");
+        output.extend_from_slice(&buf[..n]);
+    }
+    assert!(raw::is_raw_mode_enabled_on(child.master()));
+
+    child.write_all(b"\n").unwrap();
+    // Reading the master side fails once the child has exited.
+    let _ = child.read_to_end(&mut output);
+    assert!(child.wait().unwrap().success());
+
+    let output = String::from_utf8_lossy(&output);
+    let ready = output.find("ready").unwrap();
+    assert!(output[ready..].contains("\x1B[?1049l"));
+    assert!(!raw::is_raw_mode_enabled_on(child.master()));
+}